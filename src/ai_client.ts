@@ -1,77 +1,1297 @@
-import Anthropic from '@anthropic-ai/sdk';
+import Anthropic, { ClientOptions } from '@anthropic-ai/sdk';
+import axios from 'axios';
+import FormData from 'form-data';
+import sharp from 'sharp';
+import { StringDecoder } from 'string_decoder';
+import { AppConfig } from './config';
+import { AwsCredentials, loadAwsCredentials, signAwsRequest } from './aws_sigv4';
+import { CancelledError, ConfigError, ProviderAuthError, RateLimitError, TimeoutError } from './errors';
+import { redactSecrets } from './redact';
+import { RequestLimiter } from './request_limiter';
+import { proxyAgent, proxyDispatcher, proxyFor } from './proxy';
+import { connectTimeoutTransport } from './connect_timeout';
 
-export class AIClient {
+export const DEFAULT_MAX_TOKENS = 500;
+export const DEFAULT_TEMPERATURE = 0.1;
+export const CLAUDE_MODEL = 'claude-3-5-sonnet-20241022';
+export const GEMINI_MODEL = 'gemini-1.5-flash';
+export const GROQ_MODEL = 'meta-llama/llama-4-scout-17b-16e-instruct';
+export const OPENAI_MODEL = 'gpt-4o-mini';
+// Speech-to-text for voice questions
+const TRANSCRIPTION_MODEL = 'whisper-1';
+// llama-server serves whichever model it was started with and ignores the name
+export const LLAMACPP_MODEL = 'local';
+export const DEFAULT_MAX_CONCURRENT_REQUESTS = 1;
+export const DEFAULT_MAX_QUEUED_REQUESTS = 2;
+
+export const IMAGE_DETAIL_LEVELS = ['low', 'high', 'auto'] as const;
+export type ImageDetail = typeof IMAGE_DETAIL_LEVELS[number];
+
+// OpenAI's "low" detail looks at a 512x512 version of the image
+const LOW_DETAIL_SIZE = 512;
+
+export const REQUEST_TIMEOUT_MS = 60000;
+export const CONNECT_TIMEOUT_MS = 10000;
+
+const SYSTEM_PROMPT = 'You are a concise programming assistant. Provide direct, minimal responses. For coding problems, give working code in markdown blocks without extra explanation. For questions, give brief, direct answers.';
+
+export interface AnalyzeOptions {
+    maxTokens?: number;
+    /** Stream the answer, calling this with each chunk of text as it arrives */
+    onToken?: (text: string) => void;
+    /** Ask for a `StructuredAnswer` JSON object instead of markdown */
+    structured?: boolean;
+    /** Ask for a JSON object matching this schema instead; the question is the whole task */
+    schema?: object;
+    /** Aborts the request, which then fails with a `CancelledError` */
+    signal?: AbortSignal;
+    /** Describes how the images relate, e.g. frames of a burst; sent before the prompt */
+    context?: string;
+    /** Called as the request moves on: uploading (with the image bytes), then waiting on the model */
+    onStage?: (stage: RequestStage, bytes?: number) => void;
+}
+
+export type RequestStage = 'uploading' | 'waiting';
+
+/**
+ * A machine-readable answer, for tooling that shouldn't have to parse
+ * markdown. Fields that don't apply are empty strings.
+ */
+export interface StructuredAnswer {
+    problem: string;
+    language: string;
+    solution_code: string;
+    explanation: string;
+}
+
+export const STRUCTURED_ANSWER_SCHEMA = {
+    type: 'object',
+    properties: {
+        problem: { type: 'string', description: 'What is on screen or what the question asks, in one sentence' },
+        language: { type: 'string', description: 'Programming language of the solution, or an empty string' },
+        solution_code: { type: 'string', description: 'Complete working code without markdown fences, or an empty string' },
+        explanation: { type: 'string', description: 'Brief answer or explanation' }
+    },
+    required: ['problem', 'language', 'solution_code', 'explanation'],
+    additionalProperties: false
+};
+
+export interface AIClientOptions {
+    dryRun?: boolean;
+    config?: AppConfig;
+    model?: string;
+    temperature?: number;
+    topP?: number;
+    maxConcurrentRequests?: number;
+    maxQueuedRequests?: number;
+    detail?: ImageDetail;
+    /** Language to answer in, whatever language is on screen */
+    language?: string;
+}
+
+/** List pricing in USD per million tokens */
+export interface ModelPricing {
+    input: number;
+    output: number;
+}
+
+// List prices for models we know about; dry runs report "unknown" otherwise
+const MODEL_PRICING: Record<string, ModelPricing> = {
+    'claude-3-5-sonnet-20241022': { input: 3, output: 15 },
+    'claude-3-5-haiku-20241022': { input: 0.8, output: 4 },
+    'anthropic.claude-3-5-sonnet-20241022-v2:0': { input: 3, output: 15 },
+    'gemini-1.5-flash': { input: 0.075, output: 0.3 },
+    'gemini-1.5-pro': { input: 1.25, output: 5 },
+    'meta-llama/llama-4-scout-17b-16e-instruct': { input: 0.11, output: 0.34 },
+    'gpt-4o': { input: 2.5, output: 10 },
+    'gpt-4o-mini': { input: 0.15, output: 0.6 },
+    'o4-mini': { input: 1.1, output: 4.4 },
+};
+
+/** Network settings shared by every provider */
+export interface HttpSettings {
+    /** Proxy URL; falls back to HTTPS_PROXY / HTTP_PROXY / ALL_PROXY */
+    proxy?: string;
+    /** How long a request may go without any response data */
+    requestTimeoutMs?: number;
+    /** How long establishing the connection may take */
+    connectTimeoutMs?: number;
+}
+
+/**
+ * Everything a provider needs to run one vision request.
+ */
+export interface AnalysisRequest {
+    images: Buffer[];
+    prompt: string;
+    systemPrompt: string;
+    maxTokens: number;
+    temperature: number;
+    topP?: number;
+    detail?: ImageDetail;
+    onToken?: (text: string) => void;
+    /** Constrain the reply to JSON matching this schema, where the API supports it */
+    jsonSchema?: object;
+    /** Later turns of a conversation, following the first message with the images */
+    followUps?: ConversationTurn[];
+    /** Called with token counts, for providers that report them */
+    onUsage?: (usage: TokenUsage) => void;
+    /** Called once the request body has been sent, for providers that report uploads */
+    onSent?: () => void;
+    signal?: AbortSignal;
+}
+
+export interface TokenUsage {
+    inputTokens: number;
+    outputTokens: number;
+    /** Input tokens served from the provider's prompt cache */
+    cacheReadTokens?: number;
+    /** Input tokens written to the provider's prompt cache */
+    cacheWriteTokens?: number;
+}
+
+export interface ConversationTurn {
+    role: 'user' | 'assistant';
+    text: string;
+}
+
+/**
+ * A vision-capable AI backend. Providers turn an `AnalysisRequest` into an
+ * API call and map failures onto the error classes in `errors.ts`.
+ */
+export interface AIProvider {
+    readonly name: string;
+    readonly model: string;
+    readonly pricing?: ModelPricing;
+    /** Whether `analyze` honors `request.onToken` by streaming the response */
+    readonly supportsStreaming: boolean;
+    /** Whether the API takes `request.detail`; otherwise low detail is done by downscaling */
+    readonly supportsImageDetail?: boolean;
+    /** Whether `analyze` calls `request.onSent` once the upload is done */
+    readonly reportsUpload?: boolean;
+    analyze(request: AnalysisRequest): Promise<string>;
+    /** Validate the credentials with a cheap call that doesn't run a model */
+    checkKey(): Promise<KeyCheck>;
+}
+
+/** What a provider reports about the credentials in use */
+export interface KeyCheck {
+    /** Models the credentials can access */
+    models: string[];
+    organization?: string;
+    /** Rate limit headers from the response, lowercased */
+    rateLimits: Record<string, string>;
+}
+
+/**
+ * Options for the Anthropic SDK client. It's fetch-based, so the proxy and
+ * connect timeout go in as an undici dispatcher.
+ */
+export function claudeClientOptions(apiKey: string, http: HttpSettings = {}): ClientOptions {
+    const proxy = proxyFor('https://api.anthropic.com', http.proxy);
+    return {
+        apiKey: apiKey,
+        fetchOptions: { dispatcher: proxyDispatcher(proxy, http.connectTimeoutMs ?? CONNECT_TIMEOUT_MS) },
+        timeout: http.requestTimeoutMs ?? REQUEST_TIMEOUT_MS,
+    };
+}
+
+export class ClaudeProvider implements AIProvider {
+    readonly name = 'claude';
+    readonly supportsStreaming = true;
+    readonly pricing?: ModelPricing;
     private client: Anthropic;
-    private apiKey: string;
 
-    constructor(provider: string, apiKey: string) {
-        this.apiKey = apiKey;
-        this.client = new Anthropic({
-            apiKey: apiKey,
-        });
+    constructor(
+        apiKey: string,
+        readonly model: string = CLAUDE_MODEL,
+        http: HttpSettings = {},
+        private promptCaching: boolean = true
+    ) {
+        this.pricing = MODEL_PRICING[model];
+        this.client = new Anthropic(claudeClientOptions(apiKey, http));
     }
 
-    provider(): string {
-        return 'claude'; // Always return claude since we only support Claude now
+    async analyze(request: AnalysisRequest): Promise<string> {
+        const params: Anthropic.MessageCreateParamsNonStreaming = {
+            model: this.model,
+            max_tokens: request.maxTokens,
+            temperature: request.temperature,
+            ...(request.topP !== undefined ? { top_p: request.topP } : {}),
+            system: request.systemPrompt,
+            messages: [
+                {
+                    role: 'user',
+                    content: [
+                        { type: 'text', text: request.prompt },
+                        // The prompts alone are far below the 1024-token minimum
+                        // cacheable prefix, so the only breakpoint is after the
+                        // images. Caching them costs extra up front, so only do it
+                        // once a follow-up shows they'll be sent again.
+                        ...request.images.map((imageData, i) => ({
+                            ...this.imageBlock(imageData),
+                            ...this.cacheControl(!!request.followUps?.length && i === request.images.length - 1)
+                        }))
+                    ]
+                },
+                ...(request.followUps || []).map(turn => ({ role: turn.role, content: turn.text }))
+            ]
+        };
+
+        try {
+            if (request.onToken) {
+                return await this.analyzeStreaming(params, request.onToken, request.onUsage, request.signal);
+            }
+
+            const response = await this.client.messages.create(params, { signal: request.signal });
+            request.onUsage?.(this.usage(response.usage));
+
+            const content = response.content[0];
+            if (!content || content.type !== 'text') {
+                throw new Error('No text response from Claude');
+            }
+
+            // Return the raw response without additional formatting
+            return content.text.trim();
+        } catch (error) {
+            throw this.mapError(error);
+        }
     }
 
-    async analyzeImage(imageData: Buffer, userQuestion?: string): Promise<string> {
-        return this.analyzeWithClaude(imageData, userQuestion);
+    async checkKey(): Promise<KeyCheck> {
+        try {
+            const { data, response } = await this.client.models.list({ limit: 100 }).withResponse();
+            return {
+                models: data.data.map(model => model.id),
+                organization: response.headers.get('anthropic-organization-id') || undefined,
+                rateLimits: rateLimitHeaders(response.headers)
+            };
+        } catch (error) {
+            throw this.mapError(error);
+        }
     }
 
-    private async analyzeWithClaude(imageData: Buffer, userQuestion?: string): Promise<string> {
+    private async analyzeStreaming(
+        params: Anthropic.MessageCreateParamsNonStreaming,
+        onToken: (text: string) => void,
+        onUsage?: (usage: TokenUsage) => void,
+        signal?: AbortSignal
+    ): Promise<string> {
+        const stream = await this.client.messages.create({ ...params, stream: true }, { signal });
+
+        let text = '';
+        let usage: TokenUsage | undefined;
+        for await (const event of stream) {
+            if (event.type === 'message_start') {
+                // Input and cache counts arrive first, output counts at the end
+                usage = this.usage(event.message.usage);
+            } else if (event.type === 'message_delta' && usage) {
+                usage.outputTokens = event.usage.output_tokens;
+            } else if (event.type === 'content_block_delta' && event.delta.type === 'text_delta') {
+                text += event.delta.text;
+                onToken(event.delta.text);
+            }
+        }
+        if (usage) {
+            onUsage?.(usage);
+        }
+
+        if (!text) {
+            throw new Error('No text response from Claude');
+        }
+        return text.trim();
+    }
+
+    /**
+     * Marks a cache breakpoint: everything up to and including the block is
+     * cached, so repeated requests sharing that prefix read it at a discount.
+     * Prefixes under the model's minimum cacheable length (1024 tokens, 2048
+     * for Haiku) are not cached.
+     */
+    private cacheControl(enabled: boolean): { cache_control?: Anthropic.CacheControlEphemeral } {
+        return this.promptCaching && enabled ? { cache_control: { type: 'ephemeral' } } : {};
+    }
+
+    private usage(usage: Anthropic.Usage): TokenUsage {
+        return {
+            inputTokens: usage.input_tokens,
+            outputTokens: usage.output_tokens,
+            cacheReadTokens: usage.cache_read_input_tokens ?? undefined,
+            cacheWriteTokens: usage.cache_creation_input_tokens ?? undefined
+        };
+    }
+
+    private imageBlock(imageData: Buffer): Anthropic.ImageBlockParam {
+        return {
+            type: 'image',
+            source: {
+                type: 'base64',
+                // Detect image format for proper MIME type
+                media_type: detectImageFormat(imageData) as 'image/jpeg' | 'image/png' | 'image/gif' | 'image/webp',
+                // Encode image as base64 for Claude Vision API
+                data: imageData.toString('base64')
+            }
+        };
+    }
+
+    private mapError(error: unknown): unknown {
+        if (error instanceof Anthropic.APIConnectionTimeoutError) {
+            return new TimeoutError(`Claude API error: request timed out`);
+        }
+        if (error instanceof Anthropic.AuthenticationError || error instanceof Anthropic.PermissionDeniedError) {
+            return new ProviderAuthError(`Claude API error: ${redactSecrets(error.message)}`);
+        }
+        if (error instanceof Anthropic.RateLimitError) {
+            return new RateLimitError(`Claude API error: ${redactSecrets(error.message)}`, retryAfterMs(error.headers));
+        }
+        if (error instanceof Anthropic.APIError) {
+            return new Error(`Claude API error: ${redactSecrets(error.message)}`);
+        }
+        return error;
+    }
+}
+
+export class GeminiProvider implements AIProvider {
+    readonly name = 'gemini';
+    readonly supportsStreaming = true;
+    readonly reportsUpload = true;
+    readonly pricing?: ModelPricing;
+
+    constructor(private apiKey: string, readonly model: string = GEMINI_MODEL, private http: HttpSettings = {}) {
+        this.pricing = MODEL_PRICING[model];
+    }
+
+    async checkKey(): Promise<KeyCheck> {
+        const url = 'https://generativelanguage.googleapis.com/v1beta/models';
         try {
-            // Encode image as base64 for Claude Vision API
-            const base64Image = imageData.toString('base64');
+            const response = await axios.get(url, {
+                headers: { 'x-goog-api-key': this.apiKey },
+                params: { pageSize: 1000 },
+                ...httpOptions(url, this.http)
+            });
+            return {
+                models: (response.data?.models || []).map((model: { name: string }) => model.name.replace(/^models\//, '')),
+                rateLimits: rateLimitHeaders(response.headers)
+            };
+        } catch (error) {
+            throw mapHttpError('Gemini', error);
+        }
+    }
+
+    async analyze(request: AnalysisRequest): Promise<string> {
+        const baseUrl = `https://generativelanguage.googleapis.com/v1beta/models/${this.model}`;
+        const body = {
+            systemInstruction: {
+                parts: [{ text: request.systemPrompt }]
+            },
+            contents: [
+                {
+                    role: 'user',
+                    parts: [
+                        { text: request.prompt },
+                        ...request.images.map(imageData => ({
+                            inline_data: {
+                                mime_type: detectImageFormat(imageData),
+                                data: imageData.toString('base64')
+                            }
+                        }))
+                    ]
+                },
+                // Gemini calls the assistant role "model"
+                ...(request.followUps || []).map(turn => ({
+                    role: turn.role === 'assistant' ? 'model' : 'user',
+                    parts: [{ text: turn.text }]
+                }))
+            ],
+            generationConfig: {
+                maxOutputTokens: request.maxTokens,
+                temperature: request.temperature,
+                topP: request.topP,
+                ...(request.jsonSchema ? { responseMimeType: 'application/json', responseSchema: geminiSchema(request.jsonSchema) } : {})
+            }
+        };
+        const config = {
+            headers: { 'x-goog-api-key': this.apiKey },
+            ...httpOptions(baseUrl, this.http, request)
+        };
+        const extractText = (data: any): string =>
+            (data?.candidates?.[0]?.content?.parts || []).map((part: { text?: string }) => part.text || '').join('');
+
+        try {
+            let text: string;
+            if (request.onToken) {
+                const onToken = request.onToken;
+                const response = await axios.post(`${baseUrl}:streamGenerateContent?alt=sse`, body, { ...config, responseType: 'stream' });
+                text = '';
+                await readSse(response.data, data => {
+                    const chunk = extractText(JSON.parse(data));
+                    if (chunk) {
+                        text += chunk;
+                        onToken(chunk);
+                    }
+                });
+            } else {
+                const response = await axios.post(`${baseUrl}:generateContent`, body, config);
+                text = extractText(response.data);
+            }
+
+            if (!text) {
+                throw new Error('No text response from Gemini');
+            }
+
+            // Return the raw response without additional formatting
+            return text.trim();
+        } catch (error) {
+            throw mapHttpError('Gemini', error);
+        }
+    }
+}
+
+export class OllamaProvider implements AIProvider {
+    readonly name = 'ollama';
+    readonly supportsStreaming = true;
+    readonly reportsUpload = true;
+
+    constructor(private baseUrl: string, readonly model: string, private http: HttpSettings = {}) {}
 
-            // Detect image format for proper MIME type
-            const mimeType = this.detectImageFormat(imageData);
+    async checkKey(): Promise<KeyCheck> {
+        const url = `${this.baseUrl.replace(/\/+$/, '')}/api/tags`;
+        try {
+            const response = await axios.get(url, httpOptions(url, this.http));
+            return {
+                models: (response.data?.models || []).map((model: { name: string }) => model.name),
+                rateLimits: {}
+            };
+        } catch (error) {
+            if (axios.isAxiosError(error) && error.code === 'ECONNREFUSED') {
+                throw new Error(`Ollama API error: could not connect to ${this.baseUrl} - is \`ollama serve\` running?`);
+            }
+            throw mapHttpError('Ollama', error);
+        }
+    }
 
-            // Create the enhanced prompt
-            const prompt = this.createConcisePrompt(userQuestion);
+    async analyze(request: AnalysisRequest): Promise<string> {
+        const url = `${this.baseUrl.replace(/\/+$/, '')}/api/chat`;
 
-            const response = await this.client.messages.create({
-                model: 'claude-3-5-sonnet-20241022',
-                max_tokens: 500, // Reduced from 1000 for more concise responses
-                temperature: 0.1,
-                system: 'You are a concise programming assistant. Provide direct, minimal responses. For coding problems, give working code in markdown blocks without extra explanation. For questions, give brief, direct answers.',
+        try {
+            const body = {
+                model: this.model,
+                stream: !!request.onToken,
                 messages: [
+                    { role: 'system', content: request.systemPrompt },
                     {
                         role: 'user',
-                        content: [
-                            {
-                                type: 'text',
-                                text: prompt
-                            },
-                            {
-                                type: 'image',
-                                source: {
-                                    type: 'base64',
-                                    media_type: mimeType as 'image/jpeg' | 'image/png' | 'image/gif' | 'image/webp',
-                                    data: base64Image
-                                }
-                            }
-                        ]
+                        content: request.prompt,
+                        images: request.images.map(imageData => imageData.toString('base64'))
+                    },
+                    ...(request.followUps || []).map(turn => ({ role: turn.role, content: turn.text }))
+                ],
+                options: {
+                    num_predict: request.maxTokens,
+                    temperature: request.temperature,
+                    top_p: request.topP
+                },
+                // Ollama takes a JSON schema directly for structured outputs
+                ...(request.jsonSchema ? { format: request.jsonSchema } : {})
+            };
+
+            let text: string | undefined;
+            if (request.onToken) {
+                const onToken = request.onToken;
+                const response = await axios.post(url, body, { ...httpOptions(url, this.http, request), responseType: 'stream' });
+                text = '';
+                // Ollama streams newline-delimited JSON objects
+                await readLines(response.data, line => {
+                    const chunk: string | undefined = JSON.parse(line).message?.content;
+                    if (chunk) {
+                        text += chunk;
+                        onToken(chunk);
                     }
-                ]
-            });
+                });
+            } else {
+                const response = await axios.post(url, body, httpOptions(url, this.http, request));
+                text = response.data?.message?.content;
+            }
 
-            const content = response.content[0];
+            if (!text) {
+                throw new Error('No text response from Ollama');
+            }
+
+            // Return the raw response without additional formatting
+            return text.trim();
+        } catch (error) {
+            if (axios.isAxiosError(error) && error.code === 'ECONNREFUSED') {
+                throw new Error(`Ollama API error: could not connect to ${this.baseUrl} - is \`ollama serve\` running?`);
+            }
+            throw mapHttpError('Ollama', error);
+        }
+    }
+}
+
+/**
+ * Claude (or other Anthropic-format) models on AWS Bedrock, authenticated
+ * with SigV4 using credentials from the environment or ~/.aws/credentials.
+ */
+export class BedrockProvider implements AIProvider {
+    readonly name = 'bedrock';
+    // Bedrock streams use the binary AWS event-stream encoding; not supported yet
+    readonly supportsStreaming = false;
+    readonly reportsUpload = true;
+    readonly pricing?: ModelPricing;
+
+    constructor(private region: string, readonly model: string, private http: HttpSettings = {}) {
+        this.pricing = MODEL_PRICING[model];
+    }
+
+    async checkKey(): Promise<KeyCheck> {
+        const credentials = await this.credentials();
+        // Listing models is on the Bedrock control plane, not bedrock-runtime
+        const url = `https://bedrock.${this.region}.amazonaws.com/foundation-models?byOutputModality=TEXT`;
+        const headers = signAwsRequest({ method: 'GET', url, headers: { accept: 'application/json' }, body: '' }, credentials, this.region, 'bedrock');
+        delete headers.host;
+
+        try {
+            const response = await axios.get(url, { headers, ...httpOptions(url, this.http) });
+            return {
+                models: (response.data?.modelSummaries || []).map((model: { modelId: string }) => model.modelId),
+                rateLimits: rateLimitHeaders(response.headers)
+            };
+        } catch (error) {
+            throw mapHttpError('Bedrock', error);
+        }
+    }
+
+    async analyze(request: AnalysisRequest): Promise<string> {
+        const credentials = await this.credentials();
+
+        const url = `https://bedrock-runtime.${this.region}.amazonaws.com/model/${encodeURIComponent(this.model)}/invoke`;
+        const body = JSON.stringify({
+            anthropic_version: 'bedrock-2023-05-31',
+            max_tokens: request.maxTokens,
+            temperature: request.temperature,
+            top_p: request.topP,
+            system: request.systemPrompt,
+            messages: [
+                {
+                    role: 'user',
+                    content: [
+                        { type: 'text', text: request.prompt },
+                        ...request.images.map(imageData => ({
+                            type: 'image',
+                            source: {
+                                type: 'base64',
+                                media_type: detectImageFormat(imageData),
+                                data: imageData.toString('base64')
+                            }
+                        }))
+                    ]
+                },
+                ...(request.followUps || []).map(turn => ({ role: turn.role, content: turn.text }))
+            ]
+        });
+
+        const headers = signAwsRequest({
+            method: 'POST',
+            url,
+            headers: { 'content-type': 'application/json', accept: 'application/json' },
+            body
+        }, credentials, this.region, 'bedrock');
+        // axios sets Host itself from the URL
+        delete headers.host;
+
+        try {
+            const response = await axios.post(url, body, { headers, ...httpOptions(url, this.http, request) });
+
+            const content = response.data?.content?.[0];
             if (!content || content.type !== 'text') {
-                throw new Error('No text response from Claude');
+                throw new Error('No text response from Bedrock');
             }
 
             // Return the raw response without additional formatting
             return content.text.trim();
         } catch (error) {
-            if (error instanceof Anthropic.APIError) {
-                throw new Error(`Claude API error: ${error.message}`);
+            throw mapHttpError('Bedrock', error);
+        }
+    }
+
+    private async credentials(): Promise<AwsCredentials> {
+        const credentials = await loadAwsCredentials();
+        if (!credentials) {
+            throw new ProviderAuthError(
+                'Bedrock API error: no AWS credentials found. Set AWS_ACCESS_KEY_ID/AWS_SECRET_ACCESS_KEY or configure ~/.aws/credentials'
+            );
+        }
+        return credentials;
+    }
+}
+
+/**
+ * Base for providers that speak the OpenAI chat completions format with
+ * images passed as base64 data URLs.
+ */
+export abstract class OpenAICompatibleProvider implements AIProvider {
+    abstract readonly name: string;
+    readonly supportsStreaming = true;
+    readonly reportsUpload = true;
+    readonly supportsImageDetail: boolean = true;
+    readonly pricing?: ModelPricing;
+
+    constructor(
+        protected baseUrl: string,
+        protected apiKey: string,
+        private label: string,
+        readonly model: string,
+        private http: HttpSettings = {}
+    ) {
+        this.pricing = MODEL_PRICING[model];
+    }
+
+    protected requestHeaders(): Record<string, string> {
+        return { Authorization: `Bearer ${this.apiKey}` };
+    }
+
+    async checkKey(): Promise<KeyCheck> {
+        const url = `${this.baseUrl}/models`;
+        try {
+            const response = await axios.get(url, { headers: this.requestHeaders(), ...httpOptions(url, this.http) });
+            return {
+                models: (response.data?.data || []).map((model: { id: string }) => model.id),
+                organization: response.headers['openai-organization'] || undefined,
+                rateLimits: rateLimitHeaders(response.headers)
+            };
+        } catch (error) {
+            throw mapHttpError(this.label, error);
+        }
+    }
+
+    protected responseFormat(schema: object): object {
+        return { type: 'json_schema', json_schema: { name: 'answer', schema, strict: true } };
+    }
+
+    /** The chat completions request body for `request` */
+    protected requestBody(request: AnalysisRequest): Record<string, unknown> {
+        return {
+            model: this.model,
+            max_tokens: request.maxTokens,
+            temperature: request.temperature,
+            top_p: request.topP,
+            messages: [
+                { role: 'system', content: request.systemPrompt },
+                {
+                    role: 'user',
+                    content: [
+                        { type: 'text', text: request.prompt },
+                        ...request.images.map(imageData => ({
+                            type: 'image_url',
+                            image_url: {
+                                url: `data:${detectImageFormat(imageData)};base64,${imageData.toString('base64')}`,
+                                ...(request.detail ? { detail: request.detail } : {})
+                            }
+                        }))
+                    ]
+                },
+                ...(request.followUps || []).map(turn => ({ role: turn.role, content: turn.text }))
+            ],
+            ...(request.jsonSchema ? { response_format: this.responseFormat(request.jsonSchema) } : {})
+        };
+    }
+
+    async analyze(request: AnalysisRequest): Promise<string> {
+        try {
+            const body = this.requestBody(request);
+            const config = {
+                headers: this.requestHeaders(),
+                ...httpOptions(this.baseUrl, this.http, request)
+            };
+
+            let text: string | undefined;
+            if (request.onToken) {
+                const onToken = request.onToken;
+                const response = await axios.post(`${this.baseUrl}/chat/completions`, { ...body, stream: true }, { ...config, responseType: 'stream' });
+                text = '';
+                await readSse(response.data, data => {
+                    if (data === '[DONE]') {
+                        return;
+                    }
+                    const chunk: string | undefined = JSON.parse(data).choices?.[0]?.delta?.content;
+                    if (chunk) {
+                        text += chunk;
+                        onToken(chunk);
+                    }
+                });
+            } else {
+                const response = await axios.post(`${this.baseUrl}/chat/completions`, body, config);
+                text = response.data?.choices?.[0]?.message?.content;
+            }
+
+            if (!text) {
+                throw new Error(`No text response from ${this.label}`);
+            }
+
+            // Return the raw response without additional formatting
+            return text.trim();
+        } catch (error) {
+            throw mapHttpError(this.label, error);
+        }
+    }
+}
+
+export class OpenAIProvider extends OpenAICompatibleProvider {
+    readonly name = 'openai';
+
+    constructor(
+        apiKey: string,
+        model: string = OPENAI_MODEL,
+        http: HttpSettings = {},
+        private organization?: string,
+        private project?: string
+    ) {
+        super('https://api.openai.com/v1', apiKey, 'OpenAI', model, http);
+    }
+
+    // Keys for accounts in several organizations or projects need these
+    // to bill and authorize against the right one
+    protected requestHeaders(): Record<string, string> {
+        return {
+            ...super.requestHeaders(),
+            ...(this.organization ? { 'OpenAI-Organization': this.organization } : {}),
+            ...(this.project ? { 'OpenAI-Project': this.project } : {})
+        };
+    }
+
+    /**
+     * Submit a request to the Batch API, which runs it within 24 hours at
+     * half the price. Returns the batch id.
+     */
+    async submitBatch(request: AnalysisRequest): Promise<string> {
+        const line = JSON.stringify({
+            custom_id: 'analysis',
+            method: 'POST',
+            url: '/v1/chat/completions',
+            body: this.requestBody(request)
+        });
+
+        try {
+            const form = new FormData();
+            form.append('purpose', 'batch');
+            form.append('file', Buffer.from(line + '\n'), { filename: 'batch.jsonl', contentType: 'application/jsonl' });
+
+            const upload = await axios.post(`${this.baseUrl}/files`, form, {
+                headers: { ...this.requestHeaders(), ...form.getHeaders() },
+                ...httpOptions(this.baseUrl, this.http)
+            });
+            const batch = await axios.post(`${this.baseUrl}/batches`, {
+                input_file_id: upload.data.id,
+                endpoint: '/v1/chat/completions',
+                completion_window: '24h'
+            }, {
+                headers: this.requestHeaders(),
+                ...httpOptions(this.baseUrl, this.http)
+            });
+            return batch.data.id;
+        } catch (error) {
+            throw mapHttpError('OpenAI', error);
+        }
+    }
+
+    /**
+     * Look up a batch, downloading the answer once it has completed.
+     */
+    async retrieveBatch(id: string): Promise<BatchResult> {
+        const config = { headers: this.requestHeaders(), ...httpOptions(this.baseUrl, this.http) };
+
+        try {
+            const batch = (await axios.get(`${this.baseUrl}/batches/${encodeURIComponent(id)}`, config)).data;
+            const fileId: string | undefined = batch.output_file_id || batch.error_file_id;
+            if (batch.status !== 'completed' || !fileId) {
+                return { status: batch.status, error: batch.errors?.data?.[0]?.message };
+            }
+
+            // The file holds one JSON line per request; we submit one request per batch
+            const content = await axios.get(`${this.baseUrl}/files/${fileId}/content`, { ...config, responseType: 'text' });
+            const result = JSON.parse(String(content.data).trim().split('\n')[0]);
+            const answer: string | undefined = result.response?.body?.choices?.[0]?.message?.content;
+            return answer
+                ? { status: batch.status, answer: answer.trim() }
+                : { status: 'failed', error: result.error?.message || result.response?.body?.error?.message || 'no answer in batch output' };
+        } catch (error) {
+            throw mapHttpError('OpenAI', error);
+        }
+    }
+}
+
+export interface BatchResult {
+    /** OpenAI batch status: validating, in_progress, finalizing, completed, failed, expired, ... */
+    status: string;
+    answer?: string;
+    error?: string;
+}
+
+export class GroqProvider extends OpenAICompatibleProvider {
+    readonly name = 'groq';
+
+    constructor(apiKey: string, model: string = GROQ_MODEL, http: HttpSettings = {}) {
+        super('https://api.groq.com/openai/v1', apiKey, 'Groq', model, http);
+    }
+
+    // Groq's vision models support JSON mode but not schema-constrained output
+    protected responseFormat(): object {
+        return { type: 'json_object' };
+    }
+}
+
+/**
+ * A vision model run locally by llama.cpp's `llama-server` (started with
+ * the model's `--mmproj` projector), through its OpenAI-compatible API.
+ * Needs no API key and no internet connection.
+ */
+export class LlamaCppProvider extends OpenAICompatibleProvider {
+    readonly name = 'llamacpp';
+    // Local servers don't honor OpenAI's detail setting; downscale instead
+    readonly supportsImageDetail = false;
+
+    constructor(baseUrl: string, model: string = LLAMACPP_MODEL, http: HttpSettings = {}) {
+        super(baseUrl.replace(/\/+$/, ''), 'none', 'llama.cpp', model, http);
+    }
+
+    protected requestHeaders(): Record<string, string> {
+        return {};
+    }
+}
+
+/**
+ * Answers with canned text and never touches the network, for offline
+ * development and CI. Set AI_MOCK_RESPONSE to choose the answer.
+ */
+export class MockProvider implements AIProvider {
+    readonly name = 'mock';
+    readonly model = 'mock';
+    readonly supportsStreaming = true;
+
+    constructor(private response: string | undefined = process.env.AI_MOCK_RESPONSE) {}
+
+    async analyze(request: AnalysisRequest): Promise<string> {
+        const text = this.response ?? (request.jsonSchema ? this.structuredResponse(request) : this.textResponse(request));
+
+        if (request.onToken) {
+            // Stream word by word, like a real provider would
+            for (const chunk of text.match(/\S+\s*|\s+/g) || []) {
+                request.onToken(chunk);
             }
-            throw error;
         }
+        return text;
+    }
+
+    async checkKey(): Promise<KeyCheck> {
+        return { models: [this.model], rateLimits: {} };
+    }
+
+    private textResponse(request: AnalysisRequest): string {
+        const question = request.followUps?.length
+            ? request.followUps[request.followUps.length - 1].text
+            : request.prompt.split('\n')[0];
+
+        return `Mock analysis of ${request.images.length} image(s) - no API request was made.
+
+Prompt: ${question}
+
+\`\`\`python
+print("hello from the mock provider")
+\`\`\``;
+    }
+
+    private structuredResponse(request: AnalysisRequest): string {
+        const answer: StructuredAnswer = {
+            problem: `Mock analysis of ${request.images.length} image(s)`,
+            language: 'python',
+            solution_code: 'print("hello from the mock provider")',
+            explanation: 'No API request was made.'
+        };
+        return JSON.stringify(answer);
+    }
+}
+
+export const PROVIDER_NAMES = ['claude', 'openai', 'gemini', 'groq', 'ollama', 'llamacpp', 'bedrock', 'mock'];
+
+/** Providers that don't take an API key (local, or authenticated another way) */
+export const KEYLESS_PROVIDERS = ['ollama', 'llamacpp', 'bedrock', 'mock'];
+
+/**
+ * Provider-specific environment variables checked for an API key when
+ * neither --api-key nor the config file provide one, before AI_API_KEY.
+ */
+export const PROVIDER_KEY_ENV: Record<string, string[]> = {
+    claude: ['ANTHROPIC_API_KEY'],
+    openai: ['OPENAI_API_KEY'],
+    gemini: ['GEMINI_API_KEY', 'GOOGLE_API_KEY'],
+    groq: ['GROQ_API_KEY'],
+};
+
+// Other names createProvider accepts
+const PROVIDER_ALIASES: Record<string, string> = {
+    anthropic: 'claude',
+    google: 'gemini',
+};
+
+/** The provider a name stands for, e.g. "claude" for "anthropic" */
+export function canonicalProvider(name: string): string {
+    return PROVIDER_ALIASES[name] || name;
+}
+
+/** The first of `provider`'s own environment variables that is set, and its value */
+export function providerKeyFromEnv(provider: string, env: NodeJS.ProcessEnv = process.env): { name: string; key: string } | undefined {
+    const name = (PROVIDER_KEY_ENV[canonicalProvider(provider)] || []).find(envName => env[envName]);
+    return name ? { name, key: env[name]! } : undefined;
+}
+
+/**
+ * The API key for `provider`: --api-key, then `api_key` from the config
+ * file, then the provider's own variable (ANTHROPIC_API_KEY and so on),
+ * and only then AI_API_KEY - a generic key set for one provider shouldn't
+ * be sent to another.
+ */
+export function resolveApiKey(provider: string, flag?: string, configured?: string, env: NodeJS.ProcessEnv = process.env): string | undefined {
+    return flag || configured || providerKeyFromEnv(provider, env)?.key || env.AI_API_KEY || undefined;
+}
+
+/** The proxy and timeouts from the config, for every provider's requests */
+function httpSettings(config: AppConfig): HttpSettings {
+    return {
+        proxy: config.proxy,
+        requestTimeoutMs: config.requestTimeoutSecs !== undefined ? config.requestTimeoutSecs * 1000 : undefined,
+        connectTimeoutMs: config.connectTimeoutSecs !== undefined ? config.connectTimeoutSecs * 1000 : undefined
+    };
+}
+
+/**
+ * Create a provider by name. `model` overrides the provider's default model
+ * (or the Ollama/Bedrock model from the config).
+ */
+export function createProvider(name: string, apiKey: string, config: AppConfig = new AppConfig(), model?: string): AIProvider {
+    const http = httpSettings(config);
+    switch (name) {
+        case 'claude':
+        case 'anthropic':
+            return new ClaudeProvider(apiKey, model, http, config.promptCaching);
+        case 'openai':
+            return new OpenAIProvider(apiKey, model, http, config.openaiOrganization, config.openaiProject);
+        case 'gemini':
+        case 'google':
+            return new GeminiProvider(apiKey, model, http);
+        case 'groq':
+            return new GroqProvider(apiKey, model, http);
+        case 'ollama':
+            return new OllamaProvider(config.ollamaBaseUrl, model || config.ollamaModel, http);
+        case 'llamacpp':
+            return new LlamaCppProvider(config.llamacppBaseUrl, model, http);
+        case 'bedrock':
+            return new BedrockProvider(config.bedrockRegion, model || config.bedrockModelId, http);
+        case 'mock':
+            return new MockProvider();
+        default:
+            throw new ConfigError(`Unknown provider "${name}". Available providers: ${PROVIDER_NAMES.join(', ')}`);
+    }
+}
+
+/**
+ * Transcribe a WAV recording with OpenAI's speech-to-text API, for voice
+ * questions. Uses the config's proxy and timeouts.
+ */
+export async function transcribeAudio(audio: Buffer, apiKey: string, config: AppConfig = new AppConfig()): Promise<string> {
+    const url = 'https://api.openai.com/v1/audio/transcriptions';
+    try {
+        const form = new FormData();
+        form.append('model', TRANSCRIPTION_MODEL);
+        form.append('response_format', 'text');
+        form.append('file', audio, { filename: 'question.wav', contentType: 'audio/wav' });
+
+        const response = await axios.post(url, form, {
+            headers: { Authorization: `Bearer ${apiKey}`, ...form.getHeaders() },
+            responseType: 'text',
+            ...httpOptions(url, httpSettings(config))
+        });
+        return String(response.data).trim();
+    } catch (error) {
+        throw mapHttpError('OpenAI', error);
+    }
+}
+
+export class AIClient {
+    private backend: AIProvider;
+    private dryRun: boolean;
+    private temperature: number;
+    private topP?: number;
+    private detail?: ImageDetail;
+    private systemPrompt: string;
+    private limiter: RequestLimiter;
+    // The last analysis, kept so follow-up questions can reuse its images
+    private conversation: { images: Buffer[]; prompt: string; turns: ConversationTurn[] } | null = null;
+    private usage: TokenUsage | null = null;
+
+    constructor(provider: string, apiKey: string, options: AIClientOptions = {}) {
+        this.dryRun = options.dryRun || false;
+        this.temperature = options.temperature ?? DEFAULT_TEMPERATURE;
+        this.topP = options.topP;
+        this.detail = options.detail;
+        // In the system prompt so it also holds for follow-up questions
+        this.systemPrompt = options.language
+            ? `${SYSTEM_PROMPT} Always answer in ${options.language}, even if the image or question is in another language. Keep code and identifiers unchanged.`
+            : SYSTEM_PROMPT;
+        this.backend = createProvider(provider, apiKey, options.config, options.model);
+        this.limiter = new RequestLimiter(
+            options.maxConcurrentRequests ?? DEFAULT_MAX_CONCURRENT_REQUESTS,
+            options.maxQueuedRequests ?? DEFAULT_MAX_QUEUED_REQUESTS
+        );
+    }
+
+    isDryRun(): boolean {
+        return this.dryRun;
+    }
+
+    provider(): string {
+        return this.backend.name;
+    }
+
+    model(): string {
+        return this.backend.model;
+    }
+
+    /**
+     * Analyze a screenshot. Passing several questions asks them all in a
+     * single request, and the answers come back labeled by number.
+     */
+    async analyzeImage(imageData: Buffer, userQuestion?: string | string[], options: AnalyzeOptions = {}): Promise<string> {
+        return this.analyzeImages([imageData], userQuestion, options);
+    }
+
+    /**
+     * Analyze several images in one request, in the order given.
+     */
+    async analyzeImages(images: Buffer[], userQuestion?: string | string[], options: AnalyzeOptions = {}): Promise<string> {
+        const request = await this.buildRequest(images, userQuestion, options);
+
+        const text = await this.send(request, options.onStage);
+        if (!this.dryRun) {
+            // A new analysis starts a new conversation
+            this.conversation = { images: request.images, prompt: request.prompt, turns: [{ role: 'assistant', text }] };
+        }
+        return text;
+    }
+
+    /**
+     * Queue an analysis with the OpenAI Batch API instead of waiting for
+     * it. Returns the batch id to pass to `retrieveBatch` later.
+     */
+    async submitBatch(images: Buffer[], userQuestion?: string | string[], options: AnalyzeOptions = {}): Promise<string> {
+        const request = await this.buildRequest(images, userQuestion, options);
+        return this.batchBackend().submitBatch(request);
+    }
+
+    async retrieveBatch(id: string): Promise<BatchResult> {
+        return this.batchBackend().retrieveBatch(id);
+    }
+
+    private batchBackend(): OpenAIProvider {
+        if (!(this.backend instanceof OpenAIProvider)) {
+            throw new ConfigError(`Batch mode needs the OpenAI provider (current provider: ${this.backend.name})`);
+        }
+        return this.backend;
+    }
+
+    private async buildRequest(images: Buffer[], userQuestion: string | string[] | undefined, options: AnalyzeOptions): Promise<AnalysisRequest> {
+        // Create the enhanced prompt
+        const prompt = options.schema
+            ? `${userQuestion}\n\n${this.jsonInstructions(options.schema)}`
+            : options.structured
+                ? this.createStructuredPrompt(userQuestion)
+                : this.createConcisePrompt(userQuestion);
+
+        return {
+            images: await this.applyDetail(images),
+            prompt: options.context ? `${options.context}\n\n${prompt}` : prompt,
+            systemPrompt: this.systemPrompt,
+            maxTokens: options.maxTokens || DEFAULT_MAX_TOKENS,
+            temperature: this.temperature,
+            topP: this.topP,
+            detail: this.detail,
+            onToken: options.onToken,
+            jsonSchema: options.schema ?? (options.structured ? STRUCTURED_ANSWER_SCHEMA : undefined),
+            signal: options.signal
+        };
+    }
+
+    hasConversation(): boolean {
+        return this.conversation !== null;
+    }
+
+    resetConversation(): void {
+        this.conversation = null;
+    }
+
+    /**
+     * Pick up a conversation kept elsewhere, like a thread from history:
+     * its images, the first question and every turn since the first answer
+     * (which comes first). The next `followUp` sends them all as context.
+     */
+    async resumeConversation(images: Buffer[], question: string | undefined, turns: ConversationTurn[]): Promise<void> {
+        this.conversation = { images: await this.applyDetail(images), prompt: this.createConcisePrompt(question), turns };
+    }
+
+    /**
+     * Ask a follow-up question about the last analysis. The earlier images
+     * and answers are sent again as context (the APIs are stateless), so
+     * the screen doesn't have to be recaptured.
+     */
+    async followUp(question: string, options: AnalyzeOptions = {}): Promise<string> {
+        const conversation = this.conversation;
+        if (!conversation) {
+            throw new Error('No previous analysis to follow up on - capture a screenshot first');
+        }
+
+        const turn: ConversationTurn = {
+            role: 'user',
+            text: options.structured ? this.createStructuredPrompt(question) : question.trim()
+        };
+        const request: AnalysisRequest = {
+            images: conversation.images,
+            prompt: conversation.prompt,
+            systemPrompt: this.systemPrompt,
+            maxTokens: options.maxTokens || DEFAULT_MAX_TOKENS,
+            temperature: this.temperature,
+            topP: this.topP,
+            detail: this.detail,
+            onToken: options.onToken,
+            jsonSchema: options.structured ? STRUCTURED_ANSWER_SCHEMA : undefined,
+            followUps: [...conversation.turns, turn],
+            signal: options.signal
+        };
+
+        const text = await this.send(request, options.onStage);
+        if (!this.dryRun) {
+            conversation.turns.push(turn, { role: 'assistant', text });
+        }
+        return text;
+    }
+
+    /**
+     * Providers without a detail setting get low detail by sending a
+     * downscaled image, which is what OpenAI does server-side.
+     */
+    private async applyDetail(images: Buffer[]): Promise<Buffer[]> {
+        if (this.detail !== 'low' || this.backend.supportsImageDetail) {
+            return images;
+        }
+        return Promise.all(images.map(imageData =>
+            sharp(imageData)
+                .resize(LOW_DETAIL_SIZE, LOW_DETAIL_SIZE, { fit: 'inside', withoutEnlargement: true })
+                .toBuffer()
+        ));
+    }
+
+    /** Validate the credentials without running a model */
+    async checkKey(): Promise<KeyCheck> {
+        return this.backend.checkKey();
+    }
+
+    /** Token counts from the last request, when the provider reported them */
+    lastUsage(): TokenUsage | null {
+        return this.usage;
+    }
+
+    private async send(request: AnalysisRequest, onStage?: AnalyzeOptions['onStage']): Promise<string> {
+        this.usage = null;
+        request.onUsage = usage => {
+            this.usage = usage;
+        };
+
+        if (this.dryRun) {
+            const summary = await this.describePayload(request);
+            request.onToken?.(summary);
+            return summary;
+        }
+
+        let text: string;
+        try {
+            text = await this.limiter.run(() => {
+                // Cancelled while waiting for a slot
+                if (request.signal?.aborted) {
+                    throw new CancelledError();
+                }
+                const report = onStage;
+                if (report) {
+                    // Without word of the upload finishing, it counts as waiting on the model
+                    const bytes = request.images.reduce((total, image) => total + image.length, 0);
+                    report(this.backend.reportsUpload ? 'uploading' : 'waiting', bytes);
+                    request.onSent = () => report('waiting');
+                }
+                return this.backend.analyze(request);
+            });
+        } catch (error) {
+            // Each provider and SDK reports an abort differently
+            throw request.signal?.aborted ? new CancelledError() : error;
+        }
+        if (request.onToken && !this.backend.supportsStreaming) {
+            // Providers that can't stream deliver the whole answer at once
+            request.onToken(text);
+        }
+        return text;
+    }
+
+    /**
+     * Summarize the request that would be sent, without calling the API.
+     * Token counts are estimates: ~4 characters per text token and
+     * width * height / 750 for images, as documented by Anthropic.
+     */
+    private async describePayload(request: AnalysisRequest): Promise<string> {
+        const { images, prompt, maxTokens } = request;
+        const followUpLength = (request.followUps || []).reduce((total, turn) => total + turn.text.length, 0);
+        const textTokens = Math.ceil((request.systemPrompt.length + prompt.length + followUpLength) / 4);
+        let imageTokens = 0;
+        const imageLines: string[] = [];
+
+        for (const imageData of images) {
+            const metadata = await sharp(imageData).metadata();
+            const width = metadata.width || 0;
+            const height = metadata.height || 0;
+            const base64Length = Math.ceil(imageData.length / 3) * 4;
+
+            imageTokens += Math.ceil((width * height) / 750);
+            imageLines.push(`Image: ${width}x${height} ${detectImageFormat(imageData)}, ${imageData.length} bytes (${base64Length} bytes base64)`);
+        }
+
+        const inputTokens = textTokens + imageTokens;
+        const pricing = this.backend.pricing;
+        let costLine = 'Estimated cost: unknown for this provider';
+        if (pricing) {
+            const inputCost = (inputTokens / 1_000_000) * pricing.input;
+            const maxCost = inputCost + (maxTokens / 1_000_000) * pricing.output;
+            costLine = `Estimated cost: ~$${inputCost.toFixed(4)} input, up to ~$${maxCost.toFixed(4)} with a full response`;
+        }
+
+        return `🧪 Dry run - no API request was made
+
+Provider: ${this.backend.name}
+Model: ${this.backend.model}
+Max tokens: ${maxTokens}
+Temperature: ${request.temperature}${request.topP !== undefined ? `, top_p: ${request.topP}` : ''}
+Image detail: ${request.detail || 'provider default'}
+${imageLines.join('\n')}
+Estimated input tokens: ~${inputTokens} (text ~${textTokens}, image ~${imageTokens})
+${costLine}
+
+Prompt:
+${prompt}`;
     }
 
-    private createConcisePrompt(userQuestion?: string): string {
+    private createConcisePrompt(userQuestion?: string | string[]): string {
+        if (Array.isArray(userQuestion)) {
+            const questions = userQuestion.map(q => q.trim()).filter(q => q.length > 0);
+            if (questions.length > 1) {
+                return this.createMultiQuestionPrompt(questions);
+            }
+            userQuestion = questions[0];
+        }
+
         if (userQuestion && userQuestion.trim()) {
             return `Answer this question directly and concisely: ${userQuestion.trim()}
 
@@ -88,29 +1308,213 @@ If it's not code:
         }
     }
 
-    private detectImageFormat(imageData: Buffer): string {
-        if (imageData.length < 8) {
-            return 'image/png'; // Default fallback
-        }
+    private createStructuredPrompt(userQuestion?: string | string[]): string {
+        const question = Array.isArray(userQuestion) ? userQuestion.join('\n') : userQuestion;
+        const task = question && question.trim()
+            ? `Answer this question about the image: ${question.trim()}`
+            : 'Analyze what you see in this image. If it is a coding problem, solve it.';
 
-        // Check PNG signature
-        if (imageData.subarray(0, 8).equals(Buffer.from([0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A]))) {
-            return 'image/png';
-        }
+        return `${task}
+
+${this.jsonInstructions(STRUCTURED_ANSWER_SCHEMA)}`;
+    }
 
-        // Check JPEG signature
-        if (imageData.subarray(0, 3).equals(Buffer.from([0xFF, 0xD8, 0xFF]))) {
-            return 'image/jpeg';
+    private jsonInstructions(schema: object): string {
+        return `Respond with only a JSON object matching this schema, with no markdown around it:
+${JSON.stringify(schema)}`;
+    }
+
+    private createMultiQuestionPrompt(questions: string[]): string {
+        const numbered = questions.map((q, i) => `${i + 1}. ${q}`).join('\n');
+
+        return `Answer each of these questions about the image directly and concisely:
+${numbered}
+
+Label each answer with a heading like "### 1. <question>", in the same order as above.
+If code is needed, provide it in markdown code blocks without extra explanation.`;
+    }
+}
+
+/**
+ * Parse a structured answer, tolerating a markdown code fence around the
+ * JSON from providers that don't enforce the format.
+ */
+export function parseStructuredAnswer(text: string): StructuredAnswer {
+    const data = parseJsonAnswer(text);
+
+    const answer = {} as StructuredAnswer;
+    for (const key of STRUCTURED_ANSWER_SCHEMA.required as Array<keyof StructuredAnswer>) {
+        const value = data?.[key];
+        answer[key] = typeof value === 'string' ? value : '';
+    }
+    return answer;
+}
+
+/**
+ * Parse a JSON answer, tolerating a markdown code fence around it from
+ * providers that don't enforce the format.
+ */
+export function parseJsonAnswer(text: string): any {
+    const json = text.trim().replace(/^```(?:json)?\s*/, '').replace(/\s*```$/, '');
+    try {
+        return JSON.parse(json);
+    } catch (error) {
+        throw new Error(`AI response was not valid JSON: ${text.slice(0, 200)}`);
+    }
+}
+
+export function detectImageFormat(imageData: Buffer): string {
+    if (imageData.length < 8) {
+        return 'image/png'; // Default fallback
+    }
+
+    // Check PNG signature
+    if (imageData.subarray(0, 8).equals(Buffer.from([0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A]))) {
+        return 'image/png';
+    }
+
+    // Check JPEG signature
+    if (imageData.subarray(0, 3).equals(Buffer.from([0xFF, 0xD8, 0xFF]))) {
+        return 'image/jpeg';
+    }
+
+    // Check WebP signature
+    if (imageData.length >= 12 &&
+        imageData.subarray(0, 4).equals(Buffer.from('RIFF')) &&
+        imageData.subarray(8, 12).equals(Buffer.from('WEBP'))) {
+        return 'image/webp';
+    }
+
+    // Default to PNG
+    return 'image/png';
+}
+
+/**
+ * Per-request axios options: the timeouts, an agent for the proxy when one
+ * applies to `url`, and the signal that cancels the request.
+ */
+function httpOptions(url: string, settings: HttpSettings, request: Pick<AnalysisRequest, 'signal' | 'onSent'> = {}) {
+    const proxy = proxyFor(url, settings.proxy);
+    const agent = proxy ? proxyAgent(proxy) : undefined;
+    const signal = request.signal;
+    return {
+        timeout: settings.requestTimeoutMs ?? REQUEST_TIMEOUT_MS,
+        transport: connectTimeoutTransport(settings.connectTimeoutMs ?? CONNECT_TIMEOUT_MS, request.onSent),
+        // axios's own proxy support can't tunnel HTTPS, so always use our agent
+        proxy: false as const,
+        httpAgent: agent,
+        httpsAgent: agent,
+        signal
+    };
+}
+
+/**
+ * A JSON schema as Gemini's `responseSchema`, which takes a subset of
+ * OpenAPI and rejects `additionalProperties`.
+ */
+function geminiSchema(schema: object): object {
+    return JSON.parse(JSON.stringify(schema), (key, value) => key === 'additionalProperties' ? undefined : value);
+}
+
+/**
+ * Map an axios failure from an HTTP-based provider onto the shared error
+ * classes, keeping the "<Provider> API error: ..." message format.
+ */
+function mapHttpError(label: string, error: unknown): unknown {
+    if (!axios.isAxiosError(error)) {
+        return error;
+    }
+
+    if (error.code === 'ECONNABORTED') {
+        return new TimeoutError(`${label} API error: request timed out`);
+    }
+    if (error.code === 'ETIMEDOUT') {
+        return new TimeoutError(`${label} API error: ${error.message || 'connection timed out'}`);
+    }
+
+    const status = error.response?.status;
+    const data: any = error.response?.data;
+    const detail = redactSecrets(data?.error?.message || data?.message || error.message);
+
+    if (status === 401 || status === 403 || /API_KEY_INVALID|invalid api key/i.test(detail)) {
+        return new ProviderAuthError(`${label} API error: ${detail}`);
+    }
+    if (status === 429) {
+        return new RateLimitError(`${label} API error: ${detail}`, retryAfterMs(error.response?.headers));
+    }
+    return new Error(`${label} API error: ${detail}`);
+}
+
+function rateLimitHeaders(headers: any): Record<string, string> {
+    const limits: Record<string, string> = {};
+    const add = (value: unknown, name: string) => {
+        if (/ratelimit|retry-after/i.test(name)) {
+            limits[name.toLowerCase()] = String(value);
         }
+    };
+
+    // fetch-style Headers (Anthropic SDK) or a plain object (axios)
+    if (typeof headers?.forEach === 'function') {
+        headers.forEach(add);
+    } else {
+        Object.entries(headers || {}).forEach(([name, value]) => add(value, name));
+    }
+    return limits;
+}
+
+function retryAfterMs(headers: any): number | undefined {
+    if (!headers) {
+        return undefined;
+    }
+    const header = (name: string): string | null | undefined =>
+        typeof headers.get === 'function' ? headers.get(name) : headers[name];
+
+    const retryAfter = header('retry-after');
+    if (retryAfter && !isNaN(Number(retryAfter))) {
+        return Number(retryAfter) * 1000;
+    }
 
-        // Check WebP signature
-        if (imageData.length >= 12 &&
-            imageData.subarray(0, 4).equals(Buffer.from('RIFF')) &&
-            imageData.subarray(8, 12).equals(Buffer.from('WEBP'))) {
-            return 'image/webp';
+    const reset = header('anthropic-ratelimit-requests-reset');
+    if (reset) {
+        const resetAt = Date.parse(reset);
+        if (!isNaN(resetAt)) {
+            return Math.max(0, resetAt - Date.now());
         }
+    }
+    return undefined;
+}
 
-        // Default to PNG
-        return 'image/png';
+/**
+ * Call `onLine` for each non-empty line of a streamed response body.
+ */
+async function readLines(stream: AsyncIterable<Buffer | string>, onLine: (line: string) => void): Promise<void> {
+    // Chunks can end in the middle of a multi-byte character
+    const decoder = new StringDecoder('utf8');
+    let buffered = '';
+    for await (const chunk of stream) {
+        buffered += typeof chunk === 'string' ? chunk : decoder.write(chunk);
+        const lines = buffered.split('\n');
+        buffered = lines.pop() || '';
+        for (const line of lines) {
+            if (line.trim()) {
+                onLine(line.trim());
+            }
+        }
+    }
+    buffered += decoder.end();
+    if (buffered.trim()) {
+        onLine(buffered.trim());
     }
-}
\ No newline at end of file
+}
+
+/**
+ * Call `onData` with the payload of each `data:` line of a server-sent
+ * events stream. Our providers send one JSON object per event.
+ */
+async function readSse(stream: AsyncIterable<Buffer | string>, onData: (data: string) => void): Promise<void> {
+    await readLines(stream, line => {
+        if (line.startsWith('data:')) {
+            onData(line.slice('data:'.length).trim());
+        }
+    });
+}
@@ -0,0 +1,97 @@
+import sharp from 'sharp';
+import * as fs from 'fs/promises';
+import * as path from 'path';
+
+const BANNER_FONT_SIZE = 20;
+const BANNER_LINE_HEIGHT = 28;
+const BANNER_PADDING = 16;
+const SUMMARY_MAX_LENGTH = 300;
+
+/**
+ * Write a copy of the screenshot with the question and a short summary of
+ * the answer rendered into a banner below it, for pasting into tickets.
+ */
+export async function writeAnnotatedImage(
+    imageData: Buffer,
+    question: string,
+    analysis: string,
+    outputPath: string
+): Promise<void> {
+    const metadata = await sharp(imageData).metadata();
+    const width = metadata.width || 800;
+
+    // Rough character budget per line for the banner font
+    const charsPerLine = Math.max(20, Math.floor((width - BANNER_PADDING * 2) / (BANNER_FONT_SIZE * 0.55)));
+    const lines = [
+        ...wrapText(`Q: ${question}`, charsPerLine),
+        ...wrapText(`A: ${summarizeAnswer(analysis)}`, charsPerLine)
+    ];
+    const bannerHeight = lines.length * BANNER_LINE_HEIGHT + BANNER_PADDING * 2;
+
+    const textElements = lines.map((line, i) => {
+        const y = BANNER_PADDING + (i + 1) * BANNER_LINE_HEIGHT - 8;
+        return `<text x="${BANNER_PADDING}" y="${y}">${escapeXml(line)}</text>`;
+    }).join('');
+    const banner = `<svg xmlns="http://www.w3.org/2000/svg" width="${width}" height="${bannerHeight}">
+        <rect width="100%" height="100%" fill="#1e1e1e"/>
+        <g font-family="Arial, Helvetica, sans-serif" font-size="${BANNER_FONT_SIZE}" fill="#ffffff">${textElements}</g>
+    </svg>`;
+
+    const annotated = await sharp(imageData)
+        .extend({ bottom: bannerHeight, background: '#1e1e1e' })
+        .composite([{ input: Buffer.from(banner), gravity: 'south' }])
+        .png()
+        .toBuffer();
+
+    await fs.mkdir(path.dirname(path.resolve(outputPath)), { recursive: true });
+    await fs.writeFile(outputPath, annotated);
+}
+
+/**
+ * First prose line of the answer, skipping code blocks and headings.
+ */
+export function summarizeAnswer(analysis: string): string {
+    let inCodeBlock = false;
+
+    for (const rawLine of analysis.split('\n')) {
+        const line = rawLine.trim();
+        if (line.startsWith('```')) {
+            inCodeBlock = !inCodeBlock;
+            continue;
+        }
+        if (inCodeBlock || !line || line.startsWith('#')) {
+            continue;
+        }
+        return line.length > SUMMARY_MAX_LENGTH ? `${line.slice(0, SUMMARY_MAX_LENGTH - 1)}…` : line;
+    }
+
+    return 'See the full answer for details (code only).';
+}
+
+function wrapText(text: string, width: number): string[] {
+    const lines: string[] = [];
+    let current = '';
+
+    for (const word of text.split(/\s+/)) {
+        if (current && current.length + word.length + 1 > width) {
+            lines.push(current);
+            current = word;
+        } else {
+            current = current ? `${current} ${word}` : word;
+        }
+    }
+    if (current) {
+        lines.push(current);
+    }
+
+    return lines;
+}
+
+function escapeXml(text: string): string {
+    return text
+        .replace(/&/g, '&amp;')
+        .replace(/</g, '&lt;')
+        .replace(/>/g, '&gt;')
+        .replace(/"/g, '&quot;')
+        .replace(/'/g, '&apos;');
+}
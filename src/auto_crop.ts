@@ -0,0 +1,64 @@
+import sharp from 'sharp';
+import { AIClient } from './ai_client';
+import { CropSpec } from './crop';
+
+// The locating pass only needs the layout, not legible text
+const PREVIEW_SIZE = 768;
+const LOCATE_MAX_TOKENS = 50;
+
+// Room left around the located region, in percent of the screen, in case
+// the model's bounds are a little tight
+const MARGIN_PERCENT = 2;
+
+// Cropping to more than this share of the screen saves too little to be
+// worth the risk of cutting something off
+const MAX_AREA_PERCENT = 90;
+
+const LOCATE_PROMPT = 'Find the part of this screenshot with the coding problem: the problem statement and the code editor. ' +
+    'Reply with only its bounds as x,y,width,height percentages of the screenshot, e.g. 5%,10%,60%,85%, ' +
+    'or "none" if there is no coding problem.';
+
+/**
+ * Turn the locating pass's answer into a crop with a small margin.
+ * Undefined when there's no region in it or the region is nearly the
+ * whole screen.
+ */
+export function parseLocatedRegion(answer: string): CropSpec | undefined {
+    const match = answer.match(/(\d+(?:\.\d+)?)%?\s*,\s*(\d+(?:\.\d+)?)%?\s*,\s*(\d+(?:\.\d+)?)%?\s*,\s*(\d+(?:\.\d+)?)%?/);
+    if (!match) {
+        return undefined;
+    }
+    const [x, y, width, height] = match.slice(1).map(Number);
+    const left = Math.max(0, x - MARGIN_PERCENT);
+    const top = Math.max(0, y - MARGIN_PERCENT);
+    const right = Math.min(100, x + width + MARGIN_PERCENT);
+    const bottom = Math.min(100, y + height + MARGIN_PERCENT);
+    if (width <= 0 || height <= 0 || right <= left || bottom <= top ||
+        (right - left) * (bottom - top) > MAX_AREA_PERCENT * 100) {
+        return undefined;
+    }
+    const percent = (value: number) => ({ value, percent: true });
+    return { x: percent(left), y: percent(top), width: percent(right - left), height: percent(bottom - top) };
+}
+
+/**
+ * Ask the model, with a small preview, where the problem statement and
+ * code editor are. Sending only that region in the main request cuts
+ * tokens and leaves more detail for the code. Any failure falls back to
+ * the whole screen.
+ */
+export async function locateCodeRegion(aiClient: AIClient, image: Buffer): Promise<CropSpec | undefined> {
+    try {
+        const preview = await sharp(image)
+            .resize(PREVIEW_SIZE, PREVIEW_SIZE, { fit: 'inside', withoutEnlargement: true })
+            .png()
+            .toBuffer();
+        const answer = await aiClient.analyzeImages([preview], LOCATE_PROMPT, { maxTokens: LOCATE_MAX_TOKENS });
+        const region = parseLocatedRegion(answer);
+        console.log(region ? '✂️  Found the coding problem, cropping to it' : 'No distinct coding area found, sending the whole screen');
+        return region;
+    } catch (error) {
+        console.log(`⚠️  Auto-crop failed, sending the whole screen: ${error}`);
+        return undefined;
+    }
+}
@@ -0,0 +1,156 @@
+import { createHash, createHmac } from 'crypto';
+import * as fs from 'fs/promises';
+import * as path from 'path';
+import * as os from 'os';
+
+export interface AwsCredentials {
+    accessKeyId: string;
+    secretAccessKey: string;
+    sessionToken?: string;
+}
+
+export interface SignableRequest {
+    method: string;
+    url: string;
+    headers: Record<string, string>;
+    body: string;
+}
+
+/**
+ * Resolve AWS credentials the way the AWS CLI does for the common cases:
+ * environment variables first, then the shared credentials file for
+ * AWS_PROFILE (or "default").
+ */
+export async function loadAwsCredentials(): Promise<AwsCredentials | null> {
+    if (process.env.AWS_ACCESS_KEY_ID && process.env.AWS_SECRET_ACCESS_KEY) {
+        return {
+            accessKeyId: process.env.AWS_ACCESS_KEY_ID,
+            secretAccessKey: process.env.AWS_SECRET_ACCESS_KEY,
+            sessionToken: process.env.AWS_SESSION_TOKEN
+        };
+    }
+
+    const credentialsFile = process.env.AWS_SHARED_CREDENTIALS_FILE || path.join(os.homedir(), '.aws', 'credentials');
+    const profile = process.env.AWS_PROFILE || 'default';
+
+    let contents: string;
+    try {
+        contents = await fs.readFile(credentialsFile, 'utf8');
+    } catch (error) {
+        return null;
+    }
+
+    const section = parseIni(contents)[profile];
+    if (!section || !section.aws_access_key_id || !section.aws_secret_access_key) {
+        return null;
+    }
+    return {
+        accessKeyId: section.aws_access_key_id,
+        secretAccessKey: section.aws_secret_access_key,
+        sessionToken: section.aws_session_token
+    };
+}
+
+/**
+ * Sign a request with AWS Signature Version 4 and return the headers to
+ * send, including `authorization` and `x-amz-date`.
+ */
+export function signAwsRequest(
+    request: SignableRequest,
+    credentials: AwsCredentials,
+    region: string,
+    service: string,
+    now: Date = new Date()
+): Record<string, string> {
+    const url = new URL(request.url);
+    const amzDate = now.toISOString().replace(/[:-]|\.\d{3}/g, '');
+    const dateStamp = amzDate.slice(0, 8);
+
+    const headers: Record<string, string> = {
+        ...lowercaseKeys(request.headers),
+        host: url.host,
+        'x-amz-date': amzDate
+    };
+    if (credentials.sessionToken) {
+        headers['x-amz-security-token'] = credentials.sessionToken;
+    }
+
+    const signedHeaderNames = Object.keys(headers).sort();
+    const canonicalHeaders = signedHeaderNames.map(name => `${name}:${headers[name].trim()}\n`).join('');
+    const signedHeaders = signedHeaderNames.join(';');
+
+    const canonicalRequest = [
+        request.method.toUpperCase(),
+        canonicalUri(url.pathname),
+        canonicalQuery(url.searchParams),
+        canonicalHeaders,
+        signedHeaders,
+        sha256Hex(request.body)
+    ].join('\n');
+
+    const scope = `${dateStamp}/${region}/${service}/aws4_request`;
+    const stringToSign = ['AWS4-HMAC-SHA256', amzDate, scope, sha256Hex(canonicalRequest)].join('\n');
+
+    const signingKey = [dateStamp, region, service, 'aws4_request']
+        .reduce<Buffer>((key, part) => hmac(key, part), Buffer.from(`AWS4${credentials.secretAccessKey}`));
+    const signature = createHmac('sha256', signingKey).update(stringToSign).digest('hex');
+
+    headers.authorization = `AWS4-HMAC-SHA256 Credential=${credentials.accessKeyId}/${scope}, ` +
+        `SignedHeaders=${signedHeaders}, Signature=${signature}`;
+    return headers;
+}
+
+// Non-S3 services expect each path segment to be URI-encoded twice; the
+// URL path is already encoded once
+function canonicalUri(pathname: string): string {
+    return pathname.split('/').map(segment => encodeRfc3986(segment)).join('/') || '/';
+}
+
+function canonicalQuery(params: URLSearchParams): string {
+    return [...params.entries()]
+        .map(([key, value]) => `${encodeRfc3986(key)}=${encodeRfc3986(value)}`)
+        .sort()
+        .join('&');
+}
+
+function encodeRfc3986(value: string): string {
+    return encodeURIComponent(value).replace(/[!'()*]/g, c => `%${c.charCodeAt(0).toString(16).toUpperCase()}`);
+}
+
+function lowercaseKeys(headers: Record<string, string>): Record<string, string> {
+    const result: Record<string, string> = {};
+    for (const [key, value] of Object.entries(headers)) {
+        result[key.toLowerCase()] = value;
+    }
+    return result;
+}
+
+function sha256Hex(data: string): string {
+    return createHash('sha256').update(data).digest('hex');
+}
+
+function hmac(key: Buffer, data: string): Buffer {
+    return createHmac('sha256', key).update(data).digest();
+}
+
+function parseIni(contents: string): Record<string, Record<string, string>> {
+    const sections: Record<string, Record<string, string>> = {};
+    let current: Record<string, string> | null = null;
+
+    for (const rawLine of contents.split('\n')) {
+        const line = rawLine.trim();
+        if (!line || line.startsWith('#') || line.startsWith(';')) {
+            continue;
+        }
+        const header = line.match(/^\[(.+)\]$/);
+        if (header) {
+            current = sections[header[1].trim()] = {};
+        } else if (current) {
+            const eq = line.indexOf('=');
+            if (eq > 0) {
+                current[line.slice(0, eq).trim()] = line.slice(eq + 1).trim();
+            }
+        }
+    }
+    return sections;
+}
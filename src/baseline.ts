@@ -0,0 +1,40 @@
+import * as fs from 'fs/promises';
+import * as path from 'path';
+import { ConfigError } from './errors';
+
+const BASELINE_NAME_PATTERN = /^[\w.-]+$/;
+
+/**
+ * Reference captures for visual regression checks, stored as
+ * `<screenshots_dir>/baselines/<name>.png`.
+ */
+export class BaselineStore {
+    private dir: string;
+
+    constructor(screenshotsDir: string) {
+        this.dir = path.join(screenshotsDir, 'baselines');
+    }
+
+    pathFor(name: string): string {
+        if (!BASELINE_NAME_PATTERN.test(name)) {
+            throw new ConfigError(`Invalid baseline name "${name}": use letters, digits, '.', '-' or '_'`);
+        }
+        return path.join(this.dir, `${name}.png`);
+    }
+
+    async save(name: string, image: Buffer): Promise<string> {
+        const file = this.pathFor(name);
+        await fs.mkdir(this.dir, { recursive: true });
+        await fs.writeFile(file, image);
+        return file;
+    }
+
+    async load(name: string): Promise<Buffer> {
+        const file = this.pathFor(name);
+        try {
+            return await fs.readFile(file);
+        } catch (error) {
+            throw new ConfigError(`No baseline named "${name}" - create it with \`baseline set ${name}\``);
+        }
+    }
+}
@@ -0,0 +1,52 @@
+import * as fs from 'fs/promises';
+import * as path from 'path';
+
+export interface BatchJob {
+    id: string;
+    submittedAt: string;
+    question?: string;
+    status: string;
+    answer?: string;
+    error?: string;
+}
+
+// Batches that will never produce an answer
+const FINAL_FAILURE_STATUSES = ['failed', 'expired', 'cancelled'];
+
+/**
+ * Batch API submissions awaiting (or holding) their results, stored as
+ * `<screenshots_dir>/batches.json`.
+ */
+export class BatchStore {
+    private file: string;
+
+    constructor(screenshotsDir: string) {
+        this.file = path.join(screenshotsDir, 'batches.json');
+    }
+
+    async list(): Promise<BatchJob[]> {
+        try {
+            return JSON.parse(await fs.readFile(this.file, 'utf8'));
+        } catch (error) {
+            return [];
+        }
+    }
+
+    async add(job: BatchJob): Promise<void> {
+        await this.write([...(await this.list()), job]);
+    }
+
+    async update(job: BatchJob): Promise<void> {
+        const jobs = await this.list();
+        await this.write(jobs.map(existing => existing.id === job.id ? job : existing));
+    }
+
+    static isPending(job: BatchJob): boolean {
+        return job.answer === undefined && !FINAL_FAILURE_STATUSES.includes(job.status);
+    }
+
+    private async write(jobs: BatchJob[]): Promise<void> {
+        await fs.mkdir(path.dirname(this.file), { recursive: true });
+        await fs.writeFile(this.file, JSON.stringify(jobs, null, 2));
+    }
+}
@@ -0,0 +1,107 @@
+import * as fs from 'fs/promises';
+import * as path from 'path';
+import sharp from 'sharp';
+
+// Long edge of the thumbnails written next to each capture
+const THUMBNAIL_SIZE = 320;
+
+/**
+ * A local record of what was analyzed: each capture is stored in
+ * `<screenshots_dir>` as `<timestamp>.<format>` with a small
+ * `<timestamp>.thumb.jpg`, next to a `<timestamp>.md` holding the question,
+ * model and answer.
+ */
+export class CaptureStore {
+    constructor(private dir: string, private format: string = 'png', private jpegQuality: number = 95) {}
+
+    /** Save the images and analysis, returning the image paths */
+    async save(images: Buffer[], question: string | undefined, analysis: string, at: Date = new Date(), model?: string): Promise<string[]> {
+        const files = await this.saveImages(images, at);
+        await this.saveRecord(question, analysis, files, at, model);
+        return files;
+    }
+
+    /** Save the images and their thumbnails, returning the image paths */
+    async saveImages(images: Buffer[], at: Date = new Date()): Promise<string[]> {
+        await fs.mkdir(this.dir, { recursive: true });
+        const base = captureName(at);
+        const extension = ({ jpeg: 'jpg', jpg: 'jpg', webp: 'webp' } as Record<string, string>)[this.format] || 'png';
+
+        const files: string[] = [];
+        for (const [i, image] of images.entries()) {
+            const name = images.length > 1 ? `${base}-${i + 1}` : base;
+            const file = path.join(this.dir, `${name}.${extension}`);
+            await fs.writeFile(file, await this.encode(image));
+            await fs.writeFile(path.join(this.dir, thumbnailName(name)), await thumbnail(image));
+            files.push(file);
+        }
+        return files;
+    }
+
+    /**
+     * Write the markdown record of an analysis, showing the thumbnails of
+     * `files` (saved captures) when there are any. Returns its path.
+     */
+    async saveRecord(question: string | undefined, analysis: string, files: string[], at: Date = new Date(), model?: string): Promise<string> {
+        await fs.mkdir(this.dir, { recursive: true });
+        const file = path.join(this.dir, `${captureName(at)}.md`);
+        await fs.writeFile(file, captureRecord(question, analysis, files, at, model));
+        return file;
+    }
+
+    /** The most recently saved capture image, if any */
+    async latest(): Promise<string | undefined> {
+        let names: string[];
+        try {
+            names = await fs.readdir(this.dir);
+        } catch (error) {
+            return undefined;
+        }
+        // Names sort by time
+        const [latest] = names
+            .filter(name => CAPTURE_IMAGE_PATTERN.test(name))
+            .sort()
+            .reverse();
+        return latest ? path.join(this.dir, latest) : undefined;
+    }
+
+    private async encode(image: Buffer): Promise<Buffer> {
+        switch (this.format) {
+            case 'jpeg':
+            case 'jpg':
+                return sharp(image).jpeg({ quality: this.jpegQuality }).toBuffer();
+            case 'webp':
+                return sharp(image).webp().toBuffer();
+            default:
+                return sharp(image).png().toBuffer();
+        }
+    }
+}
+
+const CAPTURE_IMAGE_PATTERN = /^\d{4}-\d{2}-\d{2}_\d{2}-\d{2}-\d{2}(-\d+)?\.(png|jpg|webp)$/;
+
+/** The markdown for one analysis, with links relative to its own directory */
+export function captureRecord(question: string | undefined, analysis: string, files: string[], at: Date, model?: string): string {
+    const details = [at.toLocaleString(), ...(model ? [`\`${model}\``] : [])].join(' · ');
+    // Thumbnails link to the full captures, so browsing records stays light
+    const images = files.map(file => `[![capture](${thumbnailName(path.parse(file).name)})](${path.basename(file)})`);
+    return [`# ${question || 'What is on screen?'}`, '', `*${details}*`, '', ...(images.length > 0 ? [images.join('\n'), ''] : []), analysis, ''].join('\n');
+}
+
+export function thumbnailName(captureName: string): string {
+    return `${captureName}.thumb.jpg`;
+}
+
+export async function thumbnail(image: Buffer): Promise<Buffer> {
+    return sharp(image)
+        .resize(THUMBNAIL_SIZE, THUMBNAIL_SIZE, { fit: 'inside', withoutEnlargement: true })
+        .jpeg({ quality: 80 })
+        .toBuffer();
+}
+
+/** A sortable, filesystem-safe name like `2025-01-31_14-05-09` */
+export function captureName(at: Date): string {
+    const pad = (n: number) => String(n).padStart(2, '0');
+    return `${at.getFullYear()}-${pad(at.getMonth() + 1)}-${pad(at.getDate())}_` +
+        `${pad(at.getHours())}-${pad(at.getMinutes())}-${pad(at.getSeconds())}`;
+}
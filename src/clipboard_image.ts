@@ -0,0 +1,86 @@
+import { execFile } from 'child_process';
+import * as fs from 'fs/promises';
+import * as os from 'os';
+import * as path from 'path';
+import { promisify } from 'util';
+import { CaptureError } from './errors';
+
+const execFileAsync = promisify(execFile);
+
+// Screenshots of large displays easily exceed execFile's 1MB default
+const MAX_OUTPUT_BYTES = 200 * 1024 * 1024;
+
+// AppleScript can't write binary data to stdout, so go through a file
+const macCopyClipboard = (file: string) => `
+set png to (the clipboard as «class PNGf»)
+set out to open for access (POSIX file ${JSON.stringify(file)}) with write permission
+set eof of out to 0
+write png to out
+close access out
+`;
+
+const WINDOWS_READ_CLIPBOARD = `
+Add-Type -AssemblyName System.Windows.Forms
+$image = [System.Windows.Forms.Clipboard]::GetImage()
+if ($image) {
+    $stream = New-Object System.IO.MemoryStream
+    $image.Save($stream, [System.Drawing.Imaging.ImageFormat]::Png)
+    [Convert]::ToBase64String($stream.ToArray())
+}
+`;
+
+/**
+ * Read the image on the system clipboard as a PNG, e.g. one taken with
+ * the OS's own snipping tool. Throws a `CaptureError` when the clipboard
+ * holds no image.
+ */
+export async function readClipboardImage(): Promise<Buffer> {
+    let image: Buffer;
+    try {
+        image = await readPlatformClipboard();
+    } catch (error) {
+        throw new CaptureError(`No image on the clipboard - copy a screenshot first${linuxToolHint()}: ${error}`);
+    }
+
+    if (image.length === 0) {
+        throw new CaptureError(`No image on the clipboard - copy a screenshot first${linuxToolHint()}`);
+    }
+    return image;
+}
+
+async function readPlatformClipboard(): Promise<Buffer> {
+    switch (process.platform) {
+        case 'darwin': {
+            const file = path.join(os.tmpdir(), `ai-screenshot-clipboard-${process.pid}-${Date.now()}.png`);
+            try {
+                await execFileAsync('osascript', ['-e', macCopyClipboard(file)]);
+                return await fs.readFile(file);
+            } finally {
+                await fs.rm(file, { force: true });
+            }
+        }
+        case 'win32': {
+            // Clipboard access needs a single-threaded apartment
+            const { stdout } = await execFileAsync(
+                'powershell',
+                ['-NoProfile', '-STA', '-Command', WINDOWS_READ_CLIPBOARD],
+                { maxBuffer: MAX_OUTPUT_BYTES }
+            );
+            return Buffer.from(stdout.trim(), 'base64');
+        }
+        default: {
+            const [command, args]: [string, string[]] = process.env.WAYLAND_DISPLAY
+                ? ['wl-paste', ['--type', 'image/png']]
+                : ['xclip', ['-selection', 'clipboard', '-target', 'image/png', '-out']];
+            const { stdout } = await execFileAsync(command, args, { encoding: 'buffer', maxBuffer: MAX_OUTPUT_BYTES });
+            return stdout;
+        }
+    }
+}
+
+function linuxToolHint(): string {
+    if (process.platform !== 'linux') {
+        return '';
+    }
+    return process.env.WAYLAND_DISPLAY ? ' (reading it needs wl-clipboard)' : ' (reading it needs xclip)';
+}
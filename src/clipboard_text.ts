@@ -0,0 +1,79 @@
+import { spawn } from 'child_process';
+import { CaptureError } from './errors';
+
+/** A fenced code block from a markdown answer */
+export interface CodeBlock {
+    /** The info string after the opening fence, e.g. `python`; empty when there is none */
+    language: string;
+    code: string;
+}
+
+/**
+ * The first fenced code block (``` or ~~~) in a markdown answer, without
+ * its fences. An unclosed block runs to the end of the text, as a
+ * truncated answer would. Returns undefined when there is no block.
+ */
+export function firstCodeBlock(markdown: string): CodeBlock | undefined {
+    const lines = markdown.split('\n');
+    const start = lines.findIndex(line => /^\s*(`{3,}|~{3,})/.test(line));
+    if (start === -1) {
+        return undefined;
+    }
+
+    const [, indent, fence, info] = lines[start].match(/^(\s*)(`{3,}|~{3,})(.*)$/)!;
+    const closing = new RegExp(`^\\s*${fence[0]}{${fence.length},}\\s*$`);
+    const body: string[] = [];
+    for (const line of lines.slice(start + 1)) {
+        if (closing.test(line)) {
+            break;
+        }
+        // Blocks nested in a list are indented along with their fence
+        body.push(line.startsWith(indent) ? line.slice(indent.length) : line);
+    }
+    return { language: info.trim().split(/\s+/)[0], code: body.join('\n').replace(/\s+$/, '') };
+}
+
+/** The commands that can put text on the clipboard from stdin, tried in order */
+export function clipboardWriteCommands(platform: NodeJS.Platform = process.platform): Array<[string, string[]]> {
+    switch (platform) {
+        case 'darwin':
+            return [['pbcopy', []]];
+        case 'win32':
+            // clip.exe mangles anything outside the console code page
+            return [['powershell', ['-NoProfile', '-Command', '[Console]::InputEncoding = [Text.Encoding]::UTF8; Set-Clipboard -Value ([Console]::In.ReadToEnd())']]];
+        default: {
+            const wayland: [string, string[]] = ['wl-copy', []];
+            const x11: [string, string[]] = ['xclip', ['-selection', 'clipboard']];
+            return process.env.WAYLAND_DISPLAY ? [wayland, x11] : [x11, ['xsel', ['--clipboard', '--input']]];
+        }
+    }
+}
+
+function pipeTo(command: string, args: string[], text: string): Promise<void> {
+    return new Promise((resolve, reject) => {
+        // xclip and wl-copy stay around to serve the selection, so don't wait on their output
+        const child = spawn(command, args, { stdio: ['pipe', 'ignore', 'ignore'] });
+        child.on('error', reject);
+        child.on('exit', code => code === 0 ? resolve() : reject(new Error(`${command} exited with code ${code}`)));
+        child.stdin.end(text);
+    });
+}
+
+/**
+ * Put `text` on the system clipboard. Throws a `CaptureError` when no
+ * clipboard tool is installed.
+ */
+export async function writeClipboardText(text: string): Promise<void> {
+    for (const [command, args] of clipboardWriteCommands()) {
+        try {
+            await pipeTo(command, args, text);
+            return;
+        } catch (error) {
+            if ((error as NodeJS.ErrnoException).code !== 'ENOENT') {
+                throw new CaptureError(`Copying with ${command} failed: ${error}`);
+            }
+        }
+    }
+    const hint = process.platform === 'linux' ? ' - install wl-clipboard, xclip or xsel' : '';
+    throw new CaptureError(`No clipboard tool found${hint}`);
+}
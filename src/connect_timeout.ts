@@ -0,0 +1,38 @@
+import * as http from 'http';
+import * as https from 'https';
+
+/**
+ * An axios `transport` that fails a request when its connection (including
+ * any proxy tunnel) isn't established within `timeoutMs`. axios's own
+ * `timeout` only fires once an open connection has gone idle. `onSent` is
+ * called once the request body has been sent.
+ */
+export function connectTimeoutTransport(timeoutMs: number, onSent?: () => void) {
+    return {
+        request(options: https.RequestOptions, callback: (res: http.IncomingMessage) => void): http.ClientRequest {
+            const client = options.protocol === 'https:' ? https : http;
+            const req = client.request(options, callback);
+
+            const timer = setTimeout(() => {
+                const error: NodeJS.ErrnoException = new Error(`could not connect within ${timeoutMs / 1000}s`);
+                error.code = 'ETIMEDOUT';
+                req.destroy(error);
+            }, timeoutMs);
+            const clear = () => clearTimeout(timer);
+
+            req.on('socket', socket => {
+                // Reused keep-alive sockets are already connected
+                if (socket.connecting) {
+                    socket.once('connect', clear);
+                } else {
+                    clear();
+                }
+            });
+            req.on('close', clear);
+            if (onSent) {
+                req.once('finish', onSent);
+            }
+            return req;
+        }
+    };
+}
@@ -0,0 +1,177 @@
+import * as fs from 'fs/promises';
+import * as net from 'net';
+import * as os from 'os';
+import * as path from 'path';
+import { AppError, ExitCode } from './errors';
+import { NotificationAction } from './notification';
+import { AnswerRecord } from './output';
+import { CaptureSource } from './screenshot';
+
+/** A request to a running daemon, one JSON object per line */
+export interface ControlRequest {
+    /**
+     * `copy-code`, `open-editor` and `follow-up` act on the daemon's last
+     * answer, for the buttons on its notifications; `clear-sticky` forgets
+     * its sticky question
+     */
+    command: 'ask' | 'status' | 'clear-sticky' | NotificationAction;
+    /**
+     * For `ask`: asked about a fresh capture; the daemon's usual question when
+     * missing. Required for `follow-up`.
+     */
+    question?: string;
+    source?: CaptureSource;
+}
+
+/** What `status` reports about a running daemon */
+export interface DaemonStatus {
+    pid: number;
+    /** ISO timestamp */
+    startedAt: string;
+    mode: string;
+    paused: boolean;
+    /** e.g. "Cmd+Shift+Space → screen"; empty outside hotkey mode */
+    hotkeys: string[];
+    analyses: number;
+    lastError?: { message: string; at: string };
+    provider: string;
+    model: string;
+}
+
+export interface ControlResponse {
+    ok: boolean;
+    answer?: string;
+    /** For `ask`: the answer with its provider, model, timing and token counts */
+    record?: AnswerRecord;
+    status?: DaemonStatus;
+    error?: string;
+}
+
+const CONTROL_SOURCES: CaptureSource[] = ['screen', 'clipboard', 'active-window', 'region'];
+
+/** Raised by clients when no daemon is listening */
+export class NoDaemonError extends AppError {
+    constructor() {
+        super('No daemon is running - start one with `ai-screenshot-analyzer run`', ExitCode.GeneralError);
+    }
+}
+
+/**
+ * Where a daemon listens: a socket in the config directory, or a named
+ * pipe on Windows. One per user, so only their shells can reach it.
+ */
+export function controlSocketPath(): string {
+    if (process.platform === 'win32') {
+        return `\\\\.\\pipe\\ai-screenshot-analyzer-${os.userInfo().username}`;
+    }
+    return path.join(os.homedir(), '.config', 'ai-screenshot-analyzer', 'daemon.sock');
+}
+
+/** Validate a request line from a client; undefined if it isn't one */
+export function parseControlRequest(line: string): ControlRequest | undefined {
+    let data: any;
+    try {
+        data = JSON.parse(line);
+    } catch (error) {
+        return undefined;
+    }
+    if (['status', 'clear-sticky', 'copy-code', 'open-editor'].includes(data?.command)) {
+        return { command: data.command };
+    }
+    if (data?.command === 'follow-up') {
+        return typeof data.question === 'string' && data.question.trim() ? { command: 'follow-up', question: data.question } : undefined;
+    }
+    if (data?.command !== 'ask') {
+        return undefined;
+    }
+    if (data.question !== undefined && typeof data.question !== 'string') {
+        return undefined;
+    }
+    if (data.source !== undefined && !CONTROL_SOURCES.includes(data.source)) {
+        return undefined;
+    }
+    return { command: 'ask', question: data.question, source: data.source };
+}
+
+/**
+ * Listen for requests from `ask` and other shells, answering each with
+ * `handle` - one request line per connection. A socket left behind by a
+ * daemon that crashed is replaced; one that a live daemon is listening on
+ * is an error.
+ */
+export async function startControlServer(handle: (request: ControlRequest) => Promise<ControlResponse>): Promise<net.Server> {
+    const socketPath = controlSocketPath();
+    if (process.platform !== 'win32') {
+        if (await isListening(socketPath)) {
+            throw new Error(`another daemon is already listening on ${socketPath}`);
+        }
+        await fs.mkdir(path.dirname(socketPath), { recursive: true });
+        await fs.rm(socketPath, { force: true });
+    }
+
+    const server = net.createServer(connection => {
+        let buffered = '';
+        connection.setEncoding('utf8');
+        const onData = async (chunk: string) => {
+            buffered += chunk;
+            const newline = buffered.indexOf('\n');
+            if (newline < 0) {
+                return;
+            }
+            // One request per connection: anything after its line is ignored
+            connection.off('data', onData);
+            const line = buffered.slice(0, newline);
+            buffered = '';
+            const request = parseControlRequest(line);
+            const response = request
+                ? await handle(request).catch(error => ({ ok: false, error: String(error instanceof Error ? error.message : error) }))
+                : { ok: false, error: 'Invalid request' };
+            connection.end(JSON.stringify(response) + '\n');
+        };
+        connection.on('data', onData);
+        connection.on('error', () => {});
+    });
+
+    await new Promise<void>((resolve, reject) => {
+        server.once('error', reject);
+        server.listen(socketPath, () => resolve());
+    });
+    if (process.platform !== 'win32') {
+        await fs.chmod(socketPath, 0o600);
+    }
+    return server;
+}
+
+/** Send one request to the running daemon and wait for its answer */
+export function sendControlRequest(request: ControlRequest): Promise<ControlResponse> {
+    return new Promise((resolve, reject) => {
+        let buffered = '';
+        const connection = net.createConnection(controlSocketPath(), () => {
+            connection.write(JSON.stringify(request) + '\n');
+        });
+        connection.setEncoding('utf8');
+        connection.on('data', (chunk: string) => {
+            buffered += chunk;
+        });
+        connection.on('end', () => {
+            try {
+                resolve(JSON.parse(buffered));
+            } catch (error) {
+                reject(new Error(`Invalid response from the daemon: ${buffered.slice(0, 200)}`));
+            }
+        });
+        connection.on('error', (error: NodeJS.ErrnoException) => {
+            reject(error.code === 'ENOENT' || error.code === 'ECONNREFUSED' ? new NoDaemonError() : error);
+        });
+    });
+}
+
+function isListening(socketPath: string): Promise<boolean> {
+    return new Promise(resolve => {
+        const probe = net.createConnection(socketPath, () => {
+            probe.end();
+            resolve(true);
+        });
+        probe.on('error', () => resolve(false));
+    });
+}
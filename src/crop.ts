@@ -0,0 +1,43 @@
+/** One `--crop` coordinate: pixels, or a percentage of the image size */
+export interface CropValue {
+    value: number;
+    percent: boolean;
+}
+
+export interface CropSpec {
+    x: CropValue;
+    y: CropValue;
+    width: CropValue;
+    height: CropValue;
+}
+
+/**
+ * Parse `x,y,w,h`, where each value is pixels (`800`) or a percentage of
+ * the capture (`50%`), e.g. `50%,0,50%,100%` for the right half. Returns
+ * undefined when it can't be parsed.
+ */
+export function parseCrop(spec: string): CropSpec | undefined {
+    const parts = spec.split(',').map(part => part.trim().match(/^(\d+(?:\.\d+)?)(%?)$/));
+    if (parts.length !== 4 || parts.some(part => !part)) {
+        return undefined;
+    }
+    const [x, y, width, height] = parts.map(part => ({ value: Number(part![1]), percent: part![2] === '%' }));
+    return { x, y, width, height };
+}
+
+/**
+ * The crop as a pixel region of a `width` x `height` image, clipped to the
+ * image. Undefined when nothing of the image is left.
+ */
+export function cropRegion(crop: CropSpec, width: number, height: number): { left: number; top: number; width: number; height: number } | undefined {
+    const resolve = (value: CropValue, size: number) => Math.round(value.percent ? value.value / 100 * size : value.value);
+    const left = Math.min(resolve(crop.x, width), width);
+    const top = Math.min(resolve(crop.y, height), height);
+    const region = {
+        left,
+        top,
+        width: Math.min(resolve(crop.width, width), width - left),
+        height: Math.min(resolve(crop.height, height), height - top)
+    };
+    return region.width > 0 && region.height > 0 ? region : undefined;
+}
@@ -0,0 +1,90 @@
+import { execFile } from 'child_process';
+import { promisify } from 'util';
+import sharp from 'sharp';
+
+const execFileAsync = promisify(execFile);
+
+/**
+ * Pointer position on the primary display, in the same logical units as
+ * the display size, so it can be scaled onto a capture of any resolution.
+ */
+export interface CursorPosition {
+    x: number;
+    y: number;
+    screenWidth: number;
+    screenHeight: number;
+}
+
+// AppKit measures from the bottom-left corner of the main screen
+const MAC_CURSOR = `
+ObjC.import('AppKit');
+const frame = $.NSScreen.screens.objectAtIndex(0).frame;
+const location = $.NSEvent.mouseLocation;
+JSON.stringify({ x: location.x, y: frame.size.height - location.y, screenWidth: frame.size.width, screenHeight: frame.size.height });
+`;
+
+const WINDOWS_CURSOR = `
+Add-Type -AssemblyName System.Windows.Forms
+$position = [System.Windows.Forms.Cursor]::Position
+$bounds = [System.Windows.Forms.Screen]::PrimaryScreen.Bounds
+@{ x = $position.X; y = $position.Y; screenWidth = $bounds.Width; screenHeight = $bounds.Height } | ConvertTo-Json -Compress
+`;
+
+// A classic arrow pointer, 16x24 at 1x, tip at the origin
+const pointerSvg = (scale: number) => `<svg xmlns="http://www.w3.org/2000/svg" width="${Math.ceil(16 * scale)}" height="${Math.ceil(24 * scale)}">
+<path transform="scale(${scale})" d="M1 1 L1 19 L5.5 14.5 L8.5 21.5 L11.5 20.2 L8.6 13.4 L14.5 13.4 Z" fill="black" stroke="white" stroke-width="1.2" stroke-linejoin="round"/>
+</svg>`;
+
+export async function cursorPosition(): Promise<CursorPosition | null> {
+    try {
+        switch (process.platform) {
+            case 'darwin': {
+                const { stdout } = await execFileAsync('osascript', ['-l', 'JavaScript', '-e', MAC_CURSOR]);
+                return JSON.parse(stdout);
+            }
+            case 'win32': {
+                const { stdout } = await execFileAsync('powershell', ['-NoProfile', '-Command', WINDOWS_CURSOR]);
+                return JSON.parse(stdout);
+            }
+            default: {
+                const [{ stdout: location }, { stdout: geometry }] = await Promise.all([
+                    execFileAsync('xdotool', ['getmouselocation', '--shell']),
+                    execFileAsync('xdotool', ['getdisplaygeometry'])
+                ]);
+                const [screenWidth, screenHeight] = geometry.trim().split(/\s+/).map(Number);
+                const x = Number(location.match(/^X=(\d+)/m)?.[1]);
+                const y = Number(location.match(/^Y=(\d+)/m)?.[1]);
+                return { x, y, screenWidth, screenHeight };
+            }
+        }
+    } catch (error) {
+        console.warn('Could not read the cursor position:', error);
+        return null;
+    }
+}
+
+/**
+ * Draw a pointer onto a capture of the primary display. The capture may be
+ * at a higher resolution than the cursor's coordinates (HiDPI displays),
+ * so the position and pointer size are scaled to match.
+ */
+export async function drawCursor(image: Buffer, cursor: CursorPosition): Promise<Buffer> {
+    const { width = 0, height = 0 } = await sharp(image).metadata();
+    const scale = width / cursor.screenWidth;
+    const left = Math.round(cursor.x * scale);
+    const top = Math.round(cursor.y * (height / cursor.screenHeight));
+
+    if (!(scale > 0) || left < 0 || top < 0 || left >= width || top >= height) {
+        // Off this display, e.g. on a secondary monitor
+        return image;
+    }
+
+    // Clip the pointer at the image edge, which composite doesn't allow overflowing
+    const pointer = await sharp(Buffer.from(pointerSvg(scale))).png().toBuffer();
+    const { width: pointerWidth = 0, height: pointerHeight = 0 } = await sharp(pointer).metadata();
+    const clipped = await sharp(pointer)
+        .extract({ left: 0, top: 0, width: Math.min(pointerWidth, width - left), height: Math.min(pointerHeight, height - top) })
+        .toBuffer();
+
+    return sharp(image).composite([{ input: clipped, left, top }]).png().toBuffer();
+}
@@ -0,0 +1,149 @@
+import { execFile } from 'child_process';
+import { promisify } from 'util';
+import screenshot from 'screenshot-desktop';
+import sharp from 'sharp';
+import { CaptureError } from './errors';
+import { listGdiDisplays } from './gdi_capture';
+
+const execFileAsync = promisify(execFile);
+
+/** A connected display, numbered in the order the platform lists them */
+export interface DisplayInfo {
+    index: number;
+    /** The id screenshot-desktop captures by */
+    id: number | string;
+    name: string;
+    width?: number;
+    height?: number;
+    /** Physical pixels per logical pixel, where the platform reports it (Windows) */
+    scaleFactor?: number;
+}
+
+// Backing scale factor of each screen, the primary (menu bar) screen first
+const MAC_SCALE_FACTORS = `
+ObjC.import('AppKit');
+const screens = $.NSScreen.screens;
+const result = [];
+for (let i = 0; i < screens.count; i++) {
+    const screen = screens.objectAtIndex(i);
+    result.push({ name: screen.localizedName ? ObjC.unwrap(screen.localizedName) : '', scale: screen.backingScaleFactor });
+}
+JSON.stringify(result);
+`;
+
+/**
+ * The connected displays. `native` lists them with the GDI backend on
+ * Windows (see `native_windows_capture`).
+ */
+export async function listDisplays(native: boolean = false): Promise<DisplayInfo[]> {
+    if (native && process.platform === 'win32') {
+        return listGdiDisplays();
+    }
+    try {
+        // Some platforms also report the resolution and scaling; the typings don't say so
+        const displays: Array<{ id: number | string; name?: string; width?: number; height?: number; dpiScale?: number }> =
+            await screenshot.listDisplays();
+        return displays.map((display, index) => ({
+            index,
+            id: display.id,
+            name: display.name || `Display ${index}`,
+            width: display.width,
+            height: display.height,
+            scaleFactor: display.dpiScale
+        }));
+    } catch (error) {
+        throw new CaptureError(`Failed to list displays: ${error}`);
+    }
+}
+
+/** The `--screen` value that captures every display at once */
+export const ALL_SCREENS = 'all';
+
+export function isAllScreens(selector: string | undefined): boolean {
+    return selector?.trim().toLowerCase() === ALL_SCREENS;
+}
+
+/**
+ * Pick a display by index (as shown by the `screens` command) or by name,
+ * case-insensitively and allowing a partial name.
+ */
+export function selectDisplay(displays: DisplayInfo[], selector: string): DisplayInfo | undefined {
+    const trimmed = selector.trim();
+    if (/^\d+$/.test(trimmed)) {
+        return displays.find(display => display.index === Number(trimmed));
+    }
+
+    const needle = trimmed.toLowerCase();
+    return displays.find(display => display.name.toLowerCase() === needle)
+        || displays.find(display => display.name.toLowerCase().includes(needle));
+}
+
+/**
+ * The display's resolution, capturing it to measure when the platform
+ * didn't report one.
+ */
+export async function displayResolution(display: DisplayInfo): Promise<{ width: number; height: number }> {
+    if (display.width && display.height) {
+        return { width: display.width, height: display.height };
+    }
+    const metadata = await sharp(await screenshot({ screen: display.id, format: 'png' })).metadata();
+    return { width: metadata.width || 0, height: metadata.height || 0 };
+}
+
+/**
+ * Composite screenshots side by side, left to right and top-aligned, into
+ * one wide image. Displays of different heights leave black bands below.
+ */
+export async function stitchImages(images: Buffer[]): Promise<Buffer> {
+    const sizes = await Promise.all(images.map(image => sharp(image).metadata()));
+    const width = sizes.reduce((total, size) => total + (size.width || 0), 0);
+    const height = Math.max(...sizes.map(size => size.height || 0));
+
+    let left = 0;
+    const layers = images.map((input, i) => {
+        const layer = { input, left, top: 0 };
+        left += sizes[i].width || 0;
+        return layer;
+    });
+
+    return sharp({ create: { width, height, channels: 3, background: { r: 0, g: 0, b: 0 } } })
+        .composite(layers)
+        .png()
+        .toBuffer();
+}
+
+/**
+ * How many physical pixels make up one logical pixel on `display` (the
+ * primary display when undefined): 2 on Retina displays. Linux has no
+ * standard way to tell, so it reports 1; configure `display_scale` there.
+ */
+export async function detectScaleFactor(display?: DisplayInfo): Promise<number> {
+    try {
+        switch (process.platform) {
+            case 'darwin': {
+                const { stdout } = await execFileAsync('osascript', ['-l', 'JavaScript', '-e', MAC_SCALE_FACTORS]);
+                const screens: Array<{ name: string; scale: number }> = JSON.parse(stdout);
+                const match = display ? screens.find(screen => screen.name === display.name) : screens[0];
+                return match?.scale || 1;
+            }
+            case 'win32':
+                return (display ?? (await listDisplays())[0])?.scaleFactor || 1;
+            default:
+                return 1;
+        }
+    } catch (error) {
+        console.warn('Could not detect the display scale factor:', error);
+        return 1;
+    }
+}
+
+/**
+ * The scale factor configured for `display` in `display_scale`, keyed by
+ * index or name (case-insensitive).
+ */
+export function configuredScale(overrides: Record<string, number>, display: DisplayInfo): number | undefined {
+    const key = Object.keys(overrides).find(key =>
+        key.trim() === String(display.index) || key.trim().toLowerCase() === display.name.toLowerCase()
+    );
+    return key !== undefined ? overrides[key] : undefined;
+}
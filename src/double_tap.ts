@@ -0,0 +1,74 @@
+// Both taps, and the gap between them, must fit in this time
+export const DOUBLE_TAP_WINDOW_MS = 400;
+
+const MODIFIER_NAMES: Record<string, string> = {
+    CMD: 'META',
+    COMMAND: 'META',
+    META: 'META',
+    WIN: 'META',
+    SUPER: 'META',
+    CTRL: 'CTRL',
+    CONTROL: 'CTRL',
+    ALT: 'ALT',
+    OPTION: 'ALT',
+    SHIFT: 'SHIFT'
+};
+
+/**
+ * Parse a modifier like "right cmd", "Right-Cmd" or "left ctrl" into the
+ * key name the keyboard listener reports ("RIGHT META"). Undefined for
+ * anything that isn't a left or right modifier.
+ */
+export function parseModifierKey(name: string): string | undefined {
+    const [side, modifier, ...rest] = name.trim().toUpperCase().split(/[\s_+-]+/);
+    if (rest.length > 0 || !['LEFT', 'RIGHT'].includes(side) || !MODIFIER_NAMES[modifier]) {
+        return undefined;
+    }
+    return `${side} ${MODIFIER_NAMES[modifier]}`;
+}
+
+/**
+ * Spots a modifier tapped twice in quick succession on its own. Holding
+ * it, or pressing another key in between (a normal shortcut), doesn't
+ * count.
+ */
+export class DoubleTapDetector {
+    private pressedAt: number | null = null;
+    private lastTapAt: number | null = null;
+
+    constructor(private key: string, private windowMs: number = DOUBLE_TAP_WINDOW_MS) {}
+
+    /** Feed every key event; returns true when one completes a double tap */
+    handle(key: string, down: boolean, now: number): boolean {
+        if (key !== this.key) {
+            if (down) {
+                this.reset();
+            }
+            return false;
+        }
+
+        if (down) {
+            // Auto-repeat sends more key-downs while it's held
+            this.pressedAt = this.pressedAt ?? now;
+            return false;
+        }
+
+        const tapped = this.pressedAt !== null && now - this.pressedAt < this.windowMs;
+        this.pressedAt = null;
+        if (!tapped) {
+            this.lastTapAt = null;
+            return false;
+        }
+        if (this.lastTapAt !== null && now - this.lastTapAt <= this.windowMs) {
+            this.lastTapAt = null;
+            return true;
+        }
+        this.lastTapAt = now;
+        return false;
+    }
+
+    private reset(): void {
+        this.pressedAt = null;
+        this.lastTapAt = null;
+    }
+}
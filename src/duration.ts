@@ -0,0 +1,30 @@
+const UNIT_MS: Record<string, number> = { ms: 1, s: 1000, m: 60 * 1000 };
+
+/**
+ * Parse a duration like `500ms`, `2s`, `1.5s` or `1m` into milliseconds.
+ * A bare number is taken as seconds. Returns NaN when it can't be parsed.
+ */
+export function parseDuration(value: string): number {
+    const match = value.trim().toLowerCase().match(/^(\d+(?:\.\d+)?)\s*(ms|s|m)?$/);
+    if (!match) {
+        return NaN;
+    }
+    return Number(match[1]) * UNIT_MS[match[2] || 's'];
+}
+
+/** Format milliseconds for messages and prompts: `500ms`, `2s`, `1.5s` */
+export function formatDuration(ms: number): string {
+    return ms < 1000 ? `${ms}ms` : `${Number((ms / 1000).toFixed(2))}s`;
+}
+
+/** Format a long span like a daemon's uptime: `45s`, `12m 5s`, `3h 2m`, `2d 4h` */
+export function formatUptime(ms: number): string {
+    const seconds = Math.floor(ms / 1000);
+    const units: Array<[string, number]> = [['d', Math.floor(seconds / 86400)], ['h', Math.floor(seconds / 3600) % 24], ['m', Math.floor(seconds / 60) % 60], ['s', seconds % 60]];
+    const first = units.findIndex(([, value]) => value > 0);
+    if (first < 0) {
+        return '0s';
+    }
+    // The two largest units are precise enough
+    return units.slice(first, first + 2).filter(([, value]) => value > 0).map(([unit, value]) => `${value}${unit}`).join(' ');
+}
@@ -0,0 +1,80 @@
+/**
+ * Process exit codes, one per failure class, so shell scripts and
+ * launcher integrations can react to what went wrong.
+ */
+export enum ExitCode {
+    Success = 0,
+    GeneralError = 1,
+    ConfigError = 2,
+    PermissionDenied = 3,
+    CaptureFailed = 4,
+    ProviderAuth = 5,
+    RateLimited = 6,
+    Timeout = 7,
+    Cancelled = 8,
+}
+
+export class AppError extends Error {
+    constructor(message: string, public readonly exitCode: ExitCode) {
+        super(message);
+        this.name = new.target.name;
+    }
+}
+
+export class ConfigError extends AppError {
+    constructor(message: string) {
+        super(message, ExitCode.ConfigError);
+    }
+}
+
+export class PermissionDeniedError extends AppError {
+    constructor(message: string) {
+        super(message, ExitCode.PermissionDenied);
+    }
+}
+
+export class CaptureError extends AppError {
+    constructor(message: string) {
+        super(message, ExitCode.CaptureFailed);
+    }
+}
+
+export class ProviderAuthError extends AppError {
+    constructor(message: string) {
+        super(message, ExitCode.ProviderAuth);
+    }
+}
+
+export class RateLimitError extends AppError {
+    /** How long the provider asked us to wait, when it said so */
+    constructor(message: string, public readonly retryAfterMs?: number) {
+        super(message, ExitCode.RateLimited);
+    }
+}
+
+/** Raised when a request is refused because too many are already in progress */
+export class RequestDroppedError extends AppError {
+    constructor(message: string) {
+        super(message, ExitCode.RateLimited);
+    }
+}
+
+export class TimeoutError extends AppError {
+    constructor(message: string) {
+        super(message, ExitCode.Timeout);
+    }
+}
+
+/** Raised when the user aborts an analysis before it finishes */
+export class CancelledError extends AppError {
+    constructor(message: string = 'Analysis cancelled') {
+        super(message, ExitCode.Cancelled);
+    }
+}
+
+export function exitCodeFor(error: unknown): ExitCode {
+    if (error instanceof AppError) {
+        return error.exitCode;
+    }
+    return ExitCode.GeneralError;
+}
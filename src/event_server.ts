@@ -0,0 +1,82 @@
+import { randomBytes } from 'crypto';
+import { EventEmitter } from 'events';
+import * as fs from 'fs/promises';
+import * as os from 'os';
+import * as path from 'path';
+import { WebSocket, WebSocketServer } from 'ws';
+
+// Where the URL (with its token) is written, for editor plugins to find
+const URL_FILE = path.join(os.homedir(), '.config', 'ai-screenshot-analyzer', 'websocket_url');
+
+/** A message sent to every client, as JSON */
+export type DaemonEvent =
+    | { type: 'capture_start'; source: string }
+    | { type: 'capture_end'; source: string }
+    | { type: 'capture_error'; source: string; error: string }
+    | { type: 'analysis_start' }
+    | { type: 'token'; text: string }
+    | { type: 'analysis_end'; answer: string }
+    | { type: 'analysis_error'; error: string };
+
+/**
+ * Translate the daemon's internal events into messages for clients.
+ * `subscribe` returns a function that unsubscribes again.
+ */
+export function subscribeDaemonEvents(events: EventEmitter, send: (event: DaemonEvent) => void): () => void {
+    const message = (error: unknown) => error instanceof Error ? error.message : String(error);
+    const listeners: Record<string, (...args: any[]) => void> = {
+        captureStart: (source: string) => send({ type: 'capture_start', source }),
+        captureEnd: (source: string, error?: unknown) => send(error
+            ? { type: 'capture_error', source, error: message(error) }
+            : { type: 'capture_end', source }),
+        analysisStart: () => send({ type: 'analysis_start' }),
+        token: (text: string) => send({ type: 'token', text }),
+        analysisEnd: (answer?: string, error?: unknown) => send(error
+            ? { type: 'analysis_error', error: message(error) }
+            : { type: 'analysis_end', answer: answer || '' })
+    };
+    for (const [name, listener] of Object.entries(listeners)) {
+        events.on(name, listener);
+    }
+    return () => {
+        for (const [name, listener] of Object.entries(listeners)) {
+            events.off(name, listener);
+        }
+    };
+}
+
+/**
+ * Serve the daemon's capture and analysis events, including each token
+ * as it arrives, over a WebSocket on localhost - for browser overlays and
+ * editor plugins. Clients must pass the random token from the printed URL,
+ * so web pages can't listen in on what's on screen.
+ */
+export async function startEventServer(events: EventEmitter, port: number): Promise<WebSocketServer> {
+    const token = randomBytes(16).toString('hex');
+    const server = new WebSocketServer({
+        host: '127.0.0.1',
+        port,
+        verifyClient: ({ req }: { req: { url?: string } }) =>
+            new URL(req.url || '/', 'ws://127.0.0.1').searchParams.get('token') === token
+    });
+    await new Promise<void>((resolve, reject) => {
+        server.once('listening', resolve);
+        server.once('error', reject);
+    });
+
+    const unsubscribe = subscribeDaemonEvents(events, event => {
+        const data = JSON.stringify(event);
+        for (const client of server.clients) {
+            if (client.readyState === WebSocket.OPEN) {
+                client.send(data);
+            }
+        }
+    });
+    server.on('close', unsubscribe);
+
+    const url = `ws://127.0.0.1:${port}/?token=${token}`;
+    await fs.mkdir(path.dirname(URL_FILE), { recursive: true });
+    await fs.writeFile(URL_FILE, url + '\n', { mode: 0o600 });
+    console.log(`📡 Streaming events at ${url} (also written to ${URL_FILE})`);
+    return server;
+}
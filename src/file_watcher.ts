@@ -0,0 +1,53 @@
+import * as fs from 'fs';
+import * as path from 'path';
+
+// Editors often save in several steps (truncate, write, rename)
+const DEBOUNCE_MS = 300;
+
+/**
+ * Call `onChange` once a burst of changes to any of `files` settles.
+ * Directories are watched rather than the files themselves, so editors
+ * that save by replacing the file don't end the watch. Returns a function
+ * that stops watching.
+ */
+export function watchFiles(files: string[], onChange: () => void, debounceMs: number = DEBOUNCE_MS): () => void {
+    const byDir = new Map<string, Set<string>>();
+    for (const file of files.map(file => path.resolve(file))) {
+        const names = byDir.get(path.dirname(file)) || new Set<string>();
+        names.add(path.basename(file));
+        byDir.set(path.dirname(file), names);
+    }
+
+    let timer: NodeJS.Timeout | null = null;
+    const watchers: fs.FSWatcher[] = [];
+    for (const [dir, names] of byDir) {
+        try {
+            const watcher = fs.watch(dir, (event, filename) => {
+                // Some platforms don't report the name; treat that as a change
+                if (filename && !names.has(filename.toString())) {
+                    return;
+                }
+                if (timer) {
+                    clearTimeout(timer);
+                }
+                timer = setTimeout(() => {
+                    timer = null;
+                    onChange();
+                }, debounceMs);
+            });
+            watcher.on('error', () => {});
+            watchers.push(watcher);
+        } catch (error) {
+            // A missing directory has nothing to reload yet
+        }
+    }
+
+    return () => {
+        if (timer) {
+            clearTimeout(timer);
+        }
+        for (const watcher of watchers) {
+            watcher.close();
+        }
+    };
+}
@@ -0,0 +1,118 @@
+import { execFile } from 'child_process';
+import { promisify } from 'util';
+import { DisplayInfo } from './displays';
+import { CaptureError } from './errors';
+
+const execFileAsync = promisify(execFile);
+
+// A DPI-aware GDI fallback for Windows (`native_windows_capture`): displays
+// are copied with CopyFromScreen and windows rendered with PrintWindow, from
+// a PowerShell script. Not Windows Graphics Capture or DXGI duplication, so
+// protected content (DRM video, some secure windows) comes out black.
+
+// Screenshots of large displays easily exceed execFile's 1MB default
+const MAX_OUTPUT_BYTES = 200 * 1024 * 1024;
+
+// Per-monitor DPI awareness (v2) makes every API below work in physical
+// pixels, so displays scaled to 125-200% aren't cropped or blurred, which
+// is the usual failure of captures from a DPI-unaware process.
+const WIN32_NATIVE = `
+Add-Type @'
+using System;
+using System.Runtime.InteropServices;
+public static class Native {
+    [StructLayout(LayoutKind.Sequential)] public struct RECT { public int Left, Top, Right, Bottom; }
+    [StructLayout(LayoutKind.Sequential)] public struct POINT { public int X, Y; }
+    [DllImport("user32.dll")] public static extern bool SetProcessDpiAwarenessContext(IntPtr value);
+    [DllImport("user32.dll")] public static extern bool GetWindowRect(IntPtr hWnd, out RECT rect);
+    [DllImport("user32.dll")] public static extern bool PrintWindow(IntPtr hWnd, IntPtr hdc, uint flags);
+    [DllImport("user32.dll")] public static extern IntPtr MonitorFromPoint(POINT pt, uint flags);
+    [DllImport("shcore.dll")] public static extern int GetDpiForMonitor(IntPtr monitor, int type, out uint dpiX, out uint dpiY);
+}
+'@
+[void][Native]::SetProcessDpiAwarenessContext([IntPtr]-4)
+Add-Type -AssemblyName System.Windows.Forms, System.Drawing
+function Send-Png($bitmap) {
+    $stream = New-Object System.IO.MemoryStream
+    $bitmap.Save($stream, [System.Drawing.Imaging.ImageFormat]::Png)
+    [Convert]::ToBase64String($stream.ToArray())
+}
+`;
+
+const WIN32_LIST_DISPLAYS = `${WIN32_NATIVE}
+$screens = [System.Windows.Forms.Screen]::AllScreens
+$displays = for ($i = 0; $i -lt $screens.Count; $i++) {
+    $bounds = $screens[$i].Bounds
+    $point = New-Object Native+POINT
+    $point.X = $bounds.X + 1; $point.Y = $bounds.Y + 1
+    $dpiX = [uint32]96; $dpiY = [uint32]96
+    [void][Native]::GetDpiForMonitor([Native]::MonitorFromPoint($point, 2), 0, [ref]$dpiX, [ref]$dpiY)
+    [pscustomobject]@{ index = $i; name = $screens[$i].DeviceName; width = $bounds.Width; height = $bounds.Height; scaleFactor = $dpiX / 96 }
+}
+ConvertTo-Json -Compress -InputObject @($displays)
+`;
+
+// Display by index, or the primary display
+const win32CaptureDisplay = (index?: number) => `${WIN32_NATIVE}
+$bounds = ${index === undefined ? '[System.Windows.Forms.Screen]::PrimaryScreen.Bounds' : `[System.Windows.Forms.Screen]::AllScreens[${index}].Bounds`}
+if (-not $bounds) { throw 'display not found' }
+$bitmap = New-Object System.Drawing.Bitmap $bounds.Width, $bounds.Height
+$graphics = [System.Drawing.Graphics]::FromImage($bitmap)
+$graphics.CopyFromScreen($bounds.X, $bounds.Y, 0, 0, $bitmap.Size)
+Send-Png $bitmap
+`;
+
+// PrintWindow asks the window to render itself, so it works while covered;
+// PW_RENDERFULLCONTENT (2) includes DirectX and browser content
+const win32CaptureWindow = (hwnd: number) => `${WIN32_NATIVE}
+$rect = New-Object Native+RECT
+if (-not [Native]::GetWindowRect([IntPtr]${hwnd}, [ref]$rect)) { throw 'window not found' }
+$bitmap = New-Object System.Drawing.Bitmap ($rect.Right - $rect.Left), ($rect.Bottom - $rect.Top)
+$graphics = [System.Drawing.Graphics]::FromImage($bitmap)
+$hdc = $graphics.GetHdc()
+$printed = [Native]::PrintWindow([IntPtr]${hwnd}, $hdc, 2)
+$graphics.ReleaseHdc($hdc)
+if (-not $printed) { throw 'PrintWindow failed' }
+Send-Png $bitmap
+`;
+
+async function runPowerShell(script: string): Promise<string> {
+    const { stdout } = await execFileAsync('powershell', ['-NoProfile', '-Command', script], { maxBuffer: MAX_OUTPUT_BYTES });
+    return stdout.trim();
+}
+
+async function capturePng(script: string, what: string): Promise<Buffer> {
+    let image: Buffer;
+    try {
+        image = Buffer.from(await runPowerShell(script), 'base64');
+    } catch (error) {
+        throw new CaptureError(`Failed to capture ${what}: ${error}`);
+    }
+    if (image.length === 0) {
+        throw new CaptureError(`Failed to capture ${what}: PowerShell returned no image`);
+    }
+    return image;
+}
+
+/**
+ * Displays in `Screen.AllScreens` order (the order screenshot-desktop uses
+ * too, so indexes match), with physical resolution and DPI scaling.
+ */
+export async function listGdiDisplays(): Promise<DisplayInfo[]> {
+    try {
+        const displays: Array<Omit<DisplayInfo, 'id'>> = JSON.parse(await runPowerShell(WIN32_LIST_DISPLAYS));
+        return displays.map(display => ({ ...display, id: display.index }));
+    } catch (error) {
+        throw new CaptureError(`Failed to list displays: ${error}`);
+    }
+}
+
+/** Capture a display by index (the primary display when undefined) as a PNG */
+export async function captureGdiDisplay(index?: number): Promise<Buffer> {
+    return capturePng(win32CaptureDisplay(index), index === undefined ? 'the primary display' : `display ${index}`);
+}
+
+/** Capture one window as a PNG, even when other windows cover it */
+export async function captureGdiWindow(hwnd: number): Promise<Buffer> {
+    return capturePng(win32CaptureWindow(hwnd), `window ${hwnd}`);
+}
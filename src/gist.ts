@@ -0,0 +1,129 @@
+import axios from 'axios';
+import { execFile } from 'child_process';
+import * as fs from 'fs/promises';
+import * as os from 'os';
+import * as path from 'path';
+import { ConfigError } from './errors';
+import { HistoryEntry } from './history_store';
+import { keychainSecret } from './keychain';
+import { proxyAgent, proxyFor } from './proxy';
+import { redactSecrets } from './redact';
+
+const GIST_API = 'https://api.github.com/gists';
+const ANSWER_FILE = 'answer.md';
+
+/** A shared answer's gist, and why its screenshots are missing if they are */
+export interface SharedGist {
+    url: string;
+    imageError?: string;
+}
+
+/**
+ * The GitHub token for gists: `github_token` from the config, then the
+ * system keychain (account `github`), then GITHUB_TOKEN or GH_TOKEN.
+ */
+export async function githubToken(configured?: string, env: NodeJS.ProcessEnv = process.env): Promise<string> {
+    const token = configured || await keychainSecret('github') || env.GITHUB_TOKEN || env.GH_TOKEN;
+    if (!token) {
+        throw new ConfigError('No GitHub token - set github_token in the config file, store one in the keychain ' +
+            'under ai-screenshot-analyzer / github, or set GITHUB_TOKEN. It needs the gist scope.');
+    }
+    return token;
+}
+
+/** File names for the screenshots in the gist, keeping their extensions */
+export function gistImageNames(files: string[]): string[] {
+    return files.map((file, i) => `screenshot${files.length > 1 ? `-${i + 1}` : ''}${path.extname(file)}`);
+}
+
+/** The gist's markdown: the question, when and by which model, the screenshots, the answer and any follow-ups */
+export function gistMarkdown(entry: HistoryEntry, imageUrls: string[] = []): string {
+    const details = [new Date(entry.at).toLocaleString(), ...(entry.model ? [`\`${entry.model}\``] : [])].join(' · ');
+    const images = imageUrls.map((url, i) => `![screenshot${imageUrls.length > 1 ? ` ${i + 1}` : ''}](${url})`);
+    return redactSecrets([
+        `# ${entry.prompt || 'What is on screen?'}`, '', `*${details}*`, '',
+        ...(images.length > 0 ? [images.join('\n'), ''] : []),
+        entry.answer, '',
+        ...(entry.turns || []).flatMap(turn => [`## ${turn.question}`, '', turn.answer, ''])
+    ].join('\n'));
+}
+
+/**
+ * Share an answer and its screenshots (`images`, file paths) as a secret
+ * gist. The API only takes text, so the screenshots are pushed to the
+ * gist's git repository afterwards; without git the gist has just the
+ * answer, and `imageError` says why.
+ */
+export async function shareGist(entry: HistoryEntry, images: string[], token: string, proxy?: string): Promise<SharedGist> {
+    const proxyUrl = proxyFor(GIST_API, proxy);
+    let gist: any;
+    try {
+        gist = (await axios.post(GIST_API, {
+            description: entry.prompt || 'What is on screen?',
+            public: false,
+            files: { [ANSWER_FILE]: { content: gistMarkdown(entry) } }
+        }, {
+            headers: { authorization: `Bearer ${token}`, accept: 'application/vnd.github+json' },
+            proxy: false,
+            httpsAgent: proxyUrl ? proxyAgent(proxyUrl) : undefined
+        })).data;
+    } catch (error) {
+        const status = axios.isAxiosError(error) ? error.response?.status : undefined;
+        if (status === 401 || status === 403 || status === 404) {
+            throw new ConfigError('GitHub rejected the token - it needs the gist scope');
+        }
+        throw new Error(`GitHub API error: ${redactSecrets(error instanceof Error ? error.message : String(error))}`);
+    }
+
+    const shared: SharedGist = { url: gist.html_url };
+    if (images.length > 0) {
+        try {
+            await pushImages(gist.id, gist.owner?.login, entry, images, token);
+        } catch (error) {
+            shared.imageError = (error as NodeJS.ErrnoException).code === 'ENOENT'
+                ? 'git is needed to upload the screenshot'
+                : redactSecrets(error instanceof Error ? error.message : String(error));
+        }
+    }
+    return shared;
+}
+
+async function pushImages(id: string, owner: string, entry: HistoryEntry, images: string[], token: string): Promise<void> {
+    // In the environment rather than the arguments, so the token isn't in `ps`
+    const env = {
+        ...process.env,
+        GIT_TERMINAL_PROMPT: '0',
+        GIT_CONFIG_COUNT: '1',
+        GIT_CONFIG_KEY_0: 'http.extraHeader',
+        GIT_CONFIG_VALUE_0: `Authorization: Basic ${Buffer.from(`x-access-token:${token}`).toString('base64')}`
+    };
+    const dir = await fs.mkdtemp(path.join(os.tmpdir(), 'ai-screenshot-gist-'));
+    try {
+        await git(['clone', '--quiet', `https://gist.github.com/${id}.git`, dir], env);
+        const names = gistImageNames(images);
+        for (const [i, image] of images.entries()) {
+            await fs.copyFile(image, path.join(dir, names[i]));
+        }
+        const urls = names.map(name => `https://gist.githubusercontent.com/${owner}/${id}/raw/${name}`);
+        await fs.writeFile(path.join(dir, ANSWER_FILE), gistMarkdown(entry, urls));
+        await git(['-C', dir, 'add', '--all'], env);
+        await git(['-C', dir, '-c', 'user.name=ai-screenshot-analyzer', '-c', 'user.email=ai-screenshot-analyzer@users.noreply.github.com',
+            'commit', '--quiet', '-m', 'Add screenshot'], env);
+        await git(['-C', dir, 'push', '--quiet'], env);
+    } finally {
+        await fs.rm(dir, { recursive: true, force: true });
+    }
+}
+
+function git(args: string[], env: NodeJS.ProcessEnv): Promise<void> {
+    return new Promise((resolve, reject) => {
+        execFile('git', args, { env }, (error, stdout, stderr) => {
+            if (error) {
+                // Keep ENOENT recognizable when git isn't installed
+                reject(error.code === 'ENOENT' ? error : new Error(`git failed: ${stderr.trim() || error.message}`));
+            } else {
+                resolve();
+            }
+        });
+    });
+}
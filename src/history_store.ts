@@ -0,0 +1,169 @@
+import * as fs from 'fs/promises';
+import * as path from 'path';
+
+export const DEFAULT_HISTORY_SIZE = 500;
+
+// The last pending update of each history file. Updates read, change and
+// rewrite the whole file, so an answer and a follow-up saved at the same
+// time would otherwise drop one of them.
+const pendingUpdates = new Map<string, Promise<unknown>>();
+
+/** A follow-up question about a capture and its answer */
+export interface HistoryTurn {
+    /** ISO timestamp */
+    at: string;
+    question: string;
+    answer: string;
+}
+
+export interface HistoryEntry {
+    /** Counts up from 1 and is never reused, so `history <id>` stays valid */
+    id: number;
+    /** ISO timestamp */
+    at: string;
+    /** The question asked; missing for the default prompt */
+    prompt?: string;
+    answer: string;
+    /** e.g. "claude / claude-3-5-sonnet-20241022" */
+    model?: string;
+    /** The images sent, as file names in `<screenshots_dir>/history/` */
+    images?: string[];
+    /** Follow-ups asked since, oldest first */
+    turns?: HistoryTurn[];
+}
+
+/** The first line of an answer with text on it, for listings */
+export function firstLine(answer: string): string {
+    return answer.split('\n').map(line => line.trim()).find(line => line && !/^(```|~~~)/.test(line)) ?? '';
+}
+
+/** Entries whose questions or answers, follow-ups included, contain `text`, ignoring case */
+export function searchHistory(entries: HistoryEntry[], text: string): HistoryEntry[] {
+    const needle = text.toLowerCase();
+    return entries.filter(entry => [entry.prompt ?? '', entry.answer, ...(entry.turns || []).flatMap(turn => [turn.question, turn.answer])]
+        .some(part => part.toLowerCase().includes(needle)));
+}
+
+/**
+ * A thread as conversation turns after its first question: the first
+ * answer, then each follow-up and its answer - what's sent as context
+ * when the thread continues.
+ */
+export function threadTurns(entry: HistoryEntry): Array<{ role: 'user' | 'assistant'; text: string }> {
+    return [
+        { role: 'assistant', text: entry.answer },
+        ...(entry.turns || []).flatMap(turn => [
+            { role: 'user' as const, text: turn.question },
+            { role: 'assistant' as const, text: turn.answer }
+        ])
+    ];
+}
+
+function imageExtension(image: Buffer): string {
+    if (image.subarray(0, 3).equals(Buffer.from([0xFF, 0xD8, 0xFF]))) {
+        return 'jpg';
+    }
+    return image.subarray(8, 12).equals(Buffer.from('WEBP')) ? 'webp' : 'png';
+}
+
+/**
+ * Past answers for `history`, oldest first, stored as
+ * `<screenshots_dir>/history.json` with the images sent (as uploaded, so
+ * already scaled down) in `<screenshots_dir>/history/`. Keeps the newest
+ * `size`; 0 keeps none. Without `keepImages` (captures aren't being
+ * saved) only the text is kept.
+ */
+export class HistoryStore {
+    private file: string;
+    private imageDir: string;
+
+    constructor(screenshotsDir: string, private size: number = DEFAULT_HISTORY_SIZE, private keepImages: boolean = true) {
+        this.file = path.join(screenshotsDir, 'history.json');
+        this.imageDir = path.join(screenshotsDir, 'history');
+    }
+
+    async list(): Promise<HistoryEntry[]> {
+        try {
+            return JSON.parse(await fs.readFile(this.file, 'utf8'));
+        } catch (error) {
+            return [];
+        }
+    }
+
+    async get(id: number): Promise<HistoryEntry | undefined> {
+        return (await this.list()).find(entry => entry.id === id);
+    }
+
+    /** The newest entry, if any */
+    async last(): Promise<HistoryEntry | undefined> {
+        const entries = await this.list();
+        return entries[entries.length - 1];
+    }
+
+    /** Paths of the images sent with `entry` */
+    imagePaths(entry: HistoryEntry): string[] {
+        return (entry.images || []).map(name => path.join(this.imageDir, name));
+    }
+
+    /** Add a follow-up to entry `id`'s thread; false when it's no longer kept */
+    async addTurn(id: number, turn: HistoryTurn): Promise<boolean> {
+        return this.update(async () => {
+            const entries = await this.list();
+            const entry = entries.find(existing => existing.id === id);
+            if (!entry) {
+                return false;
+            }
+            entry.turns = [...(entry.turns || []), turn];
+            await this.write(entries);
+            return true;
+        });
+    }
+
+    /**
+     * Save an answer and the images it was about, returning its entry;
+     * undefined when history is off. Images of the entries that no longer
+     * fit are deleted.
+     */
+    async add(entry: Omit<HistoryEntry, 'id' | 'images' | 'turns'>, images: Buffer[] = []): Promise<HistoryEntry | undefined> {
+        if (this.size <= 0) {
+            return undefined;
+        }
+        return this.update(async () => {
+            const entries = await this.list();
+            const id = (entries[entries.length - 1]?.id ?? 0) + 1;
+            const added: HistoryEntry = { id, ...entry };
+            if (this.keepImages && images.length > 0) {
+                await fs.mkdir(this.imageDir, { recursive: true });
+                added.images = [];
+                for (const [i, image] of images.entries()) {
+                    const name = `${id}-${i + 1}.${imageExtension(image)}`;
+                    await fs.writeFile(path.join(this.imageDir, name), image);
+                    added.images.push(name);
+                }
+            }
+
+            const all = [...entries, added];
+            const dropped = all.slice(0, Math.max(0, all.length - this.size));
+            await this.write(all.slice(-this.size));
+            for (const file of dropped.flatMap(old => this.imagePaths(old))) {
+                await fs.rm(file, { force: true });
+            }
+            return added;
+        });
+    }
+
+    /** Run `change` once every earlier update of this file, from any store, has finished */
+    private update<T>(change: () => Promise<T>): Promise<T> {
+        const next = (pendingUpdates.get(this.file) ?? Promise.resolve()).catch(() => {}).then(change);
+        pendingUpdates.set(this.file, next);
+        return next;
+    }
+
+    private async write(entries: HistoryEntry[]): Promise<void> {
+        await fs.mkdir(path.dirname(this.file), { recursive: true });
+        // Renamed into place, so a reader never sees half a file (and takes it for an empty history)
+        const partial = `${this.file}.${process.pid}.tmp`;
+        await fs.writeFile(partial, JSON.stringify(entries, null, 2));
+        await fs.rename(partial, this.file);
+    }
+}
@@ -0,0 +1,63 @@
+import { GlobalKeyboardListener } from 'node-global-key-listener';
+
+/**
+ * A way of listening to global key events. Backends only report keys;
+ * `HotkeyDispatcher` decides what they trigger, so every backend behaves
+ * the same.
+ */
+export interface HotkeyBackend {
+    readonly name: string;
+    /** Start listening, calling `onKey` for every key press and release. Throws if unavailable. */
+    start(onKey: (key: string, down: boolean) => void): Promise<void>;
+    stop(): void;
+}
+
+/**
+ * node-global-key-listener, which runs a small native key server per
+ * platform. Needs the accessibility permission on macOS.
+ */
+export class GlobalKeyListenerBackend implements HotkeyBackend {
+    readonly name = 'node-global-key-listener';
+    private listener: GlobalKeyboardListener | null = null;
+
+    async start(onKey: (key: string, down: boolean) => void): Promise<void> {
+        this.listener = new GlobalKeyboardListener();
+        await this.listener.addListener(event => {
+            if (event.name) {
+                onKey(event.name, event.state === 'DOWN');
+            }
+        });
+    }
+
+    stop(): void {
+        this.listener?.kill();
+        this.listener = null;
+    }
+}
+
+/** Backends to try, in order of preference */
+export function defaultHotkeyBackends(): HotkeyBackend[] {
+    return [new GlobalKeyListenerBackend()];
+}
+
+/**
+ * Start the first backend that works, falling back to the next when one
+ * fails (e.g. a missing permission). Throws the last failure if none do.
+ */
+export async function startHotkeyBackend(
+    backends: HotkeyBackend[],
+    onKey: (key: string, down: boolean) => void
+): Promise<HotkeyBackend> {
+    let lastError: unknown = new Error('No hotkey backend available');
+    for (const backend of backends) {
+        try {
+            await backend.start(onKey);
+            return backend;
+        } catch (error) {
+            console.log(`⚠️  Hotkey backend ${backend.name} unavailable: ${error}`);
+            backend.stop();
+            lastError = error;
+        }
+    }
+    throw lastError;
+}
@@ -0,0 +1,89 @@
+import { CaptureSource } from './screenshot';
+import { DoubleTapDetector, parseModifierKey } from './double_tap';
+
+/** A key combination and the capture it triggers */
+export interface HotkeyBinding {
+    /** Key names as backends report them, with modifiers unsided ("META", not "LEFT META") */
+    keys: string[];
+    source: CaptureSource;
+    /** How the combination is shown, e.g. "Cmd+Shift+Space" */
+    label: string;
+}
+
+// A key whose release was missed (e.g. focus changed mid-press) stops
+// counting as held after this long
+const STUCK_KEY_MS = 5000;
+
+/** The built-in hotkeys: Cmd+Shift on macOS, Ctrl+Shift elsewhere */
+export function defaultBindings(platform: string = process.platform): HotkeyBinding[] {
+    const [modifier, name, alt] = platform === 'darwin' ? ['META', 'Cmd', 'Option'] : ['CTRL', 'Ctrl', 'Alt'];
+    return [
+        { keys: [modifier, 'SHIFT', 'SPACE'], source: 'screen', label: `${name}+Shift+Space` },
+        { keys: [modifier, 'SHIFT', 'ALT', 'SPACE'], source: 'clipboard', label: `${name}+Shift+${alt}+Space` },
+        { keys: [modifier, 'SHIFT', 'RETURN'], source: 'active-window', label: `${name}+Shift+Return` },
+        { keys: [modifier, 'SHIFT', 'R'], source: 'region', label: `${name}+Shift+R` }
+    ];
+}
+
+/** "LEFT META" and "RIGHT META" both count as "META" */
+function unsided(key: string): string {
+    return key.replace(/^(?:LEFT|RIGHT) (META|CTRL|SHIFT|ALT)$/, '$1');
+}
+
+/**
+ * Turns raw key events from any backend into hotkey triggers: the chord
+ * bindings, plus a double-tapped modifier (`double_tap_key`) when set.
+ */
+export class HotkeyDispatcher {
+    private pressed = new Map<string, number>();
+    private doubleTap: DoubleTapDetector | null;
+    private doubleTapBinding: HotkeyBinding | null;
+
+    /** `doubleTapKey` is as written in the config, e.g. "right cmd" */
+    constructor(private bindings: HotkeyBinding[], doubleTapKey?: string) {
+        const key = doubleTapKey && parseModifierKey(doubleTapKey);
+        this.doubleTap = key ? new DoubleTapDetector(key) : null;
+        this.doubleTapBinding = key ? { keys: [key], source: 'screen', label: `double-tap ${doubleTapKey}` } : null;
+    }
+
+    /** Every way to trigger a capture, for help text */
+    allBindings(): HotkeyBinding[] {
+        return this.doubleTapBinding ? [...this.bindings, this.doubleTapBinding] : this.bindings;
+    }
+
+    /**
+     * Feed every key event. Returns the binding the event completes, if
+     * any; its keys are then forgotten, so holding them doesn't fire again.
+     */
+    handle(key: string, down: boolean, now: number): HotkeyBinding | null {
+        if (this.doubleTap?.handle(key, down, now)) {
+            return this.doubleTapBinding;
+        }
+
+        const name = unsided(key);
+        if (!down) {
+            this.pressed.delete(name);
+            return null;
+        }
+        for (const [held, pressedAt] of this.pressed) {
+            if (now - pressedAt > STUCK_KEY_MS) {
+                this.pressed.delete(held);
+            }
+        }
+        this.pressed.set(name, now);
+
+        // The most specific binding wins, e.g. Cmd+Shift+Option+Space over Cmd+Shift+Space
+        const match = this.bindings
+            .filter(binding => binding.keys.every(bindingKey => this.pressed.has(bindingKey)))
+            .sort((a, b) => b.keys.length - a.keys.length)[0];
+        if (!match) {
+            return null;
+        }
+        this.pressed.clear();
+        return match;
+    }
+
+    reset(): void {
+        this.pressed.clear();
+    }
+}
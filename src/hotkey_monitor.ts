@@ -1,25 +1,28 @@
-import { GlobalKeyboardListener } from 'node-global-key-listener';
 import { EventEmitter } from 'events';
-import { AppState } from './main';
-import { printStatus, printAnalysisResult } from './ui';
+import { AppState, activeQuestion, analyzeAndPrint, cancelAnalysis, rememberQuestion } from './main';
+import { printStatus } from './ui';
+import { CancelledError, RateLimitError } from './errors';
+import { QueuedTrigger, RateLimitQueue } from './rate_limit_queue';
+import { CAPTURE_STATUS, CaptureSource } from './screenshot';
+import { HotkeyBackend, defaultHotkeyBackends, startHotkeyBackend } from './hotkey_backend';
+import { HotkeyBinding, HotkeyDispatcher, defaultBindings } from './hotkey_dispatcher';
+import { VoiceOptions, listenForQuestion } from './voice';
+import { formatDuration } from './duration';
 
 export class HotkeyMonitor extends EventEmitter {
-    private keyboardListener: GlobalKeyboardListener | null = null;
+    private backend: HotkeyBackend | null = null;
+    private dispatcher: HotkeyDispatcher | null = null;
     private isRunning: boolean = false;
     private lastTriggerTime: number = 0;
     private debounceTime: number = 1000; // 1 second debounce
-    private pressedKeys: Set<string> = new Set();
-    private requiredKeys: string[];
     private isProcessing: boolean = false; // Prevent multiple simultaneous captures
-    private keyTimeouts: Map<string, NodeJS.Timeout> = new Map(); // Track key release timeouts
-    private keyReleaseDelay: number = 500; // How long to wait before considering a key "released"
+    private rateLimitQueue: RateLimitQueue;
+    private currentState: AppState | null = null;
 
-    constructor() {
+    /** `backends` are tried in order until one starts */
+    constructor(private backends: HotkeyBackend[] = defaultHotkeyBackends()) {
         super();
-        // Define required keys based on platform
-        this.requiredKeys = process.platform === 'darwin' 
-            ? ['LEFT META', 'LEFT SHIFT', 'SPACE']  // macOS: Cmd+Shift+Space
-            : ['LEFT CTRL', 'LEFT SHIFT', 'SPACE']; // Windows/Linux: Ctrl+Shift+Space
+        this.rateLimitQueue = new RateLimitQueue(item => this.processQueuedTrigger(item));
     }
 
     async startMonitoring(state: AppState): Promise<void> {
@@ -28,33 +31,24 @@ export class HotkeyMonitor extends EventEmitter {
             return;
         }
 
-        const hotkeyStr = process.platform === 'darwin' ? 'Cmd+Shift+Space' : 'Ctrl+Shift+Space';
-        console.log(`🎹 Starting hotkey monitoring (${hotkeyStr})`);
+        const dispatcher = new HotkeyDispatcher(defaultBindings(), state.config.doubleTapKey);
+        console.log('🎹 Starting hotkey monitoring:');
+        for (const binding of dispatcher.allBindings()) {
+            console.log(`   ${binding.label} → ${binding.source}`);
+        }
         console.log(`🔍 Detected platform: ${process.platform}`);
-        console.log(`📋 Required keys: ${this.requiredKeys.join(', ')}`);
 
         try {
-            this.keyboardListener = new GlobalKeyboardListener();
-            this.isRunning = true;
-
-            this.keyboardListener.addListener((e, down) => {
-                const keyName = e.name;
-                
-                // Skip if key name is undefined
-                if (!keyName) {
-                    return;
-                }
-                
-                if (down) {
-                    // Key pressed down
-                    this.handleKeyPress(keyName, state);
-                } else {
-                    // Key released
-                    this.handleKeyRelease(keyName);
+            this.dispatcher = dispatcher;
+            this.backend = await startHotkeyBackend(this.backends, (key, down) => {
+                const binding = dispatcher.handle(key, down, Date.now());
+                if (binding) {
+                    this.trigger(state, binding);
                 }
             });
+            this.isRunning = true;
 
-            console.log('✅ Hotkey monitoring started successfully');
+            console.log(`✅ Hotkey monitoring started successfully (${this.backend.name})`);
         } catch (error) {
             console.error('❌ Failed to start hotkey monitoring:', error);
             this.isRunning = false;
@@ -62,61 +56,16 @@ export class HotkeyMonitor extends EventEmitter {
         }
     }
 
-    private handleKeyPress(keyName: string, state: AppState): void {
-        // Clear any existing timeout for this key
-        const existingTimeout = this.keyTimeouts.get(keyName);
-        if (existingTimeout) {
-            clearTimeout(existingTimeout);
-            this.keyTimeouts.delete(keyName);
+    private trigger(state: AppState, binding: HotkeyBinding): void {
+        if (this.shouldCancel(state)) {
+            // Pressing a hotkey again aborts the analysis in progress
+            cancelAnalysis(state);
+        } else if (state.paused) {
+            console.log(`⏸️  Paused, ignoring ${binding.label}`);
+        } else if (this.shouldTrigger()) {
+            console.log(`🔥 ${binding.label} detected! Capturing ${binding.source}...`);
+            this.processHotkeyTrigger(state, binding.source);
         }
-
-        // Add key to pressed set
-        this.pressedKeys.add(keyName);
-        
-        // Check if all required keys are now pressed
-        if (this.areAllKeysPressed() && this.shouldTrigger()) {
-            console.log('🔥 All hotkeys detected! Triggering screenshot...');
-            this.processHotkeyTrigger(state);
-        }
-
-        // Set a timeout to automatically remove this key if no release event comes
-        const timeout = setTimeout(() => {
-            this.pressedKeys.delete(keyName);
-            this.keyTimeouts.delete(keyName);
-        }, this.keyReleaseDelay);
-        
-        this.keyTimeouts.set(keyName, timeout);
-    }
-
-    private handleKeyRelease(keyName: string): void {
-        // Clear any existing timeout for this key
-        const existingTimeout = this.keyTimeouts.get(keyName);
-        if (existingTimeout) {
-            clearTimeout(existingTimeout);
-            this.keyTimeouts.delete(keyName);
-        }
-
-        // Remove key from pressed set
-        this.pressedKeys.delete(keyName);
-    }
-
-    private areAllKeysPressed(): boolean {
-        // Check if ALL required keys are currently pressed
-        const allPressed = this.requiredKeys.every(requiredKey => {
-            // For modifier keys, accept either LEFT or RIGHT variants
-            if (requiredKey === 'LEFT META') {
-                return this.pressedKeys.has('LEFT META') || this.pressedKeys.has('RIGHT META');
-            } else if (requiredKey === 'LEFT CTRL') {
-                return this.pressedKeys.has('LEFT CTRL') || this.pressedKeys.has('RIGHT CTRL');
-            } else if (requiredKey === 'LEFT SHIFT') {
-                return this.pressedKeys.has('LEFT SHIFT') || this.pressedKeys.has('RIGHT SHIFT');
-            } else {
-                return this.pressedKeys.has(requiredKey);
-            }
-        });
-
-        // Don't restrict extra keys - just ensure all required keys are pressed
-        return allPressed;
     }
 
     stopMonitoring(): void {
@@ -124,24 +73,32 @@ export class HotkeyMonitor extends EventEmitter {
         this.isRunning = false;
         this.isProcessing = false;
 
-        // Clear all key timeouts
-        for (const timeout of this.keyTimeouts.values()) {
-            clearTimeout(timeout);
-        }
-        this.keyTimeouts.clear();
+        this.rateLimitQueue.clear();
 
-        if (this.keyboardListener) {
-            this.keyboardListener.kill();
-            this.keyboardListener = null;
+        if (this.backend) {
+            this.backend.stop();
+            this.backend = null;
         }
         
-        this.pressedKeys.clear();
+        this.dispatcher?.reset();
+    }
+
+    /** The chords (and double tap) currently registered */
+    registeredHotkeys(): HotkeyBinding[] {
+        return this.isRunning && this.dispatcher ? this.dispatcher.allBindings() : [];
     }
 
     isMonitoring(): boolean {
         return this.isRunning;
     }
 
+    private shouldCancel(state: AppState): boolean {
+        // Outside the debounce period, so a held-down hotkey doesn't cancel its own capture
+        return this.isProcessing
+            && Date.now() - this.lastTriggerTime >= this.debounceTime
+            && !!state.analysis;
+    }
+
     private shouldTrigger(): boolean {
         // Prevent multiple simultaneous captures
         if (this.isProcessing) {
@@ -160,114 +117,141 @@ export class HotkeyMonitor extends EventEmitter {
         return true;
     }
 
-    private async processHotkeyTrigger(state: AppState): Promise<void> {
+    private async processHotkeyTrigger(state: AppState, source: CaptureSource = 'screen'): Promise<void> {
         this.isProcessing = true;
         
-        // Clear all pressed keys immediately to prevent retriggering
-        this.pressedKeys.clear();
-        for (const timeout of this.keyTimeouts.values()) {
-            clearTimeout(timeout);
-        }
-        this.keyTimeouts.clear();
+        // Forget held keys immediately to prevent retriggering
+        this.dispatcher?.reset();
         
-        console.log('🚀 Processing hotkey trigger - starting screenshot capture');
-        printStatus('📸 Capturing screenshot...');
+        try {
+            // Waits for a tray or `ask` capture that is already running
+            await state.triggers.run(`hotkey capture (${source})`, () => this.captureAndAnalyze(state, source));
+        } finally {
+            this.isProcessing = false;
+        }
+    }
+
+    private async captureAndAnalyze(state: AppState, source: CaptureSource): Promise<void> {
+        console.log(`🚀 Processing hotkey trigger - capturing ${source}`);
+        printStatus(CAPTURE_STATUS[source]);
+
+        const triggeredAt = new Date();
+        let screenshotData: Buffer | null = null;
+        this.currentState = state;
+
+        // Use the questions if provided, otherwise use custom prompt
+        let questionToAsk = activeQuestion(state);
 
         try {
             // Capture screenshot
-            const screenshotData = await state.screenshotCapture.capture();
+            screenshotData = await state.screenshotCapture.captureFrom(source);
 
-            printStatus('🤖 Analyzing with AI...');
+            // The screen is captured as it was when the hotkey was pressed, then the question is asked aloud
+            if (state.voice) {
+                const spoken = await this.listen(state.voice);
+                // A spoken question sticks for later hotkey captures like a typed one
+                rememberQuestion(state, spoken);
+                questionToAsk = spoken || questionToAsk;
+            }
 
-            // Use the question if provided, otherwise use custom prompt
-            const questionToAsk = state.customQuestion || state.customPrompt;
+            // While rate limited, keep the capture and analyze it once the limit resets
+            if (this.rateLimitQueue.isLimited()) {
+                this.queueTrigger({ triggeredAt, screenshot: screenshotData, question: questionToAsk });
+                return;
+            }
 
-            const analysis = await state.aiClient.analyzeImage(screenshotData, questionToAsk);
+            printStatus('🤖 Analyzing with AI...');
 
             // Display results
-            printAnalysisResult(analysis);
+            await analyzeAndPrint(state, [screenshotData], questionToAsk);
 
             console.log('✅ Screenshot analysis completed successfully');
+            this.emit('captureComplete');
         } catch (error) {
-            console.error('❌ Screenshot analysis failed:', error);
-        } finally {
-            this.isProcessing = false;
+            if (error instanceof RateLimitError && screenshotData) {
+                this.rateLimitQueue.pause(error);
+                this.queueTrigger({ triggeredAt, screenshot: screenshotData, question: questionToAsk });
+                return;
+            }
+            if (!(error instanceof CancelledError)) {
+                console.error('❌ Screenshot analysis failed:', error);
+            }
+            this.emit('captureComplete', error);
+        }
+    }
+
+    private async listen(voice: VoiceOptions): Promise<string> {
+        printStatus(`🎙️  Listening for ${formatDuration(voice.durationMs)} - ask your question...`);
+        const question = await listenForQuestion(voice);
+        console.log(question ? `🗣️  "${question}"` : '🔇 Nothing heard - using the default question');
+        return question;
+    }
+
+    private queueTrigger(item: QueuedTrigger): void {
+        this.rateLimitQueue.enqueue(item);
+        console.log(`⏳ Rate limited by the AI provider - capture queued (${this.rateLimitQueue.size()} waiting), ` +
+            `resuming in ~${this.rateLimitQueue.secondsUntilReset()}s`);
+    }
+
+    private async processQueuedTrigger(item: QueuedTrigger): Promise<void> {
+        const state = this.currentState;
+        if (!state) {
+            return;
+        }
+
+        printStatus(`🤖 Analyzing queued capture from ${item.triggeredAt.toLocaleTimeString()}...`);
+        try {
+            await analyzeAndPrint(state, [item.screenshot], item.question);
+            this.emit('captureComplete');
+        } catch (error) {
+            if (!(error instanceof RateLimitError)) {
+                this.emit('captureComplete', error);
+            }
+            throw error;
         }
     }
 
     async testKeyDetection(): Promise<void> {
         console.log('🧪 Testing key detection capabilities...');
         
-        const hotkey = process.platform === 'darwin' ? 'Cmd+Shift+Space' : 'Ctrl+Shift+Space';
-        
-        console.log(`Expected hotkey: ${hotkey}`);
-        console.log(`Required keys: ${this.requiredKeys.join(' + ')}`);
+        const dispatcher = new HotkeyDispatcher(defaultBindings());
+        console.log(`Expected hotkeys: ${dispatcher.allBindings().map(binding => binding.label).join(', ')}`);
         console.log('Press individual keys to see detection...');
-        console.log('Press the full hotkey combination to test complete detection');
+        console.log('Press a full hotkey combination to test complete detection');
         console.log('Press Ctrl+C to cancel test');
         
+        let finish: () => void = () => {};
+        const finished = new Promise<void>(resolve => {
+            finish = resolve;
+        });
+        const timeout = setTimeout(() => {
+            console.log('❌ Test timed out - no complete hotkey detected within 30 seconds');
+            finish();
+        }, 30000);
+        
+        let backend: HotkeyBackend | null = null;
         try {
-            const listener = new GlobalKeyboardListener();
-            let testCompleted = false;
-            const pressedTestKeys = new Set<string>();
-            
-            const timeout = setTimeout(() => {
-                if (!testCompleted) {
-                    console.log('❌ Test timed out - no complete hotkey detected within 30 seconds');
-                    listener.kill();
-                    testCompleted = true;
-                }
-            }, 30000);
-
-            listener.addListener((e, down) => {
-                const keyName = e.name;
-                
-                // Skip if key name is undefined
-                if (!keyName) {
-                    return;
-                }
-
+            backend = await startHotkeyBackend(this.backends, (key, down) => {
                 if (down) {
-                    pressedTestKeys.add(keyName);
-                    
-                    // Check if all required keys are pressed
-                    const allRequired = this.requiredKeys.every(requiredKey => {
-                        if (requiredKey === 'LEFT META') {
-                            return pressedTestKeys.has('LEFT META') || pressedTestKeys.has('RIGHT META');
-                        } else if (requiredKey === 'LEFT CTRL') {
-                            return pressedTestKeys.has('LEFT CTRL') || pressedTestKeys.has('RIGHT CTRL');
-                        } else if (requiredKey === 'LEFT SHIFT') {
-                            return pressedTestKeys.has('LEFT SHIFT') || pressedTestKeys.has('RIGHT SHIFT');
-                        } else {
-                            return pressedTestKeys.has(requiredKey);
-                        }
-                    });
-
-                    if (allRequired) {
-                        console.log('🎉 SUCCESS: Complete hotkey combination detected!');
-                        console.log('✅ Hotkey detection is working correctly');
-                        testCompleted = true;
-                        clearTimeout(timeout);
-                        listener.kill();
-                    }
-                } else {
-                    pressedTestKeys.delete(keyName);
+                    console.log(`   ⌨️  ${key}`);
+                }
+                const binding = dispatcher.handle(key, down, Date.now());
+                if (binding) {
+                    console.log(`🎉 SUCCESS: ${binding.label} detected (${binding.source})!`);
+                    console.log('✅ Hotkey detection is working correctly');
+                    finish();
                 }
             });
+            console.log(`Listening with ${backend.name}`);
             
             // Wait for test to complete
-            await new Promise<void>(resolve => {
-                const checkInterval = setInterval(() => {
-                    if (testCompleted) {
-                        clearInterval(checkInterval);
-                        resolve();
-                    }
-                }, 100);
-            });
-            
+            await finished;
         } catch (error) {
             console.error('❌ Key detection test failed:', error);
             throw error;
+        } finally {
+            clearTimeout(timeout);
+            backend?.stop();
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,145 @@
+import sharp from 'sharp';
+
+// Per-channel difference below which pixels count as unchanged, so
+// compression noise and anti-aliasing don't register as changes
+const CHANNEL_TOLERANCE = 32;
+
+export interface ImageDiff {
+    width: number;
+    height: number;
+    changedPixels: number;
+    totalPixels: number;
+    changedRatio: number;
+}
+
+/**
+ * Compare two images pixel by pixel. The second image is resized to the
+ * first one's dimensions when they differ.
+ */
+export async function compareImages(before: Buffer, after: Buffer): Promise<ImageDiff> {
+    const metadata = await sharp(before).metadata();
+    const width = metadata.width || 0;
+    const height = metadata.height || 0;
+
+    const beforeRaw = await sharp(before).removeAlpha().raw().toBuffer();
+    const afterRaw = await sharp(after)
+        .resize(width, height, { fit: 'fill' })
+        .removeAlpha()
+        .raw()
+        .toBuffer();
+
+    const totalPixels = width * height;
+    let changedPixels = 0;
+
+    for (let i = 0; i < totalPixels; i++) {
+        const offset = i * 3;
+        if (Math.abs(beforeRaw[offset] - afterRaw[offset]) > CHANNEL_TOLERANCE ||
+            Math.abs(beforeRaw[offset + 1] - afterRaw[offset + 1]) > CHANNEL_TOLERANCE ||
+            Math.abs(beforeRaw[offset + 2] - afterRaw[offset + 2]) > CHANNEL_TOLERANCE) {
+            changedPixels++;
+        }
+    }
+
+    return {
+        width,
+        height,
+        changedPixels,
+        totalPixels,
+        changedRatio: totalPixels > 0 ? changedPixels / totalPixels : 0
+    };
+}
+
+/** A changed area, in pixels of the compared images */
+export interface ChangedRegion {
+    x: number;
+    y: number;
+    width: number;
+    height: number;
+}
+
+// Changes are found per cell of this many pixels, then neighbouring cells merged
+const REGION_CELL_SIZE = 16;
+
+/**
+ * Group changed pixels into rectangles: the image is split into cells, and
+ * touching cells with any change are merged into one region.
+ */
+export function changedRegions(beforeRaw: Buffer, afterRaw: Buffer, width: number, height: number): ChangedRegion[] {
+    const columns = Math.ceil(width / REGION_CELL_SIZE);
+    const rows = Math.ceil(height / REGION_CELL_SIZE);
+    const changed = new Uint8Array(columns * rows);
+
+    for (let y = 0; y < height; y++) {
+        for (let x = 0; x < width; x++) {
+            const offset = (y * width + x) * 3;
+            if (Math.abs(beforeRaw[offset] - afterRaw[offset]) > CHANNEL_TOLERANCE ||
+                Math.abs(beforeRaw[offset + 1] - afterRaw[offset + 1]) > CHANNEL_TOLERANCE ||
+                Math.abs(beforeRaw[offset + 2] - afterRaw[offset + 2]) > CHANNEL_TOLERANCE) {
+                changed[Math.floor(y / REGION_CELL_SIZE) * columns + Math.floor(x / REGION_CELL_SIZE)] = 1;
+            }
+        }
+    }
+
+    // Flood fill over changed cells, including diagonal neighbours
+    const regions: ChangedRegion[] = [];
+    const seen = new Uint8Array(columns * rows);
+    for (let start = 0; start < changed.length; start++) {
+        if (!changed[start] || seen[start]) {
+            continue;
+        }
+        let [minColumn, minRow, maxColumn, maxRow] = [columns, rows, 0, 0];
+        const stack = [start];
+        seen[start] = 1;
+        while (stack.length > 0) {
+            const cell = stack.pop()!;
+            const column = cell % columns;
+            const row = Math.floor(cell / columns);
+            minColumn = Math.min(minColumn, column);
+            minRow = Math.min(minRow, row);
+            maxColumn = Math.max(maxColumn, column);
+            maxRow = Math.max(maxRow, row);
+
+            for (let dy = -1; dy <= 1; dy++) {
+                for (let dx = -1; dx <= 1; dx++) {
+                    const c = column + dx;
+                    const r = row + dy;
+                    const neighbour = r * columns + c;
+                    if (c >= 0 && c < columns && r >= 0 && r < rows && changed[neighbour] && !seen[neighbour]) {
+                        seen[neighbour] = 1;
+                        stack.push(neighbour);
+                    }
+                }
+            }
+        }
+
+        const x = minColumn * REGION_CELL_SIZE;
+        const y = minRow * REGION_CELL_SIZE;
+        regions.push({
+            x,
+            y,
+            width: Math.min(width, (maxColumn + 1) * REGION_CELL_SIZE) - x,
+            height: Math.min(height, (maxRow + 1) * REGION_CELL_SIZE) - y
+        });
+    }
+    return regions;
+}
+
+/**
+ * Find the changed regions between two images (the second resized to the
+ * first's dimensions) and outline them in red on the second.
+ */
+export async function highlightChanges(before: Buffer, after: Buffer): Promise<{ image: Buffer; regions: ChangedRegion[] }> {
+    const { width = 0, height = 0 } = await sharp(before).metadata();
+    const beforeRaw = await sharp(before).removeAlpha().raw().toBuffer();
+    const resized = sharp(after).resize(width, height, { fit: 'fill' }).removeAlpha();
+    const afterRaw = await resized.clone().raw().toBuffer();
+
+    const regions = changedRegions(beforeRaw, afterRaw, width, height);
+    const stroke = Math.max(2, Math.round(width / 800));
+    const outlines = `<svg xmlns="http://www.w3.org/2000/svg" width="${width}" height="${height}">
+${regions.map(region => `<rect x="${region.x}" y="${region.y}" width="${region.width}" height="${region.height}" fill="red" fill-opacity="0.15" stroke="red" stroke-width="${stroke}"/>`).join('\n')}
+</svg>`;
+
+    const image = await resized.composite([{ input: Buffer.from(outlines), left: 0, top: 0 }]).png().toBuffer();
+    return { image, regions };
+}
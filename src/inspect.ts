@@ -0,0 +1,69 @@
+import { parseJsonAnswer } from './ai_client';
+
+/** A UI element found on screen, with its box in pixels of the capture */
+export interface UiElement {
+    type: string;
+    /** Visible text, or what the element is for when it has none */
+    label: string;
+    x: number;
+    y: number;
+    width: number;
+    height: number;
+}
+
+export const UI_ELEMENT_TYPES = ['button', 'input', 'checkbox', 'radio', 'dropdown', 'link', 'tab', 'menu item', 'label', 'icon', 'image'];
+
+// Models place boxes more reliably as percentages than as pixels of an
+// image size they may not know
+export const UI_ELEMENTS_SCHEMA = {
+    type: 'object',
+    properties: {
+        elements: {
+            type: 'array',
+            items: {
+                type: 'object',
+                properties: {
+                    type: { type: 'string', enum: UI_ELEMENT_TYPES },
+                    label: { type: 'string', description: 'Visible text, or a short description if there is none' },
+                    box: {
+                        type: 'array',
+                        items: { type: 'number' },
+                        description: 'x, y, width, height as percentages (0-100) of the image width and height'
+                    }
+                },
+                required: ['type', 'label', 'box'],
+                additionalProperties: false
+            }
+        }
+    },
+    required: ['elements'],
+    additionalProperties: false
+};
+
+export const INSPECT_PROMPT = 'List the UI elements in this screenshot - buttons, inputs, checkboxes, radio buttons, dropdowns, ' +
+    'links, tabs, menu items, labels, icons and images - in reading order, each with its bounding box.';
+
+/**
+ * Parse the elements from the model's answer, converting their boxes to
+ * pixels of a `width` x `height` capture. Elements without a valid box
+ * are dropped.
+ */
+export function parseUiElements(text: string, width: number, height: number): UiElement[] {
+    const data = parseJsonAnswer(text);
+    const elements: any[] = Array.isArray(data?.elements) ? data.elements : [];
+    return elements.flatMap(element => {
+        const box = element?.box;
+        if (!Array.isArray(box) || box.length !== 4 || box.some(value => typeof value !== 'number')) {
+            return [];
+        }
+        const [x, y, boxWidth, boxHeight] = box.map((value: number) => Math.min(100, Math.max(0, value)));
+        return [{
+            type: typeof element.type === 'string' ? element.type : 'other',
+            label: typeof element.label === 'string' ? element.label : '',
+            x: Math.round(x / 100 * width),
+            y: Math.round(y / 100 * height),
+            width: Math.round(boxWidth / 100 * width),
+            height: Math.round(boxHeight / 100 * height)
+        }];
+    });
+}
@@ -0,0 +1,34 @@
+import { execFile } from 'child_process';
+
+/** The service secrets are stored under in the system keychain */
+export const KEYCHAIN_SERVICE = 'ai-screenshot-analyzer';
+
+/**
+ * The command that prints the secret stored for `account`: the login
+ * keychain on macOS, the Secret Service (GNOME Keyring, KWallet) on Linux.
+ * Undefined where there's no such store.
+ */
+export function keychainCommand(account: string, platform: NodeJS.Platform = process.platform): [string, string[]] | undefined {
+    switch (platform) {
+        case 'darwin':
+            return ['security', ['find-generic-password', '-s', KEYCHAIN_SERVICE, '-a', account, '-w']];
+        case 'linux':
+            return ['secret-tool', ['lookup', 'service', KEYCHAIN_SERVICE, 'account', account]];
+        default:
+            return undefined;
+    }
+}
+
+/**
+ * The secret stored in the system keychain for `account`, or undefined
+ * when there is none (or no keychain to ask).
+ */
+export function keychainSecret(account: string): Promise<string | undefined> {
+    const command = keychainCommand(account);
+    if (!command) {
+        return Promise.resolve(undefined);
+    }
+    return new Promise(resolve => {
+        execFile(command[0], command[1], (error, stdout) => resolve(error ? undefined : stdout.trim() || undefined));
+    });
+}
@@ -0,0 +1,43 @@
+import * as fs from 'fs/promises';
+import * as path from 'path';
+
+// Enough for a steady median, few enough to follow a model speeding up or slowing down
+const KEPT_LATENCIES = 20;
+
+/**
+ * How long recent answers took per provider and model, stored as
+ * `<screenshots_dir>/latencies.json`, so the progress line can show the
+ * time left - in one-shot runs too.
+ */
+export class LatencyStore {
+    private file: string;
+
+    constructor(screenshotsDir: string) {
+        this.file = path.join(screenshotsDir, 'latencies.json');
+    }
+
+    /** The median of the recent latencies for `key`, or undefined before the first answer */
+    async expected(key: string): Promise<number | undefined> {
+        const latencies = [...((await this.read())[key] || [])].sort((a, b) => a - b);
+        if (latencies.length === 0) {
+            return undefined;
+        }
+        const middle = Math.floor(latencies.length / 2);
+        return latencies.length % 2 === 1 ? latencies[middle] : Math.round((latencies[middle - 1] + latencies[middle]) / 2);
+    }
+
+    async record(key: string, ms: number): Promise<void> {
+        const all = await this.read();
+        all[key] = [...(all[key] || []), Math.round(ms)].slice(-KEPT_LATENCIES);
+        await fs.mkdir(path.dirname(this.file), { recursive: true });
+        await fs.writeFile(this.file, JSON.stringify(all, null, 2));
+    }
+
+    private async read(): Promise<Record<string, number[]>> {
+        try {
+            return JSON.parse(await fs.readFile(this.file, 'utf8'));
+        } catch (error) {
+            return {};
+        }
+    }
+}
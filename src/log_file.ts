@@ -0,0 +1,50 @@
+import * as fs from 'fs';
+import * as path from 'path';
+
+/**
+ * A log file for `--log-file`. Writes are synchronous, so nothing is lost
+ * when a command ends with `process.exit()`. The daemon calls `reopen()`
+ * on SIGHUP: once logrotate has moved the file aside, the next line starts
+ * a new file at the same path instead of following the old one.
+ */
+export class LogFile {
+    private fd: number;
+
+    constructor(readonly file: string) {
+        fs.mkdirSync(path.dirname(path.resolve(file)), { recursive: true });
+        this.fd = fs.openSync(file, 'a');
+    }
+
+    write(chunk: string | Uint8Array, callback?: () => void): boolean {
+        try {
+            fs.writeSync(this.fd, chunk);
+        } catch {
+            // Nowhere left to report a failing log; don't crash the daemon over it
+        }
+        if (callback) {
+            process.nextTick(callback);
+        }
+        return true;
+    }
+
+    reopen(): void {
+        const previous = this.fd;
+        this.fd = fs.openSync(this.file, 'a');
+        fs.closeSync(previous);
+    }
+
+    close(): void {
+        fs.closeSync(this.fd);
+    }
+}
+
+/** Send everything written to stdout and stderr to `file` instead */
+export function redirectOutputTo(file: string): LogFile {
+    const log = new LogFile(file);
+    for (const stream of [process.stdout, process.stderr]) {
+        stream.write = ((chunk: string | Uint8Array, ...rest: any[]) => {
+            return log.write(chunk, rest.find(arg => typeof arg === 'function'));
+        }) as typeof stream.write;
+    }
+    return log;
+}
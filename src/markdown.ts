@@ -0,0 +1,198 @@
+import { stripVTControlCharacters } from 'util';
+
+/** How each markdown element is drawn, e.g. with chalk */
+export interface MarkdownStyles {
+    text: (text: string) => string;
+    heading: (text: string, level: number) => string;
+    bold: (text: string) => string;
+    italic: (text: string) => string;
+    inlineCode: (text: string) => string;
+    link: (text: string, url: string) => string;
+    /** List bullets and numbers */
+    bullet: (marker: string) => string;
+    quote: (text: string) => string;
+    rule: (text: string) => string;
+    tableBorder: (text: string) => string;
+    /** Code block fences, kept as they are */
+    fence: (line: string) => string;
+    code: (line: string) => string;
+}
+
+const RULE_WIDTH = 40;
+
+type Alignment = 'left' | 'right' | 'center';
+
+/** Columns `text` takes up, ignoring escape codes */
+export function visibleWidth(text: string): number {
+    return stripVTControlCharacters(text).length;
+}
+
+/** Bold, italics, links and inline code, leaving code spans untouched */
+export function renderInline(text: string, styles: MarkdownStyles): string {
+    return text.split(/(`[^`]+`)/).map((part, index) => {
+        if (index % 2 === 1) {
+            return styles.inlineCode(part.slice(1, -1));
+        }
+        return part
+            .replace(/\[([^\]]+)\]\(([^)\s]+)\)/g, (match, label: string, url: string) => styles.link(label, url))
+            .replace(/\*\*(?=\S)(.+?)(?<=\S)\*\*|__(?=\S)(.+?)(?<=\S)__/g, (match, stars?: string, underscores?: string) => styles.bold(stars ?? underscores!))
+            .replace(/(?<![*\w])\*(?=[^\s*])(.+?)(?<=[^\s*])\*(?![*\w])|(?<![_\w])_(?=[^\s_])(.+?)(?<=[^\s_])_(?![_\w])/g,
+                (match, stars?: string, underscores?: string) => styles.italic(stars ?? underscores!));
+    }).join('');
+}
+
+/**
+ * Word-wrap rendered text to `width` columns, ignoring escape codes, with
+ * `indent` before each continuation line. Words longer than a line (URLs,
+ * paths) are kept whole.
+ */
+export function wrapVisible(text: string, width: number, indent: string = ''): string[] {
+    if (visibleWidth(text) <= width) {
+        return [text];
+    }
+    const lines: string[] = [];
+    const words = text.split(' ');
+    let line = words[0];
+    for (const word of words.slice(1)) {
+        const candidate = `${line} ${word}`;
+        // Never break inside the line's own indentation
+        if (visibleWidth(candidate) > width && stripVTControlCharacters(line).trim() && visibleWidth(line) > indent.length) {
+            lines.push(line);
+            line = indent + word;
+        } else {
+            line = candidate;
+        }
+    }
+    lines.push(line);
+    return lines;
+}
+
+function tableCells(line: string): string[] {
+    return line.trim().replace(/^\|/, '').replace(/\|$/, '').split('|').map(cell => cell.trim());
+}
+
+function pad(text: string, width: number, alignment: Alignment): string {
+    const space = width - visibleWidth(text);
+    if (alignment === 'right') {
+        return ' '.repeat(space) + text;
+    }
+    if (alignment === 'center') {
+        return ' '.repeat(Math.floor(space / 2)) + text + ' '.repeat(Math.ceil(space / 2));
+    }
+    return text + ' '.repeat(space);
+}
+
+/**
+ * Draw the rows of a markdown table as a box, with the columns sized to
+ * fit and aligned as the `|:---:|` row says.
+ */
+export function renderTable(lines: string[], styles: MarkdownStyles): string[] {
+    const rows = lines.map(tableCells);
+    const isSeparator = (cells: string[]) => cells.every(cell => /^:?-+:?$/.test(cell));
+    const hasHeader = rows.length > 1 && isSeparator(rows[1]);
+    const alignments: Alignment[] = hasHeader
+        ? rows[1].map(cell => cell.endsWith(':') ? (cell.startsWith(':') ? 'center' : 'right') : 'left')
+        : [];
+    const body = rows.filter((cells, index) => !(hasHeader && index === 1));
+
+    const columns = Math.max(...body.map(cells => cells.length));
+    const rendered = body.map((cells, index) => Array.from({ length: columns }, (_, column) => {
+        const cell = renderInline(cells[column] ?? '', styles);
+        return hasHeader && index === 0 ? styles.bold(cell) : cell;
+    }));
+    const widths = Array.from({ length: columns }, (_, column) => Math.max(...rendered.map(cells => visibleWidth(cells[column]))));
+
+    const border = (left: string, middle: string, right: string) =>
+        styles.tableBorder(left + widths.map(width => '─'.repeat(width + 2)).join(middle) + right);
+    const row = (cells: string[]) => styles.tableBorder('│') +
+        cells.map((cell, column) => ` ${pad(cell, widths[column], alignments[column] ?? 'left')} `).join(styles.tableBorder('│')) +
+        styles.tableBorder('│');
+
+    const output = [border('┌', '┬', '┐')];
+    rendered.forEach((cells, index) => {
+        output.push(row(cells));
+        if (hasHeader && index === 0 && rendered.length > 1) {
+            output.push(border('├', '┼', '┤'));
+        }
+    });
+    output.push(border('└', '┴', '┘'));
+    return output;
+}
+
+/**
+ * Renders markdown for the terminal one line at a time, so it works on a
+ * stream as well as a whole answer. Code blocks keep their fences; tables
+ * are held back until their last row is in. With a `width`, prose is
+ * wrapped to it (code and tables never are) and rules span it.
+ */
+export class MarkdownRenderer {
+    private inCodeBlock: boolean = false;
+    private table: string[] = [];
+
+    constructor(private styles: MarkdownStyles, private width?: number) {}
+
+    isInCodeBlock(): boolean {
+        return this.inCodeBlock;
+    }
+
+    /** The lines to print for `line`: none while a table is collected */
+    line(line: string): string[] {
+        if (!this.inCodeBlock && /^\s*\|.*\|\s*$/.test(line)) {
+            this.table.push(line);
+            return [];
+        }
+        return [...this.end(), ...this.render(line)];
+    }
+
+    /** Whatever is still held back, at the end of the answer */
+    end(): string[] {
+        if (this.table.length === 0) {
+            return [];
+        }
+        const table = renderTable(this.table, this.styles);
+        this.table = [];
+        return table;
+    }
+
+    private render(line: string): string[] {
+        const styles = this.styles;
+        if (/^\s*(```|~~~)/.test(line)) {
+            this.inCodeBlock = !this.inCodeBlock;
+            return [styles.fence(line)];
+        }
+        if (this.inCodeBlock) {
+            return [styles.code(line)];
+        }
+
+        let match: RegExpMatchArray | null;
+        if ((match = line.match(/^(#{1,6})\s+(.*?)\s*#*\s*$/))) {
+            return this.wrap(styles.heading(renderInline(match[2], styles), match[1].length));
+        }
+        if (/^\s*([-*_])(\s*\1){2,}\s*$/.test(line)) {
+            return [styles.rule('─'.repeat(this.width ?? RULE_WIDTH))];
+        }
+        if ((match = line.match(/^(\s*)[-*+]\s+(?:\[([ xX])\]\s+)?(.*)$/))) {
+            const marker = match[2] === undefined ? '•' : match[2] === ' ' ? '☐' : '☑';
+            return this.listItem(match[1], marker, match[3]);
+        }
+        if ((match = line.match(/^(\s*)(\d+[.)])\s+(.*)$/))) {
+            return this.listItem(match[1], match[2], match[3]);
+        }
+        if ((match = line.match(/^\s*>\s?(.*)$/))) {
+            const quoted = renderInline(match[1], styles);
+            return (this.width ? wrapVisible(quoted, Math.max(1, this.width - 2)) : [quoted]).map(text => styles.quote(`│ ${text}`));
+        }
+        return this.wrap(styles.text(renderInline(line, styles)), line.match(/^\s*/)![0]);
+    }
+
+    /** Continuation lines line up with the item's text */
+    private listItem(indent: string, marker: string, text: string): string[] {
+        const styles = this.styles;
+        const hanging = ' '.repeat(indent.length + visibleWidth(marker) + 1);
+        return this.wrap(`${indent}${styles.bullet(marker)} ${styles.text(renderInline(text, styles))}`, hanging);
+    }
+
+    private wrap(text: string, indent: string = ''): string[] {
+        return this.width ? wrapVisible(text, this.width, indent) : [text];
+    }
+}
@@ -0,0 +1,120 @@
+import { execFile } from 'child_process';
+import { speechText } from './speech';
+
+/** What a notification button asks the daemon to do with the last answer */
+export type NotificationAction = 'copy-code' | 'open-editor' | 'follow-up';
+
+/** The buttons on an answer's notification, in order */
+export const NOTIFICATION_BUTTONS: Array<[string, NotificationAction]> = [
+    ['Copy code', 'copy-code'],
+    ['Open in editor', 'open-editor'],
+    ['Ask follow-up', 'follow-up']
+];
+
+const TITLE = 'AI Screenshot Analyzer';
+
+// Banners show about two lines; the rest is in the terminal
+const SUMMARY_LENGTH = 180;
+
+/** The start of an answer as one line of plain text, for the notification body */
+export function notificationSummary(answer: string, length: number = SUMMARY_LENGTH): string {
+    const text = speechText(answer).replace(/\s+/g, ' ').trim();
+    return text.length <= length ? text : `${text.slice(0, length - 1).trimEnd()}…`;
+}
+
+/**
+ * Arguments for `alerter`, which shows the buttons and prints the label of
+ * the one clicked (or `@CLOSED`, `@TIMEOUT`, `@CONTENTCLICKED`) on exit.
+ */
+export function alerterArgs(message: string, buttons: Array<[string, NotificationAction]> = NOTIFICATION_BUTTONS): string[] {
+    const args = ['-title', TITLE, '-message', message, '-group', 'ai-screenshot-analyzer'];
+    if (buttons.length > 0) {
+        args.push('-actions', buttons.map(([label]) => label).join(','), '-closeLabel', 'Dismiss', '-timeout', '600');
+    }
+    return args;
+}
+
+/** The action for the button `alerter` reports, if one was clicked */
+export function clickedAction(output: string, buttons: Array<[string, NotificationAction]> = NOTIFICATION_BUTTONS): NotificationAction | undefined {
+    const label = output.trim();
+    return buttons.find(([button]) => button === label)?.[1];
+}
+
+// AppleScript string literal
+function quoted(text: string): string {
+    return `"${text.replace(/\\/g, '\\\\').replace(/"/g, '\\"')}"`;
+}
+
+/**
+ * Show an answer as a desktop notification. On macOS with `alerter`
+ * installed it has `buttons`, and this resolves with the action clicked
+ * once the notification is dismissed; everywhere else (and without
+ * `alerter`) it's a plain banner and this resolves undefined right away.
+ * Throws when no notifier is available at all.
+ */
+export async function notify(message: string, buttons: Array<[string, NotificationAction]> = NOTIFICATION_BUTTONS): Promise<NotificationAction | undefined> {
+    if (process.platform === 'darwin') {
+        if (buttons.length > 0) {
+            try {
+                return clickedAction(await run('alerter', alerterArgs(message, buttons)), buttons);
+            } catch (error) {
+                if ((error as NodeJS.ErrnoException).code !== 'ENOENT') {
+                    throw error;
+                }
+                // Without alerter, a banner with no buttons
+            }
+        }
+        await run('osascript', ['-e', `display notification ${quoted(message)} with title ${quoted(TITLE)}`]);
+        return undefined;
+    }
+    if (process.platform === 'linux') {
+        await run('notify-send', ['--app-name', TITLE, TITLE, message]);
+        return undefined;
+    }
+    throw new Error(`Notifications aren't supported on ${process.platform}`);
+}
+
+/**
+ * Ask for a line of text in a dialog, for "Ask follow-up". Undefined when
+ * cancelled or left empty.
+ */
+export async function promptForText(prompt: string): Promise<string | undefined> {
+    if (process.platform !== 'darwin') {
+        return undefined;
+    }
+    try {
+        const script = `text returned of (display dialog ${quoted(prompt)} default answer "" with title ${quoted(TITLE)})`;
+        return (await run('osascript', ['-e', script])).trim() || undefined;
+    } catch (error) {
+        // Cancel exits with an error
+        return undefined;
+    }
+}
+
+/**
+ * Ask a yes/no question in a dialog. False when it's declined, or when
+ * there's no way to ask.
+ */
+export async function confirmDialog(message: string): Promise<boolean> {
+    try {
+        if (process.platform === 'darwin') {
+            const script = `button returned of (display dialog ${quoted(message)} buttons {"Don't Allow", "Allow"} ` +
+                `default button "Don't Allow" with title ${quoted(TITLE)})`;
+            return (await run('osascript', ['-e', script])).trim() === 'Allow';
+        }
+        if (process.platform === 'linux') {
+            // zenity exits non-zero for No
+            await run('zenity', ['--question', `--title=${TITLE}`, `--text=${message}`]);
+            return true;
+        }
+    } catch (error) {
+        return false;
+    }
+    return false;
+}
+
+function run(command: string, args: string[]): Promise<string> {
+    return new Promise((resolve, reject) => {
+        execFile(command, args, (error, stdout) => error ? reject(error) : resolve(stdout));
+    });
+}
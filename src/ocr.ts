@@ -0,0 +1,50 @@
+import { execFile } from 'child_process';
+import * as fs from 'fs/promises';
+import * as os from 'os';
+import * as path from 'path';
+import { promisify } from 'util';
+import sharp from 'sharp';
+import { CaptureError } from './errors';
+
+const execFileAsync = promisify(execFile);
+
+// Word boxes from `tesseract ... tsv` easily exceed execFile's 1MB default
+const MAX_OUTPUT_BYTES = 50 * 1024 * 1024;
+
+/**
+ * Run the tesseract CLI on an image: plain text, or `tsv` with a box per
+ * word. Throws a `CaptureError` starting with `failure` when tesseract
+ * isn't installed or fails, so each caller can say what needed it.
+ */
+export async function runTesseract(image: Buffer, format: 'txt' | 'tsv', failure: string): Promise<string> {
+    const file = path.join(os.tmpdir(), `ai-screenshot-ocr-${process.pid}-${Date.now()}.png`);
+    try {
+        await fs.writeFile(file, await sharp(image).png().toBuffer());
+        const args = format === 'tsv' ? [file, 'stdout', 'tsv'] : [file, 'stdout'];
+        const { stdout } = await execFileAsync('tesseract', args, { maxBuffer: MAX_OUTPUT_BYTES });
+        return stdout;
+    } catch (error) {
+        throw new CaptureError(`${failure}: ${error}`);
+    } finally {
+        await fs.rm(file, { force: true });
+    }
+}
+
+/**
+ * On-screen text, with the blank lines OCR leaves between blocks collapsed.
+ * Pass the full-resolution capture: small text is lost in the scaled-down
+ * upload image.
+ */
+export async function ocrText(image: Buffer): Promise<string> {
+    const text = await runTesseract(image, 'txt', '--ocr-only needs tesseract installed to read the screen');
+    return text.replace(/\n{3,}/g, '\n\n').trim();
+}
+
+/**
+ * Tells the model it gets OCR'd text instead of the screenshot, for
+ * `--ocr-only`.
+ */
+export function ocrContext(text: string): string {
+    return 'No image is attached. Instead, this is the text on the user\'s screen, extracted by OCR ' +
+        `(it may contain recognition errors):\n\n"""\n${text}\n"""`;
+}
@@ -0,0 +1,28 @@
+import { spawn } from 'child_process';
+
+/**
+ * The command that opens `target` with the default app. On Windows that's
+ * rundll32 rather than `cmd /c start`, whose command line would treat `&`
+ * and `|` in a URL as shell syntax.
+ */
+export function openCommand(target: string, platform: NodeJS.Platform = process.platform): [string, string[]] {
+    switch (platform) {
+        case 'darwin':
+            return ['open', [target]];
+        case 'win32':
+            return ['rundll32', ['url.dll,FileProtocolHandler', target]];
+        default:
+            return ['xdg-open', [target]];
+    }
+}
+
+/**
+ * Open a URL or file with the default app. Fire and forget: callers
+ * print what they open, so a missing opener isn't fatal.
+ */
+export function openPath(target: string): void {
+    const [command, args] = openCommand(target);
+    const child = spawn(command, args, { detached: true, stdio: 'ignore' });
+    child.on('error', () => {});
+    child.unref();
+}
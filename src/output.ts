@@ -0,0 +1,76 @@
+import * as fs from 'fs/promises';
+import * as path from 'path';
+import { TokenUsage } from './ai_client';
+import { redactSecrets } from './redact';
+
+export const OUTPUT_FORMATS = ['text', 'json'] as const;
+export type OutputFormat = typeof OUTPUT_FORMATS[number];
+
+export function isOutputFormat(value: string): value is OutputFormat {
+    return (OUTPUT_FORMATS as readonly string[]).includes(value);
+}
+
+/** One answer as `--output json` prints it: a single line on stdout */
+export interface AnswerRecord {
+    provider: string;
+    model: string;
+    /** The question asked, or null for the default prompt */
+    prompt: string | string[] | null;
+    duration_ms: number;
+    /** Null when the provider didn't report usage */
+    tokens: { input: number; output: number } | null;
+    /** The answer text, or the parsed object for --json and `inspect` */
+    answer: unknown;
+}
+
+export function tokenCounts(usage: TokenUsage | null): AnswerRecord['tokens'] {
+    return usage ? { input: usage.inputTokens, output: usage.outputTokens } : null;
+}
+
+/**
+ * Print `text` on stdout as is. Written directly, past `sendLogsToStderr`
+ * and --quiet, so it has to be redacted here rather than by the console
+ * wrapper.
+ */
+export function writeText(text: string): void {
+    process.stdout.write(redactSecrets(text));
+}
+
+/** Print `value` as one line of JSON on stdout */
+export function writeJson(value: unknown): void {
+    writeText(JSON.stringify(value) + '\n');
+}
+
+/**
+ * Whether to color output going to a stream: never with NO_COLOR set (to
+ * anything, see no-color.org) or TERM=dumb, always with FORCE_COLOR, and
+ * otherwise only on a terminal, so logs and pipes get plain text.
+ */
+export function colorEnabled(env: NodeJS.ProcessEnv, isTTY: boolean | undefined): boolean {
+    if (env.NO_COLOR) {
+        return false;
+    }
+    if (env.FORCE_COLOR !== undefined) {
+        return env.FORCE_COLOR !== '0' && env.FORCE_COLOR !== 'false';
+    }
+    return env.TERM !== 'dumb' && !!isTTY;
+}
+
+/**
+ * Write `text` to `file` for `--out`, replacing what was there and creating
+ * missing parent directories. Redacted like everything printed.
+ */
+export async function writeOutFile(file: string, text: string): Promise<void> {
+    await fs.mkdir(path.dirname(path.resolve(file)), { recursive: true });
+    await fs.writeFile(file, redactSecrets(text.endsWith('\n') ? text : text + '\n'));
+}
+
+/**
+ * Keep stdout for `writeJson`: everything logged through the console goes
+ * to stderr instead, and the screen is never cleared.
+ */
+export function sendLogsToStderr(): void {
+    console.log = console.error;
+    console.info = console.error;
+    console.clear = () => {};
+}
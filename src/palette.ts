@@ -0,0 +1,95 @@
+import sharp from 'sharp';
+
+/** One dominant color and how much of the image it covers */
+export interface PaletteColor {
+    hex: string;
+    rgb: [number, number, number];
+    /** Fraction of the image's pixels, 0-1 */
+    share: number;
+}
+
+// Captures are sampled down to this size; small accents still register
+const SAMPLE_SIZE = 256;
+
+// Colors closer than this (RGB distance) count as one, so anti-aliased
+// edges and subtle gradients don't crowd out real design colors
+const MERGE_DISTANCE = 32;
+
+interface Cluster {
+    r: number;
+    g: number;
+    b: number;
+    pixels: number;
+}
+
+/**
+ * The `count` most common colors in raw pixel data, most common first.
+ * Pixels are bucketed by their top four bits per channel, then buckets
+ * with similar average colors are merged.
+ */
+export function dominantColors(raw: Buffer, channels: number, count: number): PaletteColor[] {
+    const buckets = new Map<number, Cluster>();
+    const total = Math.floor(raw.length / channels);
+    for (let i = 0; i < total * channels; i += channels) {
+        const key = (raw[i] >> 4) << 8 | (raw[i + 1] >> 4) << 4 | raw[i + 2] >> 4;
+        const bucket = buckets.get(key) || { r: 0, g: 0, b: 0, pixels: 0 };
+        bucket.r += raw[i];
+        bucket.g += raw[i + 1];
+        bucket.b += raw[i + 2];
+        bucket.pixels++;
+        buckets.set(key, bucket);
+    }
+
+    const clusters: Cluster[] = [];
+    for (const bucket of [...buckets.values()].sort((a, b) => b.pixels - a.pixels)) {
+        const similar = clusters.find(cluster => distance(cluster, bucket) < MERGE_DISTANCE);
+        if (similar) {
+            similar.r += bucket.r;
+            similar.g += bucket.g;
+            similar.b += bucket.b;
+            similar.pixels += bucket.pixels;
+        } else {
+            clusters.push({ ...bucket });
+        }
+    }
+
+    return clusters
+        .sort((a, b) => b.pixels - a.pixels)
+        .slice(0, count)
+        .map(cluster => {
+            const rgb = [cluster.r, cluster.g, cluster.b].map(sum => Math.round(sum / cluster.pixels)) as [number, number, number];
+            return { hex: toHex(rgb), rgb, share: cluster.pixels / total };
+        });
+}
+
+function distance(a: Cluster, b: Cluster): number {
+    return Math.hypot(a.r / a.pixels - b.r / b.pixels, a.g / a.pixels - b.g / b.pixels, a.b / a.pixels - b.b / b.pixels);
+}
+
+function toHex(rgb: [number, number, number]): string {
+    return '#' + rgb.map(channel => channel.toString(16).padStart(2, '0')).join('');
+}
+
+/** The dominant colors of an encoded image */
+export async function extractPalette(image: Buffer, count: number): Promise<PaletteColor[]> {
+    // Nearest-neighbour sampling keeps real colors instead of blending edges
+    const { data, info } = await sharp(image)
+        .resize(SAMPLE_SIZE, SAMPLE_SIZE, { fit: 'inside', withoutEnlargement: true, kernel: 'nearest' })
+        .removeAlpha()
+        .raw()
+        .toBuffer({ resolveWithObject: true });
+    return dominantColors(data, info.channels, count);
+}
+
+/**
+ * The palette as design tokens: CSS custom properties for a `.css` file,
+ * JSON otherwise.
+ */
+export function paletteTokens(colors: PaletteColor[], file: string): string {
+    if (file.toLowerCase().endsWith('.css')) {
+        const properties = colors.map((color, i) => `  --color-${i + 1}: ${color.hex};`);
+        return `:root {\n${properties.join('\n')}\n}\n`;
+    }
+    const tokens = colors.map(color => ({ ...color, share: Math.round(color.share * 1000) / 1000 }));
+    return JSON.stringify(tokens, null, 2) + '\n';
+}
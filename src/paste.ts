@@ -0,0 +1,75 @@
+import { execFile } from 'child_process';
+import { writeClipboardText } from './clipboard_text';
+import { CaptureError } from './errors';
+
+/** The commands that press the paste shortcut in the focused window, tried in order */
+export function pasteKeystrokeCommands(platform: NodeJS.Platform = process.platform, env: NodeJS.ProcessEnv = process.env): Array<[string, string[]]> {
+    switch (platform) {
+        case 'darwin':
+            return [['osascript', ['-e', 'tell application "System Events" to keystroke "v" using command down']]];
+        case 'win32':
+            return [['powershell', ['-NoProfile', '-Command',
+                'Add-Type -AssemblyName System.Windows.Forms; [System.Windows.Forms.SendKeys]::SendWait("^v")']]];
+        default: {
+            const wayland: [string, string[]] = ['wtype', ['-M', 'ctrl', 'v', '-m', 'ctrl']];
+            // Released first, or a modifier still held from a hotkey turns this into another shortcut
+            const x11: [string, string[]] = ['xdotool', ['key', '--clearmodifiers', 'ctrl+v']];
+            return env.WAYLAND_DISPLAY ? [wayland, x11] : [x11];
+        }
+    }
+}
+
+/**
+ * Wait up to `waitMs` for another window than the terminal to be focused,
+ * calling `onWait` first if the terminal has focus now. True once another
+ * window has it, false when the terminal kept it, and undefined when
+ * `terminalFocused` can't tell - callers shouldn't paste blind then.
+ */
+export async function otherWindowFocused(
+    terminalFocused: () => Promise<boolean | undefined>,
+    waitMs: number,
+    pollMs: number,
+    onWait: () => void = () => {}
+): Promise<boolean | undefined> {
+    const deadline = Date.now() + waitMs;
+    let focused = await terminalFocused();
+    if (focused) {
+        onWait();
+    }
+    while (focused) {
+        if (Date.now() >= deadline) {
+            return false;
+        }
+        await new Promise(resolve => setTimeout(resolve, pollMs));
+        focused = await terminalFocused();
+    }
+    return focused === undefined ? undefined : true;
+}
+
+/**
+ * Type `text` into the focused window by putting it on the clipboard and
+ * pressing the paste shortcut - faster than typing it out key by key, and
+ * editors don't auto-indent a paste. The text stays on the clipboard.
+ */
+export async function insertText(text: string): Promise<void> {
+    await writeClipboardText(text);
+    for (const [command, args] of pasteKeystrokeCommands()) {
+        try {
+            await run(command, args);
+            return;
+        } catch (error) {
+            if ((error as NodeJS.ErrnoException).code !== 'ENOENT') {
+                const hint = process.platform === 'darwin' ? ' - allow your terminal under Privacy & Security > Accessibility' : '';
+                throw new CaptureError(`Pasting with ${command} failed: ${error}${hint}`);
+            }
+        }
+    }
+    const hint = process.platform === 'linux' ? ' - install xdotool (X11) or wtype (Wayland)' : '';
+    throw new CaptureError(`No tool to press the paste shortcut found${hint}`);
+}
+
+function run(command: string, args: string[]): Promise<void> {
+    return new Promise((resolve, reject) => {
+        execFile(command, args, error => error ? reject(error) : resolve());
+    });
+}
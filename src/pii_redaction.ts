@@ -0,0 +1,134 @@
+import sharp from 'sharp';
+import { runTesseract } from './ocr';
+import { MaskRect } from './privacy_mask';
+
+// Extra pixels blurred around each match, so glyph edges don't survive
+const PADDING = 4;
+
+/** A word found by OCR, with its box in image pixels */
+export interface OcrWord {
+    text: string;
+    /** Identifies the line the word is on */
+    line: string;
+    left: number;
+    top: number;
+    width: number;
+    height: number;
+}
+
+const PII_PATTERNS: Array<{ kind: string; pattern: RegExp; valid?: (match: string) => boolean }> = [
+    { kind: 'email', pattern: /[\w.+-]+@[\w-]+(?:\.[\w-]+)+/g },
+    { kind: 'api key', pattern: /sk-[A-Za-z0-9_-]{20,}|AKIA[0-9A-Z]{16}|gh[pousr]_[A-Za-z0-9]{30,}|xox[abprs]-[A-Za-z0-9-]{10,}|AIza[0-9A-Za-z_-]{30,}/g },
+    { kind: 'card number', pattern: /\b\d(?:[ -]?\d){12,18}\b/g, valid: passesLuhn }
+];
+
+/**
+ * Parse tesseract's TSV output into words. Rows at level 5 are words; the
+ * block, paragraph and line numbers identify the line.
+ */
+export function parseTesseractTsv(tsv: string): OcrWord[] {
+    return tsv.split('\n').slice(1).flatMap(row => {
+        const columns = row.split('\t');
+        if (columns.length < 12 || columns[0] !== '5' || !columns[11].trim()) {
+            return [];
+        }
+        const [, page, block, paragraph, line, , left, top, width, height] = columns.map(Number);
+        return [{ text: columns[11].trim(), line: `${page}.${block}.${paragraph}.${line}`, left, top, width, height }];
+    });
+}
+
+/**
+ * Find emails, API keys and card numbers in OCR'd words, returning one box
+ * per match. Matches are looked for across each line, so a card number
+ * split into groups of four is still found.
+ */
+export function findPii(words: OcrWord[]): MaskRect[] {
+    const lines = new Map<string, OcrWord[]>();
+    for (const word of words) {
+        lines.set(word.line, [...(lines.get(word.line) || []), word]);
+    }
+
+    const boxes: MaskRect[] = [];
+    for (const lineWords of lines.values()) {
+        // Where each word starts and ends in the joined line
+        let offset = 0;
+        const spans = lineWords.map(word => {
+            const span = { word, start: offset, end: offset + word.text.length };
+            offset = span.end + 1;
+            return span;
+        });
+        const text = lineWords.map(word => word.text).join(' ');
+
+        for (const { pattern, valid } of PII_PATTERNS) {
+            for (const match of text.matchAll(pattern)) {
+                if (valid && !valid(match[0])) {
+                    continue;
+                }
+                const start = match.index || 0;
+                const end = start + match[0].length;
+                const matched = spans.filter(span => span.start < end && span.end > start).map(span => span.word);
+                boxes.push(boundingBox(matched));
+            }
+        }
+    }
+    return boxes;
+}
+
+function boundingBox(words: OcrWord[]): MaskRect {
+    const left = Math.min(...words.map(word => word.left));
+    const top = Math.min(...words.map(word => word.top));
+    const right = Math.max(...words.map(word => word.left + word.width));
+    const bottom = Math.max(...words.map(word => word.top + word.height));
+    return { x: left, y: top, width: right - left, height: bottom - top };
+}
+
+/** The checksum every real card number passes, to skip phone numbers and ids */
+export function passesLuhn(candidate: string): boolean {
+    const digits = candidate.replace(/\D/g, '');
+    let sum = 0;
+    for (let i = 0; i < digits.length; i++) {
+        let digit = Number(digits[digits.length - 1 - i]);
+        if (i % 2 === 1) {
+            digit *= 2;
+            if (digit > 9) {
+                digit -= 9;
+            }
+        }
+        sum += digit;
+    }
+    return digits.length >= 13 && sum % 10 === 0;
+}
+
+/**
+ * OCR the image locally with tesseract and blur every email, API key and
+ * card number found. Throws a `CaptureError` if OCR isn't available, so an
+ * unredacted image is never uploaded by accident.
+ */
+export async function redactPii(image: Buffer): Promise<{ image: Buffer; regions: number }> {
+    const tsv = await runTesseract(image, 'tsv', 'PII redaction needs tesseract installed (redact_pii is on)');
+    const boxes = findPii(parseTesseractTsv(tsv));
+    if (boxes.length === 0) {
+        return { image, regions: 0 };
+    }
+
+    const { width = 0, height = 0 } = await sharp(image).metadata();
+    const layers = await Promise.all(boxes.map(async box => {
+        const left = Math.max(0, box.x - PADDING);
+        const top = Math.max(0, box.y - PADDING);
+        const region = {
+            left,
+            top,
+            width: Math.min(width - left, box.width + 2 * PADDING),
+            height: Math.min(height - top, box.height + 2 * PADDING)
+        };
+        const blurred = await sharp(image)
+            .extract(region)
+            .blur(Math.max(8, region.height / 2))
+            .png()
+            .toBuffer();
+        return { input: blurred, left, top };
+    }));
+
+    return { image: await sharp(image).composite(layers).png().toBuffer(), regions: boxes.length };
+}
+
@@ -0,0 +1,36 @@
+import sharp from 'sharp';
+
+/**
+ * Steps that make low-contrast captures (dark-mode terminals, washed-out
+ * themes) easier to read for the model and for local OCR:
+ * - `contrast` stretches the luminance to the full range
+ * - `sharpen` crisps up anti-aliased text
+ * - `grayscale` drops color, which also helps PNG compress
+ */
+export const PREPROCESS_STEPS = ['contrast', 'sharpen', 'grayscale'] as const;
+export type PreprocessStep = typeof PREPROCESS_STEPS[number];
+
+export function isPreprocessStep(step: string): step is PreprocessStep {
+    return (PREPROCESS_STEPS as readonly string[]).includes(step);
+}
+
+/** Apply the steps in the order given */
+export async function preprocessImage(image: Buffer, steps: PreprocessStep[]): Promise<Buffer> {
+    let pipeline = sharp(image);
+    for (const step of steps) {
+        switch (step) {
+            case 'contrast':
+                pipeline = pipeline.normalise();
+                break;
+            case 'sharpen':
+                pipeline = pipeline.sharpen();
+                break;
+            case 'grayscale':
+                pipeline = pipeline.grayscale();
+                break;
+        }
+        // sharp applies operations in its own fixed order, so run steps one at a time
+        pipeline = sharp(await pipeline.png().toBuffer());
+    }
+    return pipeline.png().toBuffer();
+}
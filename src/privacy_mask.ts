@@ -0,0 +1,75 @@
+import sharp from 'sharp';
+
+/**
+ * A rectangle in capture pixels. Negative `x`/`y` count from the right and
+ * bottom edges, so a corner can be masked on any resolution.
+ */
+export interface MaskRect {
+    x: number;
+    y: number;
+    width: number;
+    height: number;
+}
+
+/**
+ * Named parts of the desktop, in logical pixels, generous enough to cover
+ * the usual sizes. Notifications appear top-right on macOS and bottom-right
+ * (above the taskbar) elsewhere.
+ */
+const MASK_AREA_RECTS: Record<string, (width: number, platform: NodeJS.Platform) => MaskRect> = {
+    'menu bar': width => ({ x: 0, y: 0, width, height: 38 }),
+    'dock': width => ({ x: 0, y: -90, width, height: 90 }),
+    'taskbar': width => ({ x: 0, y: -48, width, height: 48 }),
+    'system tray': () => ({ x: -360, y: -48, width: 360, height: 48 }),
+    'notifications': (_, platform) => platform === 'darwin'
+        ? { x: -420, y: 38, width: 420, height: 260 }
+        : { x: -420, y: -308, width: 420, height: 260 }
+};
+
+export const MASK_AREAS = Object.keys(MASK_AREA_RECTS);
+
+export function isMaskArea(name: string): boolean {
+    return MASK_AREAS.includes(name.trim().toLowerCase());
+}
+
+/**
+ * Resolve named areas and configured rectangles to absolute, clipped
+ * rectangles on a `width` x `height` capture. `scale` converts the named
+ * areas' logical pixels to the capture's (2 for a Retina capture).
+ */
+export function maskRects(
+    areas: string[],
+    regions: MaskRect[],
+    width: number,
+    height: number,
+    scale: number = 1,
+    platform: NodeJS.Platform = process.platform
+): MaskRect[] {
+    const named = areas
+        .filter(isMaskArea)
+        .map(area => MASK_AREA_RECTS[area.trim().toLowerCase()](width / scale, platform))
+        .map(rect => ({ x: rect.x * scale, y: rect.y * scale, width: rect.width * scale, height: rect.height * scale }));
+
+    return [...named, ...regions].flatMap(rect => {
+        const left = Math.max(0, Math.round(rect.x < 0 ? width + rect.x : rect.x));
+        const top = Math.max(0, Math.round(rect.y < 0 ? height + rect.y : rect.y));
+        const right = Math.min(width, Math.round((rect.x < 0 ? width + rect.x : rect.x) + rect.width));
+        const bottom = Math.min(height, Math.round((rect.y < 0 ? height + rect.y : rect.y) + rect.height));
+        return right > left && bottom > top ? [{ x: left, y: top, width: right - left, height: bottom - top }] : [];
+    });
+}
+
+/** Black out the rectangles, which must lie within the image */
+export async function applyMasks(image: Buffer, rects: MaskRect[]): Promise<Buffer> {
+    if (rects.length === 0) {
+        return image;
+    }
+    const layers = await Promise.all(rects.map(async rect => ({
+        input: await sharp({ create: { width: rect.width, height: rect.height, channels: 3, background: { r: 0, g: 0, b: 0 } } })
+            .png()
+            .toBuffer(),
+        left: rect.x,
+        top: rect.y
+    })));
+    return sharp(image).composite(layers).png().toBuffer();
+}
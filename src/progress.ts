@@ -0,0 +1,203 @@
+import { stripVTControlCharacters } from 'util';
+import { redactSecrets } from './redact';
+
+const SPINNER_FRAMES = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+const REDRAW_INTERVAL_MS = 100;
+
+// Close enough for English text and code with every tokenizer in use
+const CHARS_PER_TOKEN = 4;
+
+export function estimateTokens(chars: number): number {
+    return Math.ceil(chars / CHARS_PER_TOKEN);
+}
+
+export const STAGES = ['capturing', 'encoding', 'uploading', 'waiting', 'receiving', 'rendering'] as const;
+export type Stage = typeof STAGES[number];
+
+// On the progress line until the first token arrives
+const STAGE_LABELS: Record<Stage, string> = {
+    capturing: 'capturing',
+    encoding: 'encoding',
+    uploading: 'uploading',
+    waiting: 'waiting on the model',
+    receiving: 'receiving',
+    rendering: 'rendering'
+};
+
+// In the timings printed after the answer
+const STAGE_NAMES: Record<Stage, string> = {
+    capturing: 'capture',
+    encoding: 'encode',
+    uploading: 'upload',
+    waiting: 'model',
+    receiving: 'answer',
+    rendering: 'render'
+};
+
+export function formatBytes(bytes: number): string {
+    if (bytes < 1024) {
+        return `${bytes} B`;
+    }
+    return bytes < 1024 * 1024 ? `${Math.round(bytes / 1024)} KB` : `${(bytes / 1024 / 1024).toFixed(1)} MB`;
+}
+
+function formatSeconds(ms: number): string {
+    return `${(ms / 1000).toFixed(1)}s`;
+}
+
+/**
+ * When each stage of a capture and its answer began, from grabbing the
+ * screen to printing the answer, for the progress line and the timings
+ * shown after it.
+ */
+export class StageTimer {
+    private entries: { stage: Stage; at: number }[] = [];
+    /** Size of the images sent */
+    bytes?: number;
+
+    /** Start over, for a new capture or answer */
+    reset(): void {
+        this.entries = [];
+        this.bytes = undefined;
+    }
+
+    enter(stage: Stage, at: number = Date.now()): void {
+        if (this.current() !== stage) {
+            this.entries.push({ stage, at });
+        }
+    }
+
+    current(): Stage | undefined {
+        return this.entries[this.entries.length - 1]?.stage;
+    }
+
+    /** The current stage for the progress line, e.g. "uploading 412 KB" */
+    label(): string | undefined {
+        const stage = this.current();
+        if (stage === 'uploading' && this.bytes !== undefined) {
+            return `${STAGE_LABELS.uploading} ${formatBytes(this.bytes)}`;
+        }
+        return stage && STAGE_LABELS[stage];
+    }
+
+    /** Time spent in each stage, the current one until `end` */
+    durations(end: number = Date.now()): Partial<Record<Stage, number>> {
+        const durations: Partial<Record<Stage, number>> = {};
+        this.entries.forEach((entry, index) => {
+            const until = this.entries[index + 1]?.at ?? end;
+            durations[entry.stage] = (durations[entry.stage] ?? 0) + until - entry.at;
+        });
+        return durations;
+    }
+
+    /** The time each stage took, e.g. "capture 0.4s · upload 412 KB 0.6s · model 2.3s" */
+    summary(end: number = Date.now()): string {
+        const durations = this.durations(end);
+        const parts = STAGES.filter(stage => durations[stage] !== undefined).map(stage =>
+            stage === 'uploading' && this.bytes !== undefined
+                ? `${STAGE_NAMES[stage]} ${formatBytes(this.bytes)} ${formatSeconds(durations[stage]!)}`
+                : `${STAGE_NAMES[stage]} ${formatSeconds(durations[stage]!)}`);
+        // Providers that can't tell when the upload is done still say how much was sent
+        if (this.bytes !== undefined && durations.uploading === undefined) {
+            parts.push(`${formatBytes(this.bytes)} sent`);
+        }
+        return parts.join(' · ');
+    }
+}
+
+/** The progress line's stage and remaining time, when known */
+export interface ProgressStatus {
+    stage?: string;
+    etaMs?: number;
+}
+
+/**
+ * The progress line's text: a spinner, the time so far, roughly how many
+ * tokens have arrived (or the stage before that), the time left and the
+ * end of the partial line, cut to `columns`.
+ */
+export function progressText(frame: number, elapsedMs: number, chars: number, partial: string, columns: number, status: ProgressStatus = {}): string {
+    const stats = `${SPINNER_FRAMES[frame % SPINNER_FRAMES.length]} ${formatSeconds(elapsedMs)}`
+        + (chars > 0 ? ` · ~${estimateTokens(chars)} tokens` : ` · ${status.stage ?? 'waiting for the first token'}`)
+        + (status.etaMs === undefined ? '' : ` · ETA ${status.etaMs < 1000 ? '<1s' : `~${Math.ceil(status.etaMs / 1000)}s`}`);
+    const text = partial.trim() ? `${stats} · ${partial.trim()}` : stats;
+    const room = Math.max(1, columns - 1);
+    if (text.length <= room) {
+        return text;
+    }
+    // Keep the stats and the newest whole words, dropping from the middle
+    const tail = partial.trim().slice(-Math.max(1, room - stats.length - 4));
+    return `${stats} · …${tail.includes(' ') ? tail.replace(/^\S*\s+/, '') : tail}`.slice(0, room);
+}
+
+/**
+ * A line at the bottom of the terminal, redrawn in place while an answer
+ * arrives. Does nothing when disabled (not a TTY, or --quiet).
+ */
+export class AnswerProgress {
+    private startedAt: number = Date.now();
+    private chars: number = 0;
+    private partial: string = '';
+    private frame: number = 0;
+    private timer?: NodeJS.Timeout;
+    private shown: boolean = false;
+
+    /**
+     * `stages` names the stage until tokens arrive, and `expectedMs` (how
+     * long answers usually take) gives the time left.
+     */
+    constructor(
+        private enabled: boolean,
+        private style: (text: string) => string = text => text,
+        private stages?: StageTimer,
+        private expectedMs?: number
+    ) {}
+
+    start(): void {
+        if (this.enabled && !this.timer) {
+            this.startedAt = Date.now();
+            this.timer = setInterval(() => {
+                this.frame++;
+                this.draw();
+            }, REDRAW_INTERVAL_MS);
+            this.draw();
+        }
+    }
+
+    /** Count tokens as they arrive; the line shows the newest partial line */
+    add(text: string): void {
+        this.stages?.enter('receiving');
+        this.chars += text.length;
+        const lines = (this.partial + text).split('\n');
+        this.partial = lines[lines.length - 1];
+    }
+
+    /** Erase the line, e.g. to print above it; it comes back on the next redraw */
+    clear(): void {
+        if (this.shown) {
+            process.stdout.write('\r\x1B[K');
+            this.shown = false;
+        }
+    }
+
+    draw(): void {
+        if (!this.timer) {
+            return;
+        }
+        const columns = process.stdout.columns || 80;
+        const partial = stripVTControlCharacters(redactSecrets(this.partial));
+        const elapsed = Date.now() - this.startedAt;
+        const status = {
+            stage: this.stages?.label(),
+            etaMs: this.expectedMs === undefined ? undefined : Math.max(0, this.expectedMs - elapsed)
+        };
+        process.stdout.write(`\r\x1B[K${this.style(progressText(this.frame, elapsed, this.chars, partial, columns, status))}`);
+        this.shown = true;
+    }
+
+    stop(): void {
+        clearInterval(this.timer);
+        this.timer = undefined;
+        this.clear();
+    }
+}
@@ -0,0 +1,67 @@
+import * as readline from 'readline';
+import * as fs from 'fs/promises';
+import * as path from 'path';
+import * as os from 'os';
+
+const HISTORY_FILE = path.join(os.homedir(), '.config', 'ai-screenshot-analyzer', 'question_history');
+const HISTORY_SIZE = 500;
+
+/**
+ * Read a question from the terminal with line editing, Up/Down recall of
+ * previously asked questions (persisted per user), and multi-line input:
+ * end a line with `\` to continue on the next one.
+ */
+export async function promptQuestion(prompt: string): Promise<string> {
+    const history = await loadHistory();
+
+    const rl = readline.createInterface({
+        input: process.stdin,
+        output: process.stdout,
+        terminal: true,
+        history,
+        historySize: HISTORY_SIZE,
+        removeHistoryDuplicates: true
+    });
+
+    const lines: string[] = [];
+    try {
+        let line = await ask(rl, prompt);
+        while (line.endsWith('\\')) {
+            lines.push(line.slice(0, -1));
+            line = await ask(rl, '... ');
+        }
+        lines.push(line);
+    } finally {
+        rl.close();
+    }
+
+    const question = lines.join('\n').trim();
+    if (question) {
+        await saveHistory([question.replace(/\n/g, ' '), ...history]);
+    }
+    return question;
+}
+
+function ask(rl: readline.Interface, prompt: string): Promise<string> {
+    return new Promise(resolve => rl.question(prompt, resolve));
+}
+
+async function loadHistory(): Promise<string[]> {
+    try {
+        const contents = await fs.readFile(HISTORY_FILE, 'utf8');
+        // Stored oldest first; readline expects most recent first
+        return contents.split('\n').filter(line => line.trim()).reverse();
+    } catch (error) {
+        return [];
+    }
+}
+
+async function saveHistory(history: string[]): Promise<void> {
+    const unique = [...new Set(history)].slice(0, HISTORY_SIZE);
+    try {
+        await fs.mkdir(path.dirname(HISTORY_FILE), { recursive: true });
+        await fs.writeFile(HISTORY_FILE, unique.reverse().join('\n') + '\n');
+    } catch (error) {
+        // History is a convenience; never fail a question because of it
+    }
+}
@@ -0,0 +1,99 @@
+import { RateLimitError } from './errors';
+
+// Used when the provider rate limits us without saying for how long
+const DEFAULT_RETRY_AFTER_MS = 30000;
+
+export interface QueuedTrigger {
+    triggeredAt: Date;
+    screenshot: Buffer;
+    question?: string | string[];
+}
+
+/**
+ * Holds triggers that arrive while the provider is rate limiting us and
+ * replays them, oldest first, once the limit resets.
+ */
+export class RateLimitQueue {
+    private items: QueuedTrigger[] = [];
+    private limitedUntil: number = 0;
+    private drainTimer: NodeJS.Timeout | null = null;
+    private draining: boolean = false;
+
+    constructor(private readonly handler: (item: QueuedTrigger) => Promise<void>) {}
+
+    isLimited(): boolean {
+        // Stay "limited" while draining so new triggers queue behind older ones
+        return Date.now() < this.limitedUntil || this.drainTimer !== null || this.draining;
+    }
+
+    size(): number {
+        return this.items.length;
+    }
+
+    secondsUntilReset(): number {
+        return Math.max(0, Math.ceil((this.limitedUntil - Date.now()) / 1000));
+    }
+
+    /**
+     * Record a rate limit response and schedule draining for when it resets.
+     */
+    pause(error: RateLimitError): void {
+        this.limitedUntil = Date.now() + (error.retryAfterMs ?? DEFAULT_RETRY_AFTER_MS);
+        this.scheduleDrain();
+    }
+
+    enqueue(item: QueuedTrigger): void {
+        this.items.push(item);
+    }
+
+    clear(): void {
+        if (this.drainTimer) {
+            clearTimeout(this.drainTimer);
+            this.drainTimer = null;
+        }
+        this.items = [];
+        this.limitedUntil = 0;
+    }
+
+    private scheduleDrain(): void {
+        if (this.drainTimer) {
+            clearTimeout(this.drainTimer);
+        }
+        this.drainTimer = setTimeout(() => {
+            this.drainTimer = null;
+            this.drain();
+        }, Math.max(0, this.limitedUntil - Date.now()));
+    }
+
+    private async drain(): Promise<void> {
+        if (this.items.length > 0) {
+            console.log(`▶️  Rate limit reset, processing ${this.items.length} queued capture(s)...`);
+        }
+
+        this.draining = true;
+        try {
+            await this.drainItems();
+        } finally {
+            this.draining = false;
+        }
+    }
+
+    private async drainItems(): Promise<void> {
+        while (this.items.length > 0) {
+            const item = this.items[0];
+            try {
+                await this.handler(item);
+                this.items.shift();
+            } catch (error) {
+                if (error instanceof RateLimitError) {
+                    // Still limited: keep the item at the front and wait again
+                    this.pause(error);
+                    console.log(`⏳ Still rate limited, retrying in ${this.secondsUntilReset()}s`);
+                    return;
+                }
+                this.items.shift();
+                console.error('❌ Queued capture failed:', error);
+            }
+        }
+    }
+}
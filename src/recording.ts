@@ -0,0 +1,71 @@
+import { compareImages } from './image_diff';
+
+export interface Frame {
+    /** Milliseconds since the recording started */
+    at: number;
+    image: Buffer;
+}
+
+/**
+ * Capture frames at `fps` for `durationMs`. Captures are slower than a
+ * real screen recorder, so frames whose slot has already passed are
+ * skipped rather than captured late.
+ */
+export async function recordFrames(
+    capture: () => Promise<Buffer>,
+    durationMs: number,
+    fps: number,
+    onFrame?: (frame: Frame) => void
+): Promise<Frame[]> {
+    const frameMs = 1000 / fps;
+    const start = Date.now();
+    const frames: Frame[] = [];
+
+    for (let slot = 0; slot * frameMs <= durationMs; slot++) {
+        const wait = start + slot * frameMs - Date.now();
+        if (wait < 0 && slot > 0) {
+            continue;
+        }
+        await new Promise(resolve => setTimeout(resolve, Math.max(0, wait)));
+
+        const frame = { at: Date.now() - start, image: await capture() };
+        frames.push(frame);
+        onFrame?.(frame);
+    }
+    return frames;
+}
+
+/**
+ * Pick at most `max` frame indices to send: the first and last frame, plus
+ * the frames that changed the most from the one before. `changes[i]` is how
+ * much frame `i` differs from frame `i - 1` (0-1; `changes[0]` is ignored).
+ * Frames that didn't change at all are never picked.
+ */
+export function selectKeyframes(changes: number[], max: number): number[] {
+    const last = changes.length - 1;
+    if (last < 0 || max < 1) {
+        return [];
+    }
+    if (last === 0 || max === 1) {
+        return [0];
+    }
+
+    const middle = changes
+        .map((change, index) => ({ change, index }))
+        .slice(1, last)
+        .filter(frame => frame.change > 0)
+        .sort((a, b) => b.change - a.change)
+        .slice(0, max - 2)
+        .map(frame => frame.index);
+
+    return [0, ...middle, last].sort((a, b) => a - b);
+}
+
+/** Sample the frames that best show what happened during the recording */
+export async function sampleKeyframes(frames: Frame[], max: number): Promise<Frame[]> {
+    const changes = [0];
+    for (let i = 1; i < frames.length; i++) {
+        changes.push((await compareImages(frames[i - 1].image, frames[i].image)).changedRatio);
+    }
+    return selectKeyframes(changes, max).map(index => frames[index]);
+}
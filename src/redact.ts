@@ -0,0 +1,57 @@
+import { format } from 'util';
+
+const REDACTED = '[REDACTED]';
+
+// Shapes of credentials that may be echoed back in API errors or debug output
+const SECRET_PATTERNS: RegExp[] = [
+    /sk-ant-[A-Za-z0-9_-]{8,}/g,
+    /sk-[A-Za-z0-9_-]{20,}/g,
+    /(Bearer\s+)[A-Za-z0-9._~+/=-]{8,}/gi,
+    /((?:x-api-key|authorization|api[_-]?key)["']?\s*[:=]\s*["']?)[^\s"',}]{8,}/gi,
+];
+
+const knownSecrets = new Set<string>();
+let installed = false;
+
+/**
+ * Register a secret (e.g. the configured API key) to be masked wherever it
+ * appears, even if it doesn't match one of the known key shapes.
+ */
+export function registerSecret(secret: string | undefined): void {
+    if (secret && secret.length >= 8) {
+        knownSecrets.add(secret);
+    }
+}
+
+export function redactSecrets(text: string): string {
+    let result = text;
+
+    for (const secret of knownSecrets) {
+        result = result.split(secret).join(REDACTED);
+    }
+    for (const pattern of SECRET_PATTERNS) {
+        result = result.replace(pattern, (match, prefix?: string) =>
+            typeof prefix === 'string' ? `${prefix}${REDACTED}` : REDACTED
+        );
+    }
+
+    return result;
+}
+
+/**
+ * Route all console output through `redactSecrets`, so keys never reach the
+ * terminal or any log file the output is redirected to.
+ */
+export function installConsoleRedaction(): void {
+    if (installed) {
+        return;
+    }
+    installed = true;
+
+    for (const method of ['log', 'info', 'warn', 'error', 'debug'] as const) {
+        const original = console[method].bind(console);
+        console[method] = (...args: unknown[]) => {
+            original(redactSecrets(format(...args)));
+        };
+    }
+}
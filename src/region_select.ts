@@ -0,0 +1,111 @@
+import { execFile } from 'child_process';
+import * as fs from 'fs/promises';
+import * as os from 'os';
+import * as path from 'path';
+import { promisify } from 'util';
+import { CancelledError, CaptureError } from './errors';
+
+const execFileAsync = promisify(execFile);
+
+// Screenshots of large displays easily exceed execFile's 1MB default
+const MAX_OUTPUT_BYTES = 200 * 1024 * 1024;
+
+// Freeze the desktop in a borderless full-screen window, let the user drag
+// out a rectangle on it and print that part as a base64 PNG. Esc prints
+// nothing. DPI awareness keeps the frozen image at physical resolution.
+const WINDOWS_SELECT_REGION = `
+Add-Type -Name Dpi -Namespace Native -MemberDefinition '[DllImport("user32.dll")] public static extern bool SetProcessDPIAware();'
+[void][Native.Dpi]::SetProcessDPIAware()
+Add-Type -AssemblyName System.Windows.Forms, System.Drawing
+$bounds = [System.Windows.Forms.SystemInformation]::VirtualScreen
+$screen = New-Object System.Drawing.Bitmap $bounds.Width, $bounds.Height
+[System.Drawing.Graphics]::FromImage($screen).CopyFromScreen($bounds.X, $bounds.Y, 0, 0, $screen.Size)
+$form = New-Object System.Windows.Forms.Form
+$form.FormBorderStyle = 'None'; $form.StartPosition = 'Manual'; $form.Bounds = $bounds; $form.TopMost = $true
+$form.BackgroundImage = $screen; $form.Cursor = [System.Windows.Forms.Cursors]::Cross; $form.KeyPreview = $true
+$script:start = $null; $script:done = $false; $script:selection = [System.Drawing.Rectangle]::Empty
+$form.Add_KeyDown({ if ($_.KeyCode -eq 'Escape') { $form.Close() } })
+$form.Add_MouseDown({ $script:start = $_.Location })
+$form.Add_MouseMove({
+    if ($script:start) {
+        $script:selection = [System.Drawing.Rectangle]::FromLTRB(
+            [Math]::Min($script:start.X, $_.X), [Math]::Min($script:start.Y, $_.Y),
+            [Math]::Max($script:start.X, $_.X), [Math]::Max($script:start.Y, $_.Y))
+        $form.Invalidate()
+    }
+})
+$form.Add_Paint({ if ($script:start) { $_.Graphics.DrawRectangle([System.Drawing.Pens]::Red, $script:selection) } })
+$form.Add_MouseUp({ $script:done = $true; $form.Close() })
+[void]$form.ShowDialog()
+if ($script:done -and $script:selection.Width -gt 1 -and $script:selection.Height -gt 1) {
+    $region = $screen.Clone($script:selection, $screen.PixelFormat)
+    $stream = New-Object System.IO.MemoryStream
+    $region.Save($stream, [System.Drawing.Imaging.ImageFormat]::Png)
+    [Convert]::ToBase64String($stream.ToArray())
+}
+`;
+
+/**
+ * Let the user drag out a region of the screen and return it as a PNG,
+ * using the platform's own selector where there is one. Throws a
+ * `CancelledError` if the selection is cancelled (Esc).
+ */
+export async function selectRegionImage(): Promise<Buffer> {
+    let image: Buffer;
+    try {
+        image = await selectPlatformRegion();
+    } catch (error) {
+        throw new CaptureError(`Region selection failed${linuxToolHint()}: ${error}`);
+    }
+
+    if (image.length === 0) {
+        throw new CancelledError('Region selection cancelled');
+    }
+    return image;
+}
+
+async function selectPlatformRegion(): Promise<Buffer> {
+    switch (process.platform) {
+        case 'darwin': {
+            // screencapture exits without writing the file when Esc is pressed
+            const file = path.join(os.tmpdir(), `ai-screenshot-region-${process.pid}-${Date.now()}.png`);
+            try {
+                await execFileAsync('screencapture', ['-i', '-x', file]);
+                return await fs.readFile(file).catch(() => Buffer.alloc(0));
+            } finally {
+                await fs.rm(file, { force: true });
+            }
+        }
+        case 'win32': {
+            // Windows Forms needs a single-threaded apartment
+            const { stdout } = await execFileAsync(
+                'powershell',
+                ['-NoProfile', '-STA', '-Command', WINDOWS_SELECT_REGION],
+                { maxBuffer: MAX_OUTPUT_BYTES }
+            );
+            return Buffer.from(stdout.trim(), 'base64');
+        }
+        default: {
+            if (process.env.WAYLAND_DISPLAY) {
+                // slurp exits with an error when the selection is cancelled
+                const geometry = await execFileAsync('slurp').then(({ stdout }) => stdout.trim(), () => '');
+                if (!geometry) {
+                    return Buffer.alloc(0);
+                }
+                const { stdout } = await execFileAsync('grim', ['-g', geometry, '-'], { encoding: 'buffer', maxBuffer: MAX_OUTPUT_BYTES });
+                return stdout;
+            }
+            // ImageMagick, which screenshot-desktop already needs on Linux;
+            // without -window it waits for a region to be dragged out
+            const { stdout } = await execFileAsync('import', ['png:-'], { encoding: 'buffer', maxBuffer: MAX_OUTPUT_BYTES });
+            return stdout;
+        }
+    }
+}
+
+function linuxToolHint(): string {
+    if (process.platform !== 'linux') {
+        return '';
+    }
+    return process.env.WAYLAND_DISPLAY ? ' (it needs slurp and grim)' : ' (it needs ImageMagick)';
+}
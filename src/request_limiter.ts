@@ -0,0 +1,46 @@
+import { RequestDroppedError } from './errors';
+
+/**
+ * Caps how many AI requests run at once. Requests over the limit wait in a
+ * short queue; once that is full, further requests are dropped.
+ */
+export class RequestLimiter {
+    private inFlight: number = 0;
+    private waiting: Array<() => void> = [];
+
+    constructor(private readonly maxConcurrent: number, private readonly maxQueued: number) {}
+
+    active(): number {
+        return this.inFlight;
+    }
+
+    queued(): number {
+        return this.waiting.length;
+    }
+
+    async run<T>(task: () => Promise<T>): Promise<T> {
+        if (this.inFlight >= this.maxConcurrent) {
+            if (this.waiting.length >= this.maxQueued) {
+                throw new RequestDroppedError(
+                    `Too many AI requests in progress (${this.inFlight} running, ${this.waiting.length} queued), dropping this one`
+                );
+            }
+            console.log(`⏳ ${this.inFlight} AI request(s) in progress, queued this one (position ${this.waiting.length + 1})`);
+            // The finishing request hands its slot straight to us
+            await new Promise<void>(resolve => this.waiting.push(resolve));
+        } else {
+            this.inFlight++;
+        }
+
+        try {
+            return await task();
+        } finally {
+            const next = this.waiting.shift();
+            if (next) {
+                next();
+            } else {
+                this.inFlight--;
+            }
+        }
+    }
+}
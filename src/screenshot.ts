@@ -1,15 +1,173 @@
+import { EventEmitter } from 'events';
+import * as fs from 'fs/promises';
+import * as path from 'path';
 import screenshot from 'screenshot-desktop';
 import sharp from 'sharp';
+import { CaptureError, PermissionDeniedError } from './errors';
+import { WindowInfo, activeWindow, captureWindowImage, findBlockedWindows, listWindows, matchWindow } from './window_capture';
+import { readClipboardImage } from './clipboard_image';
+import { selectRegionImage } from './region_select';
+import { TerminalWindow } from './terminal_window';
+import { cursorPosition, drawCursor } from './cursor';
+import { MaskRect, applyMasks, maskRects } from './privacy_mask';
+import { redactPii } from './pii_redaction';
+import { captureGdiDisplay } from './gdi_capture';
+import { CropSpec, cropRegion } from './crop';
+import { PreprocessStep, preprocessImage } from './preprocess';
+import { DisplayInfo, configuredScale, detectScaleFactor, isAllScreens, listDisplays, selectDisplay, stitchImages } from './displays';
+
+/**
+ * Encodings for uploads. `auto` picks PNG for text-heavy captures and JPEG
+ * for busy ones. AVIF is left out: the vision APIs don't accept it.
+ */
+export const UPLOAD_FORMATS = ['auto', 'png', 'jpeg', 'webp'] as const;
+export type UploadFormat = typeof UPLOAD_FORMATS[number];
+
+/** What a trigger captures: the screen, the clipboard image, or the focused window */
+export type CaptureSource = 'screen' | 'clipboard' | 'active-window' | 'region';
+
+export const CAPTURE_STATUS: Record<CaptureSource, string> = {
+    'screen': '📸 Capturing screenshot...',
+    'clipboard': '📋 Reading image from clipboard...',
+    'active-window': '🪟 Capturing active window...',
+    'region': '✂️  Select a region to capture (Esc to cancel)...'
+};
+
+export interface CaptureOptions {
+    /** Capture only the window whose app name or title matches this */
+    window?: string;
+    /** Display to capture, by index or name, or "all" (default: the primary display) */
+    screen?: string;
+    /** Downscale so the long edge is at most this many pixels before upload */
+    maxDimension?: number;
+    /** Where to keep full-resolution originals of downscaled captures, if anywhere */
+    originalsDir?: string;
+    format?: UploadFormat;
+    jpegQuality?: number;
+    webpQuality?: number;
+    /** Hide the terminal running the analyzer while the screen is captured */
+    hideTerminal?: boolean;
+    /** Draw the mouse pointer into captures of the primary display */
+    includeCursor?: boolean;
+    /** Shrink HiDPI captures back to logical resolution (a 2x Retina capture to 1x) */
+    downsampleHiDpi?: boolean;
+    /** Scale factors by display index or name, overriding detection */
+    displayScale?: Record<string, number>;
+    /** Named desktop areas blacked out of screen captures, e.g. "menu bar" */
+    maskAreas?: string[];
+    /** Rectangles blacked out of screen captures */
+    maskRegions?: MaskRect[];
+    /** Blur emails, API keys and card numbers found by local OCR */
+    redactPii?: boolean;
+    /** Use the DPI-aware GDI backend on Windows instead of screenshot-desktop's */
+    nativeWindowsCapture?: boolean;
+    /** Keep only this part of the capture */
+    crop?: CropSpec;
+    /** Picks the part of each capture to keep when `crop` isn't set */
+    autoCrop?: (image: Buffer) => Promise<CropSpec | undefined>;
+    /** Readability steps applied before upload (and before PII redaction's OCR) */
+    preprocess?: PreprocessStep[];
+    /** Skip downscaling and upload encoding, returning a lossless PNG (for local OCR) */
+    fullResolution?: boolean;
+    /** Apps whose windows must never be uploaded */
+    blockedApps?: string[];
+    /** Black out blocked windows on the primary display instead of refusing the capture */
+    maskBlockedApps?: boolean;
+    /**
+     * Emits 'captureStart' and 'captureEnd' (with the error, if any) around
+     * every capture, and 'captureEncode' once it's being prepared for upload
+     */
+    events?: EventEmitter;
+}
 
 export class ScreenshotCapture {
-    constructor() {}
+    private terminalWindow = new TerminalWindow();
+
+    constructor(private options: CaptureOptions = {}) {}
+
+    async captureFrom(source: CaptureSource): Promise<Buffer> {
+        this.options.events?.emit('captureStart', source);
+        try {
+            const image = await this.captureSource(source);
+            this.options.events?.emit('captureEnd', source);
+            return image;
+        } catch (error) {
+            this.options.events?.emit('captureEnd', source, error);
+            throw error;
+        }
+    }
+
+    private captureSource(source: CaptureSource): Promise<Buffer> {
+        switch (source) {
+            case 'clipboard':
+                return this.captureClipboard();
+            case 'active-window':
+                return this.captureActiveWindow();
+            case 'region':
+                return this.captureRegion();
+            default:
+                return this.capture();
+        }
+    }
+
+    /**
+     * Capture like `captureFrom`, but as a lossless PNG at the screen's
+     * full resolution rather than the scaled-down upload image - for local
+     * OCR, which misreads small text once it's shrunk.
+     */
+    captureFullResolution(source: CaptureSource): Promise<Buffer> {
+        return new ScreenshotCapture({ ...this.options, downsampleHiDpi: false, fullResolution: true }).captureFrom(source);
+    }
+
+    /**
+     * Capture `frames` images `intervalMs` apart, oldest first, calling
+     * `onFrame` before each one.
+     */
+    async captureBurst(
+        source: CaptureSource,
+        frames: number,
+        intervalMs: number,
+        onFrame?: (frame: number) => void
+    ): Promise<Buffer[]> {
+        const images: Buffer[] = [];
+        for (let frame = 1; frame <= frames; frame++) {
+            if (frame > 1) {
+                await new Promise(resolve => setTimeout(resolve, intervalMs));
+            }
+            onFrame?.(frame);
+            images.push(await this.captureFrom(source));
+        }
+        return images;
+    }
 
     async capture(): Promise<Buffer> {
+        if (this.options.window) {
+            return this.captureWindow(this.options.window);
+        }
+
+        if (isAllScreens(this.options.screen)) {
+            return this.captureAllScreens();
+        }
+
+        const screen = this.options.screen ? await this.resolveScreen(this.options.screen) : undefined;
+        const blockedWindows = await this.checkBlockedApps(!screen);
+
         try {
-            console.log('Capturing screenshot from primary display...');
+            console.log(`Capturing screenshot from ${screen ? `display ${screen.index} (${screen.name})` : 'primary display'}...`);
             
             // Capture screenshot using screenshot-desktop
-            const imageBuffer = await screenshot({ format: 'png' });
+            let imageBuffer = await this.grab(() => this.captureDisplay(screen));
+            
+            // Captures never include the pointer, so draw it in where it was
+            if (this.options.includeCursor && !screen) {
+                const cursor = await cursorPosition();
+                if (cursor) {
+                    imageBuffer = await drawCursor(imageBuffer, cursor);
+                }
+            }
+            
+            imageBuffer = await this.downsampleHiDpi(imageBuffer, screen);
+            imageBuffer = await this.maskPrivateAreas(imageBuffer, screen, !!this.options.downsampleHiDpi, blockedWindows);
             
             console.log('Screenshot captured successfully');
             console.log(`Raw image buffer size: ${imageBuffer.length} bytes`);
@@ -20,40 +178,273 @@ export class ScreenshotCapture {
             return optimizedBuffer;
         } catch (error) {
             console.error('Screenshot capture failed:', error);
-            throw new Error(`Failed to capture screenshot: ${error}`);
+            if (/permission|not authorized|EACCES|EPERM/i.test(String(error))) {
+                throw new PermissionDeniedError(`Screen capture permission denied: ${error}`);
+            }
+            throw new CaptureError(`Failed to capture screenshot: ${error}`);
+        }
+    }
+
+    /**
+     * Capture every display and stitch them into one wide image, for
+     * questions about content spread across monitors.
+     */
+    async captureAllScreens(): Promise<Buffer> {
+        await this.checkBlockedApps(false);
+
+        try {
+            console.log('Capturing screenshot from all displays...');
+            const displays = await this.displays();
+            if (displays.length === 0) {
+                throw new Error('no displays found');
+            }
+            
+            // Capture every display at once rather than one after the other
+            const images = await this.grab(() => Promise.all(displays.map(display => this.captureDisplay(display))));
+            console.log(`Captured ${images.length} display(s)`);
+            
+            // sharp works on a thread pool, so the displays are processed concurrently too.
+            // Each display has its own scale factor and menu bar or taskbar, so do this before stitching
+            const processed = await Promise.all(images.map(async (image, i) => {
+                const downsampled = await this.downsampleHiDpi(image, displays[i]);
+                return this.maskPrivateAreas(downsampled, displays[i], !!this.options.downsampleHiDpi);
+            }));
+            
+            const stitched = processed.length === 1 ? processed[0] : await stitchImages(processed);
+            return await this.optimizeImage(stitched);
+        } catch (error) {
+            console.error('Screenshot capture failed:', error);
+            if (/permission|not authorized|EACCES|EPERM/i.test(String(error))) {
+                throw new PermissionDeniedError(`Screen capture permission denied: ${error}`);
+            }
+            throw new CaptureError(`Failed to capture displays: ${error}`);
+        }
+    }
+
+    /**
+     * Use the image on the clipboard instead of capturing the screen, e.g.
+     * after taking a snip with the OS's own tool.
+     */
+    async captureClipboard(): Promise<Buffer> {
+        console.log('Reading image from the clipboard...');
+        const imageBuffer = await readClipboardImage();
+        console.log(`Clipboard image size: ${imageBuffer.length} bytes`);
+        return this.optimizeImage(imageBuffer);
+    }
+
+    /**
+     * Let the user drag out the part of the screen to analyze. The region
+     * could show a blocked app anywhere on screen, so check them all first.
+     */
+    async captureRegion(): Promise<Buffer> {
+        await this.checkBlockedApps(false);
+        const imageBuffer = await selectRegionImage();
+        console.log(`Region image size: ${imageBuffer.length} bytes`);
+        return this.optimizeImage(imageBuffer);
+    }
+
+    /**
+     * HiDPI displays capture at 2x (or more), doubling upload cost for
+     * detail the model rarely needs.
+     */
+    private async downsampleHiDpi(imageBuffer: Buffer, screen?: DisplayInfo): Promise<Buffer> {
+        if (!this.options.downsampleHiDpi) {
+            return imageBuffer;
+        }
+
+        const factor = await this.scaleFactor(screen);
+        if (factor <= 1) {
+            return imageBuffer;
+        }
+
+        const { width = 0 } = await sharp(imageBuffer).metadata();
+        console.log(`Downsampling ${factor}x HiDPI capture to logical resolution`);
+        return sharp(imageBuffer).resize(Math.round(width / factor)).toBuffer();
+    }
+
+    /** Grab one display (the primary display when undefined) as a PNG */
+    private async captureDisplay(display?: DisplayInfo): Promise<Buffer> {
+        if (this.native()) {
+            return captureGdiDisplay(display?.index);
+        }
+        return screenshot({ format: 'png', ...(display ? { screen: display.id } : {}) });
+    }
+
+    private native(): boolean {
+        return !!this.options.nativeWindowsCapture && process.platform === 'win32';
+    }
+
+    private async displays(): Promise<DisplayInfo[]> {
+        return listDisplays(this.native());
+    }
+
+    private hasMasks(): boolean {
+        return !!this.options.maskAreas?.length || !!this.options.maskRegions?.length;
+    }
+
+    /**
+     * Find windows of `blocked_apps` on screen. They are returned for
+     * masking when that's configured and possible (`canMask`: a capture of
+     * the primary display, whose origin window positions are relative to);
+     * otherwise the capture is refused.
+     */
+    private async checkBlockedApps(canMask: boolean): Promise<MaskRect[]> {
+        const blockedApps = this.options.blockedApps || [];
+        if (blockedApps.length === 0) {
+            return [];
         }
+
+        const blocked = findBlockedWindows(await listWindows(), blockedApps);
+        if (blocked.length === 0) {
+            return [];
+        }
+
+        const apps = [...new Set(blocked.map(window => window.app))].join(', ');
+        if (!canMask || !this.options.maskBlockedApps || blocked.some(window => !window.bounds)) {
+            throw new CaptureError(`Not capturing: ${apps} is on screen (blocked_apps)`);
+        }
+        console.log(`Masking ${blocked.length} window(s) of ${apps}`);
+        return blocked.map(window => window.bounds!);
     }
 
-    private async optimizeImage(imageBuffer: Buffer): Promise<Buffer> {
+    /**
+     * Black out the configured areas of a display capture, and `windows`
+     * (desktop rectangles in logical pixels). Only screen captures are
+     * masked: the coordinates mean nothing in a window or clipboard image.
+     */
+    private async maskPrivateAreas(
+        imageBuffer: Buffer,
+        screen: DisplayInfo | undefined,
+        downsampled: boolean,
+        windows: MaskRect[] = []
+    ): Promise<Buffer> {
+        if (!this.hasMasks() && windows.length === 0) {
+            return imageBuffer;
+        }
+
+        const areas = this.options.maskAreas || [];
+        // Named areas and windows are in logical pixels; a capture at full HiDPI resolution needs them scaled
+        const scale = (areas.length > 0 || windows.length > 0) && !downsampled ? await this.scaleFactor(screen) : 1;
+        const { width = 0, height = 0 } = await sharp(imageBuffer).metadata();
+        const rects = maskRects(areas, [...(this.options.maskRegions || []), ...desktopRects(windows, scale)], width, height, scale);
+
+        console.log(`Masking ${rects.length} private region(s)`);
+        return applyMasks(imageBuffer, rects);
+    }
+
+    private async scaleFactor(screen?: DisplayInfo): Promise<number> {
+        const overrides = this.options.displayScale || {};
+        if (Object.keys(overrides).length > 0) {
+            // Without --screen, the primary display is the first one listed
+            const display = screen ?? (await this.displays())[0];
+            const configured = display ? configuredScale(overrides, display) : undefined;
+            if (configured) {
+                return configured;
+            }
+        }
+        return detectScaleFactor(screen);
+    }
+
+    private async grab<T>(capture: () => Promise<T>): Promise<T> {
+        return this.options.hideTerminal ? this.terminalWindow.hiddenDuring(capture) : capture();
+    }
+
+    private async resolveScreen(selector: string): Promise<DisplayInfo> {
+        const displays = await this.displays();
+        const display = selectDisplay(displays, selector);
+        if (!display) {
+            throw new CaptureError(`No display matching "${selector}" - run \`ai-screenshot-analyzer screens\` to list them`);
+        }
+        return display;
+    }
+
+    /**
+     * Capture a single window, matched by app name or title, so nothing
+     * else on screen ends up in the image.
+     */
+    async captureWindow(matcher: string): Promise<Buffer> {
+        const windows = await listWindows();
+        const window = matchWindow(windows, matcher);
+        if (!window) {
+            const apps = [...new Set(windows.map(w => w.app).filter(app => app))];
+            throw new CaptureError(`No window matching "${matcher}". Open windows belong to: ${apps.join(', ') || 'none'}`);
+        }
+        return this.captureWindowInfo(window);
+    }
+
+    /**
+     * Capture only the frontmost window - usually the app the question is
+     * about. From a terminal, that's the terminal itself, so this is meant
+     * for hotkeys and launchers.
+     */
+    async captureActiveWindow(): Promise<Buffer> {
+        return this.captureWindowInfo(await activeWindow());
+    }
+
+    private async captureWindowInfo(window: WindowInfo): Promise<Buffer> {
+        if (findBlockedWindows([window], this.options.blockedApps || []).length > 0) {
+            throw new CaptureError(`Not capturing: "${window.app}" is in blocked_apps`);
+        }
+
+        try {
+            console.log(`Capturing window: ${window.app}${window.title ? ` - ${window.title}` : ''}`);
+            const imageBuffer = await captureWindowImage(window, this.native());
+            return await this.optimizeImage(imageBuffer);
+        } catch (error) {
+            console.error('Window capture failed:', error);
+            if (/permission|not authorized|EACCES|EPERM/i.test(String(error))) {
+                throw new PermissionDeniedError(`Screen capture permission denied: ${error}`);
+            }
+            throw new CaptureError(`Failed to capture window "${window.app || window.title}": ${error}`);
+        }
+    }
+
+    private async optimizeImage(originalBuffer: Buffer): Promise<Buffer> {
+        this.options.events?.emit('captureEncode');
+        // Outside the try below: if cropping or redaction fails, the capture must fail too
+        const crop = this.options.crop ?? await this.options.autoCrop?.(originalBuffer);
+        if (crop) {
+            originalBuffer = await this.crop(originalBuffer, crop);
+        }
+        if (this.options.preprocess?.length) {
+            console.log(`Preprocessing: ${this.options.preprocess.join(', ')}`);
+            originalBuffer = await preprocessImage(originalBuffer, this.options.preprocess);
+        }
+        if (this.options.redactPii) {
+            const { image, regions } = await redactPii(originalBuffer);
+            console.log(`🕶️  Redacted ${regions} PII region(s)`);
+            originalBuffer = image;
+        }
+        if (this.options.fullResolution) {
+            return sharp(originalBuffer).png().toBuffer();
+        }
+
         try {
             // Get image metadata
-            const metadata = await sharp(imageBuffer).metadata();
+            const metadata = await sharp(originalBuffer).metadata();
             console.log(`Image metadata: ${metadata.width}x${metadata.height}, format: ${metadata.format}`);
             
-            // Analyze image complexity to choose optimal format
-            const complexity = await this.analyzeImageComplexity(imageBuffer);
-            console.log(`Image complexity: ${complexity.toFixed(2)}`);
+            const imageBuffer = await this.downscale(originalBuffer, metadata.width || 0, metadata.height || 0);
             
             let optimizedBuffer: Buffer;
             
-            if (complexity < 0.3) {
-                // Low complexity - use PNG for better text preservation
-                console.log('Using PNG format for low complexity image');
-                optimizedBuffer = await sharp(imageBuffer)
-                    .png({
-                        compressionLevel: 9,
-                        adaptiveFiltering: true
-                    })
-                    .toBuffer();
+            if (this.options.format && this.options.format !== 'auto') {
+                console.log(`Using ${this.options.format.toUpperCase()} format (configured)`);
+                optimizedBuffer = await this.encode(imageBuffer, this.options.format);
             } else {
-                // High complexity - use high-quality JPEG
-                console.log('Using JPEG format for high complexity image');
-                optimizedBuffer = await sharp(imageBuffer)
-                    .jpeg({
-                        quality: 95,
-                        progressive: true
-                    })
-                    .toBuffer();
+                // Analyze image complexity to choose optimal format
+                const complexity = await this.analyzeImageComplexity(imageBuffer);
+                console.log(`Image complexity: ${complexity.toFixed(2)}`);
+                
+                if (complexity < 0.3) {
+                    // Low complexity - use PNG for better text preservation
+                    console.log('Using PNG format for low complexity image');
+                    optimizedBuffer = await this.encode(imageBuffer, 'png');
+                } else {
+                    // High complexity - use high-quality JPEG
+                    console.log('Using JPEG format for high complexity image');
+                    optimizedBuffer = await this.encode(imageBuffer, 'jpeg');
+                }
             }
             
             console.log(`Optimized image size: ${optimizedBuffer.length} bytes`);
@@ -82,8 +473,65 @@ export class ScreenshotCapture {
         } catch (error) {
             console.error('Image optimization failed:', error);
             // Return original buffer if optimization fails
+            return originalBuffer;
+        }
+    }
+
+    private async crop(imageBuffer: Buffer, crop: CropSpec): Promise<Buffer> {
+        const { width = 0, height = 0 } = await sharp(imageBuffer).metadata();
+        const region = cropRegion(crop, width, height);
+        if (!region) {
+            throw new CaptureError(`--crop is outside the ${width}x${height} capture`);
+        }
+        console.log(`Cropping to ${region.width}x${region.height} at ${region.left},${region.top}`);
+        return sharp(imageBuffer).extract(region).png().toBuffer();
+    }
+
+    private async encode(imageBuffer: Buffer, format: Exclude<UploadFormat, 'auto'>): Promise<Buffer> {
+        const image = sharp(imageBuffer);
+        switch (format) {
+            case 'png':
+                return image.png({ compressionLevel: 9, adaptiveFiltering: true }).toBuffer();
+            case 'jpeg':
+                return image.jpeg({ quality: this.options.jpegQuality ?? 95, progressive: true }).toBuffer();
+            case 'webp':
+                // Usually about half the size of PNG for screenshots, with text still crisp
+                return image.webp({ quality: this.options.webpQuality ?? 85, smartSubsample: true }).toBuffer();
+        }
+    }
+
+    /**
+     * 4K/5K captures encode to many megabytes and slow the upload, while
+     * models resize large images anyway, so shrink them to maxDimension.
+     */
+    private async downscale(imageBuffer: Buffer, width: number, height: number): Promise<Buffer> {
+        const maxDimension = this.options.maxDimension;
+        if (!maxDimension || Math.max(width, height) <= maxDimension) {
             return imageBuffer;
         }
+
+        if (this.options.originalsDir) {
+            await this.saveOriginal(imageBuffer);
+        }
+
+        const resized = await sharp(imageBuffer)
+            .resize(maxDimension, maxDimension, { fit: 'inside', withoutEnlargement: true })
+            .toBuffer();
+        console.log(`Downscaled ${width}x${height} to fit ${maxDimension}px`);
+        return resized;
+    }
+
+    private async saveOriginal(imageBuffer: Buffer): Promise<void> {
+        const dir = this.options.originalsDir!;
+        const file = path.join(dir, `${new Date().toISOString().replace(/[:.]/g, '-')}.png`);
+        try {
+            await fs.mkdir(dir, { recursive: true });
+            await fs.writeFile(file, await sharp(imageBuffer).png().toBuffer());
+            console.log(`Original saved to ${file}`);
+        } catch (error) {
+            // Losing the local copy shouldn't stop the analysis
+            console.warn('Failed to save original capture:', error);
+        }
     }
 
     private async analyzeImageComplexity(imageBuffer: Buffer): Promise<number> {
@@ -156,4 +604,19 @@ export class ScreenshotCapture {
         // Default to PNG
         return 'image/png';
     }
-}
\ No newline at end of file
+}
+
+/**
+ * Scale desktop rectangles to capture pixels of the primary display. Parts
+ * left of or above it (on other displays) are cut off, since negative
+ * mask coordinates would count from the opposite edge.
+ */
+function desktopRects(rects: MaskRect[], scale: number): MaskRect[] {
+    return rects.flatMap(rect => {
+        const x = Math.max(0, rect.x);
+        const y = Math.max(0, rect.y);
+        const width = rect.x + rect.width - x;
+        const height = rect.y + rect.height - y;
+        return width > 0 && height > 0 ? [{ x: x * scale, y: y * scale, width: width * scale, height: height * scale }] : [];
+    });
+}
@@ -0,0 +1,260 @@
+import { execFile } from 'child_process';
+import * as fs from 'fs/promises';
+import * as os from 'os';
+import * as path from 'path';
+import { promisify } from 'util';
+import { PROVIDER_KEY_ENV } from './ai_client';
+import { ConfigError } from './errors';
+
+const execFileAsync = promisify(execFile);
+
+const LAUNCHD_LABEL = 'com.ai-screenshot-analyzer.daemon';
+const LOGIN_LABEL = 'com.ai-screenshot-analyzer.login';
+const SYSTEMD_UNIT = 'ai-screenshot-analyzer.service';
+
+// Service managers start with a bare environment: pass through what the
+// daemon needs to find its tools, its display and its API keys
+const PASSED_ENV = [
+    'PATH',
+    'AI_API_KEY',
+    ...Object.values(PROVIDER_KEY_ENV).flat(),
+    'HTTPS_PROXY', 'HTTP_PROXY', 'ALL_PROXY', 'NO_PROXY',
+    'https_proxy', 'http_proxy', 'all_proxy', 'no_proxy',
+    'DISPLAY', 'WAYLAND_DISPLAY', 'XAUTHORITY'
+];
+
+/** What the service runs, and where its output goes */
+export interface ServiceSpec {
+    /** The program followed by its arguments */
+    command: string[];
+    env: Record<string, string>;
+    logFile: string;
+    errorLogFile: string;
+}
+
+/** Where the service definition lives on this platform */
+export function serviceFile(platform: NodeJS.Platform = process.platform): string {
+    if (platform === 'darwin') {
+        return path.join(os.homedir(), 'Library', 'LaunchAgents', `${LAUNCHD_LABEL}.plist`);
+    }
+    if (platform === 'linux') {
+        const configHome = process.env.XDG_CONFIG_HOME || path.join(os.homedir(), '.config');
+        return path.join(configHome, 'systemd', 'user', SYSTEMD_UNIT);
+    }
+    throw new ConfigError('Services can only be installed on macOS (launchd) and Linux (systemd)');
+}
+
+/** This CLI as it was started (same Node, same script), with `args` */
+export function cliCommand(args: string[]): string[] {
+    return [process.execPath, path.resolve(process.argv[1]), ...args];
+}
+
+/** The variables a process started outside a shell needs, from this one */
+export function passedEnvironment(): Record<string, string> {
+    const env: Record<string, string> = {};
+    for (const name of PASSED_ENV) {
+        if (process.env[name]) {
+            env[name] = process.env[name]!;
+        }
+    }
+    return env;
+}
+
+/** Where services and login items write their output */
+export function logDir(platform: NodeJS.Platform = process.platform): string {
+    return platform === 'darwin'
+        ? path.join(os.homedir(), 'Library', 'Logs', 'ai-screenshot-analyzer')
+        : path.join(process.env.XDG_STATE_HOME || path.join(os.homedir(), '.local', 'state'), 'ai-screenshot-analyzer');
+}
+
+/**
+ * The daemon as this process was started, with `args`, the environment
+ * it needs and per-platform log paths.
+ */
+export function currentServiceSpec(args: string[], platform: NodeJS.Platform = process.platform): ServiceSpec {
+    return {
+        command: cliCommand(['run', ...args]),
+        env: passedEnvironment(),
+        logFile: path.join(logDir(platform), 'daemon.log'),
+        errorLogFile: path.join(logDir(platform), 'daemon.err.log')
+    };
+}
+
+function xmlEscape(text: string): string {
+    return text.replace(/&/g, '&amp;').replace(/</g, '&lt;').replace(/>/g, '&gt;').replace(/"/g, '&quot;');
+}
+
+/**
+ * A launchd agent that starts the daemon at login and, with `keepAlive`,
+ * restarts it if it crashes
+ */
+export function launchdPlist(spec: ServiceSpec, label: string = LAUNCHD_LABEL, keepAlive: boolean = true): string {
+    const string = (value: string) => `<string>${xmlEscape(value)}</string>`;
+    const lines = [
+        '<?xml version="1.0" encoding="UTF-8"?>',
+        '<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">',
+        '<plist version="1.0">',
+        '<dict>',
+        '  <key>Label</key>',
+        `  ${string(label)}`,
+        '  <key>ProgramArguments</key>',
+        '  <array>',
+        ...spec.command.map(arg => `    ${string(arg)}`),
+        '  </array>',
+        '  <key>EnvironmentVariables</key>',
+        '  <dict>',
+        ...Object.entries(spec.env).flatMap(([name, value]) => [`    <key>${xmlEscape(name)}</key>`, `    ${string(value)}`]),
+        '  </dict>',
+        '  <key>RunAtLoad</key>',
+        '  <true/>',
+        ...(keepAlive ? [
+            '  <key>KeepAlive</key>',
+            '  <dict>',
+            '    <key>SuccessfulExit</key>',
+            '    <false/>',
+            '  </dict>'
+        ] : []),
+        '  <key>StandardOutPath</key>',
+        `  ${string(spec.logFile)}`,
+        '  <key>StandardErrorPath</key>',
+        `  ${string(spec.errorLogFile)}`,
+        '</dict>',
+        '</plist>'
+    ];
+    return lines.join('\n') + '\n';
+}
+
+// systemd splits ExecStart on spaces and expands `$` and `%`
+function systemdQuote(value: string): string {
+    const escaped = value.replace(/\\/g, '\\\\').replace(/"/g, '\\"').replace(/\$/g, '$$$$').replace(/%/g, '%%');
+    return /[\s"'\\$%]/.test(value) ? `"${escaped}"` : escaped;
+}
+
+/** A systemd user unit that starts the daemon with the graphical session */
+export function systemdUnit(spec: ServiceSpec): string {
+    const lines = [
+        '[Unit]',
+        'Description=AI Screenshot Analyzer daemon',
+        'After=graphical-session.target',
+        'PartOf=graphical-session.target',
+        '',
+        '[Service]',
+        `ExecStart=${spec.command.map(systemdQuote).join(' ')}`,
+        ...Object.entries(spec.env).map(([name, value]) => `Environment=${systemdQuote(`${name}=${value}`)}`),
+        'Restart=on-failure',
+        'RestartSec=5',
+        `StandardOutput=append:${spec.logFile.replace(/%/g, '%%')}`,
+        `StandardError=append:${spec.errorLogFile.replace(/%/g, '%%')}`,
+        '',
+        '[Install]',
+        'WantedBy=graphical-session.target'
+    ];
+    return lines.join('\n') + '\n';
+}
+
+/**
+ * Write the service definition and start it now and at every login. The
+ * file holds API keys from the environment, so only the user can read it.
+ */
+export async function installService(spec: ServiceSpec): Promise<string> {
+    const file = serviceFile();
+    const contents = process.platform === 'darwin' ? launchdPlist(spec) : systemdUnit(spec);
+    await fs.mkdir(path.dirname(file), { recursive: true });
+    await fs.mkdir(path.dirname(spec.logFile), { recursive: true });
+    await fs.writeFile(file, contents, { mode: 0o600 });
+    // `mode` only applies to new files
+    await fs.chmod(file, 0o600);
+
+    if (process.platform === 'darwin') {
+        const domain = `gui/${os.userInfo().uid}`;
+        // Replace an older install; fails harmlessly when there is none
+        await execFileAsync('launchctl', ['bootout', `${domain}/${LAUNCHD_LABEL}`]).catch(() => {});
+        await execFileAsync('launchctl', ['bootstrap', domain, file]);
+    } else {
+        await execFileAsync('systemctl', ['--user', 'daemon-reload']);
+        await execFileAsync('systemctl', ['--user', 'enable', SYSTEMD_UNIT]);
+        // Restart rather than start, so reinstalling applies the new unit
+        await execFileAsync('systemctl', ['--user', 'restart', SYSTEMD_UNIT]);
+    }
+    return file;
+}
+
+/** Stop the service and remove its definition. Returns false if it wasn't installed. */
+export async function uninstallService(): Promise<boolean> {
+    const file = serviceFile();
+    try {
+        await fs.access(file);
+    } catch (error) {
+        return false;
+    }
+
+    if (process.platform === 'darwin') {
+        await execFileAsync('launchctl', ['bootout', `gui/${os.userInfo().uid}/${LAUNCHD_LABEL}`]).catch(() => {});
+        await fs.unlink(file);
+    } else {
+        await execFileAsync('systemctl', ['--user', 'disable', '--now', SYSTEMD_UNIT]).catch(() => {});
+        await fs.unlink(file);
+        await execFileAsync('systemctl', ['--user', 'daemon-reload']);
+    }
+    return true;
+}
+
+/** Where the login item lives on this platform */
+export function loginItemFile(platform: NodeJS.Platform = process.platform): string {
+    if (platform === 'darwin') {
+        return path.join(os.homedir(), 'Library', 'LaunchAgents', `${LOGIN_LABEL}.plist`);
+    }
+    if (platform === 'linux') {
+        const configHome = process.env.XDG_CONFIG_HOME || path.join(os.homedir(), '.config');
+        return path.join(configHome, 'autostart', 'ai-screenshot-analyzer.desktop');
+    }
+    throw new ConfigError('run_at_login is only supported on macOS and Linux');
+}
+
+// Desktop entries quote like a shell, but only inside double quotes, and
+// the whole line is then unescaped once more as a string value
+export function desktopQuote(value: string): string {
+    const quoted = /[\s"'\\`$<>~|&;*?#()]/.test(value) ? `"${value.replace(/(["`$\\])/g, '\\$1')}"` : value;
+    return quoted.replace(/\\/g, '\\\\').replace(/%/g, '%%');
+}
+
+/**
+ * An XDG autostart entry for the daemon. Desktop entries can't set
+ * variables, so the environment is passed through `env`.
+ */
+export function desktopEntry(spec: ServiceSpec): string {
+    const variables = Object.entries(spec.env).map(([name, value]) => `${name}=${value}`);
+    const command = variables.length > 0 ? ['env', ...variables, ...spec.command] : spec.command;
+    const lines = [
+        '[Desktop Entry]',
+        'Type=Application',
+        'Name=AI Screenshot Analyzer',
+        'Comment=Start the AI Screenshot Analyzer daemon at login',
+        `Exec=${command.map(desktopQuote).join(' ')}`,
+        'Terminal=false',
+        'X-GNOME-Autostart-enabled=true'
+    ];
+    return lines.join('\n') + '\n';
+}
+
+/**
+ * Add or remove the login item that starts the daemon. On macOS it's a
+ * launch agent that runs once at login - what System Settings lists under
+ * Login Items for apps that aren't bundles (SMAppService needs a signed app).
+ * Unlike `installService`, nothing is started now.
+ */
+export async function setRunAtLogin(enabled: boolean, spec: ServiceSpec): Promise<string> {
+    const file = loginItemFile();
+    if (!enabled) {
+        await fs.rm(file, { force: true });
+        return file;
+    }
+
+    const contents = process.platform === 'darwin' ? launchdPlist(spec, LOGIN_LABEL, false) : desktopEntry(spec);
+    await fs.mkdir(path.dirname(file), { recursive: true });
+    await fs.mkdir(path.dirname(spec.logFile), { recursive: true });
+    // Holds API keys from the environment
+    await fs.writeFile(file, contents, { mode: 0o600 });
+    await fs.chmod(file, 0o600);
+    return file;
+}
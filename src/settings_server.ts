@@ -0,0 +1,184 @@
+import * as http from 'http';
+import { randomBytes } from 'crypto';
+import { PROVIDER_NAMES } from './ai_client';
+import { AppConfig } from './config';
+import { parseModifierKey } from './double_tap';
+import { openPath } from './open_path';
+import { THEME_PRESETS } from './theme';
+
+interface SettingsField {
+    key: keyof AppConfig;
+    label: string;
+    type: 'text' | 'number' | 'password' | 'checkbox' | 'select';
+    /** The choices of a select; a blank choice stands for the default */
+    options?: readonly string[];
+    /** An error message for a value the config would reject */
+    validate?: (value: string) => string | undefined;
+}
+
+// Provider and key, the double-tap hotkey and output preferences; the rest
+// stays in config.toml. The hotkey chords themselves are fixed, and the
+// prompt comes from --prompt-file, so neither is edited here.
+const SETTINGS_FIELDS: SettingsField[] = [
+    {
+        key: 'doubleTapKey',
+        label: 'Double-tap hotkey, e.g. right cmd (blank for none)',
+        type: 'text',
+        validate: value => parseModifierKey(value) ? undefined : 'Use a left or right modifier, e.g. "right cmd"'
+    },
+    { key: 'defaultProvider', label: 'AI provider (blank for claude)', type: 'select', options: PROVIDER_NAMES },
+    { key: 'model', label: 'Model (blank for the provider default)', type: 'text' },
+    { key: 'apiKey', label: 'API key', type: 'password' },
+    { key: 'responseLanguage', label: 'Answer language (blank for the language on screen)', type: 'text' },
+    { key: 'maxTokens', label: 'Max answer length in tokens (blank for 500)', type: 'number' },
+    { key: 'theme', label: 'Terminal colors (blank for dark)', type: 'select', options: THEME_PRESETS },
+    { key: 'copyAnswers', label: 'Copy every answer to the clipboard', type: 'checkbox' },
+    { key: 'saveCaptures', label: 'Keep every capture in the screenshots directory', type: 'checkbox' },
+    { key: 'saveResults', label: 'Keep a markdown record of every answer', type: 'checkbox' },
+];
+
+/**
+ * A small settings page served on localhost and opened in the default
+ * browser, for editing config.toml without touching the file by hand.
+ * Resolves when the user closes the page with "Done".
+ */
+export async function openSettingsWindow(config: AppConfig): Promise<void> {
+    // Random token in the URL so other local pages can't post to the form
+    const token = randomBytes(16).toString('hex');
+
+    await new Promise<void>((resolve, reject) => {
+        const server = http.createServer(async (req, res) => {
+            const url = new URL(req.url || '/', 'http://127.0.0.1');
+            if (url.searchParams.get('token') !== token) {
+                res.writeHead(403).end('Forbidden');
+                return;
+            }
+
+            if (req.method === 'POST' && url.pathname === '/done') {
+                res.writeHead(200, { 'Content-Type': 'text/html' }).end(renderPage(config, token, 'Settings closed. You can close this tab.', true));
+                server.close();
+                resolve();
+                return;
+            }
+
+            if (req.method === 'POST' && url.pathname === '/') {
+                const body = await readBody(req);
+                const message = await applySettings(config, new URLSearchParams(body));
+                res.writeHead(200, { 'Content-Type': 'text/html' }).end(renderPage(config, token, message));
+                return;
+            }
+
+            res.writeHead(200, { 'Content-Type': 'text/html' }).end(renderPage(config, token));
+        });
+
+        server.on('error', reject);
+        server.listen(0, '127.0.0.1', () => {
+            const address = server.address();
+            const port = typeof address === 'object' && address ? address.port : 0;
+            const url = `http://127.0.0.1:${port}/?token=${token}`;
+
+            console.log(`⚙️  Settings are open at ${url}`);
+            console.log('   Press "Done" on the page (or Ctrl+C) when finished.');
+            openPath(url);
+        });
+    });
+}
+
+/**
+ * Save the posted form. A blank field (or an unchecked box) removes its
+ * setting, so the default applies again; a blank API key keeps the current
+ * one, since it's never shown.
+ */
+export async function applySettings(config: AppConfig, form: URLSearchParams): Promise<string> {
+    const changes: Partial<Record<keyof AppConfig, unknown>> = {};
+
+    for (const field of SETTINGS_FIELDS) {
+        const value = (form.get(field.key) || '').trim();
+        if (field.type === 'checkbox') {
+            changes[field.key] = form.has(field.key) || undefined;
+        } else if (field.type === 'password') {
+            if (value) {
+                changes[field.key] = value;
+            }
+        } else if (!value) {
+            changes[field.key] = undefined;
+        } else if (field.type === 'number') {
+            const parsed = Number(value);
+            if (isNaN(parsed) || parsed <= 0) {
+                return `❌ ${field.label} must be a positive number`;
+            }
+            changes[field.key] = parsed;
+        } else if (field.options && !field.options.includes(value)) {
+            return `❌ ${field.label} must be one of ${field.options.join(', ')}`;
+        } else if (field.validate?.(value)) {
+            return `❌ ${field.label}: ${field.validate(value)}`;
+        } else {
+            changes[field.key] = value;
+        }
+    }
+
+    // Through the constructor, so required settings left blank get their defaults
+    Object.assign(config, new AppConfig({ ...config, ...changes } as Partial<AppConfig>));
+    await config.save();
+    return '✅ Settings saved';
+}
+
+function renderPage(config: AppConfig, token: string, message?: string, closed: boolean = false): string {
+    const rows = SETTINGS_FIELDS.map(field => {
+        if (field.type === 'checkbox') {
+            return `<label><input name="${field.key}" type="checkbox"${config[field.key] ? ' checked' : ''}> ${field.label}</label>`;
+        }
+        if (field.type === 'select') {
+            const current = String(config[field.key] ?? '');
+            const options = ['', ...(field.options || [])].map(option =>
+                `<option value="${option}"${option === current ? ' selected' : ''}>${option}</option>`
+            ).join('');
+            return `<label>${field.label}<select name="${field.key}">${options}</select></label>`;
+        }
+        const value = field.type === 'password' ? '' : String(config[field.key] ?? '');
+        const placeholder = field.type === 'password' && config.apiKey ? 'unchanged' : '';
+        return `<label>${field.label}<input name="${field.key}" type="${field.type}" value="${escapeHtml(value)}" placeholder="${placeholder}"></label>`;
+    }).join('\n');
+
+    return `<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>AI Screenshot Analyzer Settings</title>
+<style>
+    body { font-family: -apple-system, 'Segoe UI', sans-serif; max-width: 520px; margin: 40px auto; }
+    label { display: block; margin: 12px 0; }
+    input, select { display: block; width: 100%; padding: 6px; margin-top: 4px; box-sizing: border-box; }
+    input[type=checkbox] { display: inline; width: auto; }
+    button { margin-right: 8px; padding: 8px 16px; }
+</style>
+</head>
+<body>
+<h1>🤖 Settings</h1>
+${message ? `<p>${escapeHtml(message)}</p>` : ''}
+${closed ? '' : `<form method="post" action="/?token=${token}">
+${rows}
+<button type="submit">Save</button>
+<button type="submit" formaction="/done?token=${token}">Done</button>
+</form>`}
+</body>
+</html>`;
+}
+
+function readBody(req: http.IncomingMessage): Promise<string> {
+    return new Promise((resolve, reject) => {
+        let body = '';
+        req.setEncoding('utf8');
+        req.on('data', chunk => { body += chunk; });
+        req.on('end', () => resolve(body));
+        req.on('error', reject);
+    });
+}
+
+function escapeHtml(text: string): string {
+    return text
+        .replace(/&/g, '&amp;')
+        .replace(/</g, '&lt;')
+        .replace(/>/g, '&gt;')
+        .replace(/"/g, '&quot;');
+}
@@ -0,0 +1,63 @@
+import { spawn } from 'child_process';
+
+export type SoundCue = 'capture' | 'done' | 'error';
+
+// Built-in sounds every install of each platform ships with
+const SYSTEM_SOUNDS: Record<string, Record<SoundCue, string>> = {
+    darwin: {
+        capture: '/System/Library/Sounds/Tink.aiff',
+        done: '/System/Library/Sounds/Glass.aiff',
+        error: '/System/Library/Sounds/Basso.aiff'
+    },
+    linux: {
+        capture: '/usr/share/sounds/freedesktop/stereo/camera-shutter.oga',
+        done: '/usr/share/sounds/freedesktop/stereo/complete.oga',
+        error: '/usr/share/sounds/freedesktop/stereo/dialog-error.oga'
+    },
+    win32: {
+        capture: 'C:\\Windows\\Media\\Windows Navigation Start.wav',
+        done: 'C:\\Windows\\Media\\Windows Notify System Generic.wav',
+        error: 'C:\\Windows\\Media\\Windows Critical Stop.wav'
+    }
+};
+
+/**
+ * The commands that can play `file` (or the cue's system sound), tried in
+ * order. Windows plays WAV files only.
+ */
+export function soundCommands(cue: SoundCue, file?: string, platform: NodeJS.Platform = process.platform): Array<[string, string[]]> {
+    const sound = file || SYSTEM_SOUNDS[platform]?.[cue];
+    if (!sound) {
+        return [];
+    }
+    switch (platform) {
+        case 'darwin':
+            return [['afplay', [sound]]];
+        case 'win32':
+            return [['powershell', ['-NoProfile', '-Command', `(New-Object Media.SoundPlayer '${sound.replace(/'/g, "''")}').PlaySync()`]]];
+        default:
+            return [['paplay', [sound]], ['ffplay', ['-nodisp', '-autoexit', '-loglevel', 'quiet', sound]]];
+    }
+}
+
+/**
+ * Play a cue without waiting for it. A missing player or sound file is
+ * ignored: cues are a nicety, never a reason to fail a capture.
+ */
+export function playSound(cue: SoundCue, file?: string): void {
+    const play = (commands: Array<[string, string[]]>) => {
+        if (commands.length === 0) {
+            return;
+        }
+        const [[command, args], ...fallbacks] = commands;
+        const child = spawn(command, args, { stdio: 'ignore', detached: true });
+        // Only a missing player falls through; anything else just stays quiet
+        child.on('error', (error: NodeJS.ErrnoException) => {
+            if (error.code === 'ENOENT') {
+                play(fallbacks);
+            }
+        });
+        child.unref();
+    };
+    play(soundCommands(cue, file));
+}
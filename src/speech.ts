@@ -0,0 +1,116 @@
+import { ChildProcess, spawn } from 'child_process';
+
+/**
+ * An answer as it should be read aloud: markdown markup dropped, links
+ * read by their label and code blocks summarized rather than spelled out,
+ * since they're on screen to copy anyway.
+ */
+export function speechText(markdown: string): string {
+    const lines: string[] = [];
+    let fence: RegExp | undefined;
+    let language = '';
+    let codeLines = 0;
+    const summarizeCode = () => {
+        lines.push(`(${language ? `${language} ` : ''}code, ${codeLines} line${codeLines === 1 ? '' : 's'}, shown on screen.)`);
+        fence = undefined;
+    };
+
+    for (const line of markdown.split('\n')) {
+        if (fence) {
+            if (fence.test(line)) {
+                summarizeCode();
+            } else {
+                codeLines++;
+            }
+            continue;
+        }
+        const open = line.match(/^\s*(`{3,}|~{3,})(.*)$/);
+        if (open) {
+            fence = new RegExp(`^\\s*${open[1][0]}{${open[1].length},}\\s*$`);
+            language = open[2].trim().split(/\s+/)[0];
+            codeLines = 0;
+            continue;
+        }
+        // Rules and the separator row of tables
+        if (/^\s*([-*_])(\s*\1){2,}\s*$/.test(line) || /^\s*\|?(\s*:?-+:?\s*\|)+\s*:?-*:?\s*\|?\s*$/.test(line)) {
+            continue;
+        }
+        lines.push(line
+            .replace(/^\s*#{1,6}\s+(.*?)\s*#*\s*$/, '$1')
+            .replace(/^\s*>\s?/, '')
+            .replace(/^\s*([-*+]|\d+[.)])\s+(\[[ xX]\]\s+)?/, '')
+            .replace(/^\s*\|(.*)\|\s*$/, (match, cells: string) => cells.split('|').map(cell => cell.trim()).join(', '))
+            .replace(/\[([^\]]+)\]\([^)\s]+\)/g, '$1')
+            .replace(/`([^`]+)`/g, '$1')
+            .replace(/\*\*(.+?)\*\*|__(.+?)__/g, '$1$2')
+            .replace(/(?<![*\w])\*(?=\S)(.+?)(?<=\S)\*(?![*\w])|(?<![_\w])_(?=\S)(.+?)(?<=\S)_(?![_\w])/g, '$1$2')
+            .trim());
+    }
+    if (fence) {
+        // A truncated answer can end inside a block
+        summarizeCode();
+    }
+    return lines.join('\n').replace(/\n{3,}/g, '\n\n').trim();
+}
+
+/** The text-to-speech commands that read stdin aloud, tried in order */
+export function speechCommands(platform: NodeJS.Platform = process.platform): Array<[string, string[]]> {
+    switch (platform) {
+        case 'darwin':
+            return [['say', ['-f', '-']]];
+        case 'win32':
+            return [['powershell', ['-NoProfile', '-Command',
+                'Add-Type -AssemblyName System.Speech; [Console]::InputEncoding = [Text.Encoding]::UTF8; ' +
+                '(New-Object System.Speech.Synthesis.SpeechSynthesizer).Speak([Console]::In.ReadToEnd())']]];
+        default:
+            return [['espeak-ng', ['--stdin']], ['espeak', ['--stdin']], ['spd-say', ['--wait', '--pipe-mode']]];
+    }
+}
+
+// The answer being read, so the next one (or a cancel) can cut it short
+let speaking: ChildProcess | undefined;
+
+/**
+ * Start reading `text` aloud, cutting off whatever was still being read.
+ * Resolves once the voice has started, not when it finishes; throws when
+ * no text-to-speech engine is installed.
+ */
+export async function speak(text: string): Promise<void> {
+    stopSpeaking();
+    for (const [command, args] of speechCommands()) {
+        try {
+            speaking = await startSpeech(command, args, text);
+            return;
+        } catch (error) {
+            if ((error as NodeJS.ErrnoException).code !== 'ENOENT') {
+                throw new Error(`Reading aloud with ${command} failed: ${error}`);
+            }
+        }
+    }
+    const hint = process.platform === 'linux' ? ' - install espeak-ng or speech-dispatcher' : '';
+    throw new Error(`No text-to-speech engine found${hint}`);
+}
+
+/** Stop reading aloud. Returns whether anything was being read. */
+export function stopSpeaking(): boolean {
+    const child = speaking;
+    speaking = undefined;
+    if (!child || child.exitCode !== null || child.signalCode !== null) {
+        return false;
+    }
+    child.kill();
+    return true;
+}
+
+function startSpeech(command: string, args: string[], text: string): Promise<ChildProcess> {
+    return new Promise((resolve, reject) => {
+        const child = spawn(command, args, { stdio: ['pipe', 'ignore', 'ignore'] });
+        child.once('error', reject);
+        child.once('spawn', () => {
+            // Killed mid-sentence, the engine stops reading its stdin
+            child.stdin?.on('error', () => {});
+            child.stdin?.end(text);
+            resolve(child);
+        });
+    });
+}
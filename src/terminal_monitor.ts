@@ -1,14 +1,31 @@
 // src/terminal_monitor.ts
 import * as readline from 'readline';
-import { AppState } from './main';
-import { printStatus, printAnalysisResult } from './ui';
+import { EventEmitter } from 'events';
+import { AppState, activeQuestion, analyzeAndPrint, cancelAnalysis, clearStickyQuestion, copySolution, followUpAndPrint, rememberQuestion } from './main';
+import { CancelledError } from './errors';
+import { printStatus, separator } from './ui';
+import { promptQuestion } from './question_prompt';
+import { CAPTURE_STATUS, CaptureSource } from './screenshot';
 
-export class TerminalMonitor {
+/**
+ * Raw mode turns Ctrl+C into input rather than a signal, so pass it on to
+ * the daemon's graceful shutdown, or just exit when nothing handles it.
+ */
+function requestExit(): void {
+    if (!process.emit('SIGINT')) {
+        console.log('\n👋 Goodbye!');
+        process.exit();
+    }
+}
+
+export class TerminalMonitor extends EventEmitter {
     private rl: readline.Interface | null = null;
     private isProcessing: boolean = false;
     private lastCommand: string = '';
+    private keyHandler: ((key: string) => void) | null = null;
 
     constructor() {
+        super();
         // Enable raw mode for single keypress detection if needed
         if (process.stdin.isTTY) {
             process.stdin.setRawMode(true);
@@ -34,7 +51,13 @@ export class TerminalMonitor {
         console.log('  [s]      → Solve coding problem');
         console.log('  [e]      → Explain what\'s on screen');
         console.log('  [q]      → Ask custom question');
+        console.log('  [v]      → Analyze the image on the clipboard');
+        console.log('  [r]      → Select a region to capture & analyze');
+        console.log('  [f]      → Follow-up question about the last capture');
+        console.log('  [t]      → Toggle sticky question');
+        console.log('  [x]      → Clear sticky question');
         console.log('  [h]      → Show this help');
+        console.log('  [Esc]    → Cancel the analysis in progress');
         console.log('  [Ctrl+C] → Exit\n');
         console.log('Ready! Press Space or Enter to capture...\n');
 
@@ -42,15 +65,22 @@ export class TerminalMonitor {
         process.stdin.setEncoding('utf8');
         process.stdin.resume();
 
-        process.stdin.on('data', async (key: string) => {
+        this.keyHandler = async (key: string) => {
             // Handle Ctrl+C
             if (key === '\u0003') {
-                console.log('\n👋 Goodbye!');
-                process.exit();
+                requestExit();
+                return;
+            }
+
+            // Esc aborts the request without quitting the daemon
+            if (key === '\u001b') {
+                cancelAnalysis(state);
+                return;
             }
 
             // Ignore input while processing
             if (this.isProcessing) {
+                console.log('⏳ Still processing previous capture... (Esc to cancel)');
                 return;
             }
 
@@ -64,7 +94,7 @@ export class TerminalMonitor {
                 
                 case 's':
                 case 'S':
-                    await this.triggerCapture(state, 'Analyze this coding problem and provide a complete solution.');
+                    await this.triggerCapture(state, 'Analyze this coding problem and provide a complete solution.', 'screen', true);
                     break;
                 
                 case 'e':
@@ -77,6 +107,31 @@ export class TerminalMonitor {
                     await this.askQuestion(state);
                     break;
                 
+                case 'v':
+                case 'V':
+                    await this.triggerCapture(state, undefined, 'clipboard');
+                    break;
+                
+                case 'r':
+                case 'R':
+                    await this.triggerCapture(state, undefined, 'region');
+                    break;
+                
+                case 'f':
+                case 'F':
+                    await this.askFollowUp(state);
+                    break;
+                
+                case 't':
+                case 'T':
+                    this.toggleSticky(state);
+                    break;
+                
+                case 'x':
+                case 'X':
+                    clearStickyQuestion(state);
+                    break;
+                
                 case 'h':
                 case 'H':
                     this.showHelp();
@@ -92,7 +147,8 @@ export class TerminalMonitor {
                     // Ignore other keys
                     break;
             }
-        });
+        };
+        process.stdin.on('data', this.keyHandler);
     }
 
     /**
@@ -110,7 +166,10 @@ export class TerminalMonitor {
         console.log('  solve / s       → Solve coding problem');
         console.log('  explain / e     → Explain screen content');
         console.log('  ask <question>  → Ask specific question');
+        console.log('  follow <q>      → Follow-up about the last capture');
         console.log('  repeat / r      → Repeat last capture');
+        console.log('  sticky          → Toggle sticky question');
+        console.log('  unstick         → Clear sticky question');
         console.log('  clear           → Clear screen');
         console.log('  help / h        → Show this help');
         console.log('  exit / quit     → Exit\n');
@@ -131,12 +190,19 @@ export class TerminalMonitor {
             if (!command || command === 'capture' || command === 'c') {
                 await this.triggerCapture(state);
             } else if (command === 'solve' || command === 's') {
-                await this.triggerCapture(state, 'Analyze this coding problem and provide a complete solution.');
+                await this.triggerCapture(state, 'Analyze this coding problem and provide a complete solution.', 'screen', true);
             } else if (command === 'explain' || command === 'e') {
                 await this.triggerCapture(state, 'Explain what you see in this image clearly and concisely.');
             } else if (command.startsWith('ask ')) {
-                const question = input.substring(4).trim();
+                const question = input.trim().substring(4).trim();
+                rememberQuestion(state, question);
                 await this.triggerCapture(state, question);
+            } else if (command.startsWith('follow ')) {
+                await this.followUp(state, input.trim().substring(7).trim());
+            } else if (command === 'sticky') {
+                this.toggleSticky(state);
+            } else if (command === 'unstick') {
+                clearStickyQuestion(state);
             } else if (command === 'repeat' || command === 'r') {
                 if (this.lastCommand) {
                     await this.triggerCapture(state, this.lastCommand);
@@ -149,8 +215,8 @@ export class TerminalMonitor {
             } else if (command === 'help' || command === 'h') {
                 this.showHelp();
             } else if (command === 'exit' || command === 'quit') {
-                console.log('👋 Goodbye!');
-                process.exit();
+                requestExit();
+                return;
             } else {
                 console.log(`❓ Unknown command: ${command}`);
             }
@@ -158,39 +224,90 @@ export class TerminalMonitor {
             this.rl!.prompt();
         });
 
-        this.rl.on('close', () => {
-            console.log('\n👋 Goodbye!');
-            process.exit();
-        });
+        this.rl.on('close', requestExit);
+        this.rl.on('SIGINT', requestExit);
     }
 
     /**
      * Mode 3: Interactive question mode
      */
     private async askQuestion(state: AppState): Promise<void> {
+        const question = await this.readQuestion('❓ What would you like to know? ');
+
+        if (question) {
+            rememberQuestion(state, question);
+            await this.triggerCapture(state, question);
+        }
+    }
+
+    private async askFollowUp(state: AppState): Promise<void> {
+        if (!state.aiClient.hasConversation()) {
+            console.log('💬 Nothing to follow up on yet - capture a screenshot first');
+            return;
+        }
+
+        const question = await this.readQuestion('💬 Follow-up: ');
+        if (question) {
+            await this.followUp(state, question);
+        }
+    }
+
+    /**
+     * Ask about the last capture again, reusing its screenshot and answers
+     * as context instead of taking a new one.
+     */
+    private async followUp(state: AppState, question: string): Promise<void> {
+        if (this.isProcessing || !question) {
+            return;
+        }
+
+        this.isProcessing = true;
+        try {
+            await state.triggers.run('follow-up', async () => {
+                printStatus('🤖 Asking follow-up...');
+                await followUpAndPrint(state, question);
+            });
+        } catch (error) {
+            if (!(error instanceof CancelledError)) {
+                console.error('❌ Follow-up failed:', error);
+            }
+        } finally {
+            this.isProcessing = false;
+        }
+    }
+
+    private async readQuestion(prompt: string): Promise<string> {
         // Temporarily switch to line input mode
         process.stdin.setRawMode(false);
-        
-        const rl = readline.createInterface({
-            input: process.stdin,
-            output: process.stdout
-        });
+        if (this.keyHandler) {
+            process.stdin.removeListener('data', this.keyHandler);
+        }
 
-        rl.question('❓ What would you like to know? ', async (question: string) => {
-            rl.close();
-            
+        try {
+            return await promptQuestion(prompt);
+        } finally {
             // Switch back to raw mode
             if (process.stdin.isTTY) {
                 process.stdin.setRawMode(true);
             }
-            
-            if (question.trim()) {
-                await this.triggerCapture(state, question.trim());
+            if (this.keyHandler) {
+                process.stdin.on('data', this.keyHandler);
             }
-        });
+            process.stdin.resume();
+        }
+    }
+
+    private toggleSticky(state: AppState): void {
+        state.stickyQuestions = !state.stickyQuestions;
+        if (!state.stickyQuestions) {
+            state.stickyQuestion = undefined;
+        }
+        console.log(state.stickyQuestions
+            ? '📌 Sticky questions on - your next question will be reused for later captures'
+            : '📌 Sticky questions off');
     }
 
-    private async triggerCapture(state: AppState, customPrompt?: string): Promise<void> {
+    private async triggerCapture(state: AppState, customPrompt?: string, source: CaptureSource = 'screen', copyCode: boolean = false): Promise<void> {
         if (this.isProcessing) {
             return;
         }
@@ -198,22 +315,30 @@ export class TerminalMonitor {
         this.isProcessing = true;
         this.lastCommand = customPrompt || '';
 
-        console.log('\n' + '─'.repeat(50));
-        printStatus('📸 Capturing screenshot...');
-
         try {
-            const screenshotData = await state.screenshotCapture.capture();
-            
-            printStatus('🤖 Analyzing with AI...');
-            
-            const question = customPrompt || state.customQuestion || state.customPrompt;
-            const analysis = await state.aiClient.analyzeImage(screenshotData, question);
-            
-            printAnalysisResult(analysis);
-            console.log('─'.repeat(50) + '\n');
-            console.log('✅ Ready for next capture (press Space/Enter)\n');
+            const ran = await state.triggers.run(`${source} capture`, async () => {
+                console.log('\n' + separator());
+                printStatus(CAPTURE_STATUS[source]);
+                const screenshotData = await state.screenshotCapture.captureFrom(source);
+                
+                printStatus('🤖 Analyzing with AI...');
+                
+                const question = customPrompt || activeQuestion(state);
+                const analysis = await analyzeAndPrint(state, [screenshotData], question);
+                if (copyCode) {
+                    await copySolution(state, analysis);
+                }
+                console.log(separator() + '\n');
+                console.log('✅ Ready for next capture (press Space/Enter)\n');
+            });
+            if (ran) {
+                this.emit('captureComplete');
+            }
         } catch (error) {
-            console.error('❌ Capture failed:', error);
+            if (!(error instanceof CancelledError)) {
+                console.error('❌ Capture failed:', error);
+            }
+            this.emit('captureComplete', error);
         } finally {
             this.isProcessing = false;
         }
@@ -222,13 +347,21 @@ export class TerminalMonitor {
     private showHelp(): void {
         console.log('\n📌 Quick Controls:');
         console.log('  [Space/Enter] → Capture');
-        console.log('  [s] → Solve  [e] → Explain  [q] → Question');
-        console.log('  [h] → Help   [c] → Clear    [Ctrl+C] → Exit\n');
+        console.log('  [s] → Solve  [e] → Explain  [q] → Question  [f] → Follow-up  [v] → Clipboard image  [r] → Region');
+        console.log('  [t] → Toggle sticky question  [x] → Clear sticky');
+        console.log('  [h] → Help   [c] → Clear    [Esc] → Cancel  [Ctrl+C] → Exit\n');
     }
 
     stopMonitoring(): void {
+        if (this.keyHandler) {
+            process.stdin.removeListener('data', this.keyHandler);
+            this.keyHandler = null;
+        }
         if (this.rl) {
+            // Closing is not an exit request when the monitor is being stopped
+            this.rl.removeAllListeners('close');
             this.rl.close();
+            this.rl = null;
         }
         if (process.stdin.isTTY) {
             process.stdin.setRawMode(false);
@@ -245,10 +378,11 @@ export class TimerMonitor {
     private interval: NodeJS.Timeout | null = null;
     private countdown: number = 0;
     private isProcessing: boolean = false;
+    private keyHandler: ((key: string) => void) | null = null;
 
     async startMonitoring(state: AppState, intervalSeconds: number = 5): Promise<void> {
         console.log(`⏱️  Auto-capture mode: Every ${intervalSeconds} seconds`);
-        console.log('Press [p] to pause/resume, [n] for next capture now, [Ctrl+C] to exit\n');
+        console.log('Press [p] to pause/resume, [n] for next capture now, [Esc] to cancel an analysis, [Ctrl+C] to exit\n');
 
         // Set up keypress handling for pause/resume
         if (process.stdin.isTTY) {
@@ -257,34 +391,39 @@ export class TimerMonitor {
         process.stdin.setEncoding('utf8');
         process.stdin.resume();
 
-        let isPaused = false;
-
-        process.stdin.on('data', async (key: string) => {
+        this.keyHandler = async (key: string) => {
             if (key === '\u0003') { // Ctrl+C
-                this.stopMonitoring();
-                process.exit();
+                requestExit();
+            } else if (key === '\u001b') {
+                cancelAnalysis(state);
             } else if (key === 'p' || key === 'P') {
-                isPaused = !isPaused;
-                console.log(isPaused ? '⏸️  Paused' : '▶️  Resumed');
+                // Shared with the tray menu's Pause
+                state.paused = !state.paused;
+                console.log(state.paused ? '⏸️  Paused' : '▶️  Resumed');
             } else if (key === 'n' || key === 'N') {
                 if (!this.isProcessing) {
                     await this.capture(state);
                 }
             }
-        });
+        };
+        process.stdin.on('data', this.keyHandler);
 
         // Start countdown timer
         this.countdown = intervalSeconds;
         
         this.interval = setInterval(async () => {
-            if (isPaused || this.isProcessing) {
+            if (state.paused || this.isProcessing) {
                 return;
             }
 
             this.countdown--;
             
-            // Update countdown display
-            process.stdout.write(`\r⏱️  Next capture in: ${this.countdown}s  `);
+            // Update countdown display, off stdout when that carries JSON answers,
+            // and only on a terminal: in a log it would be a line per second
+            const display = state.output === 'json' ? process.stderr : process.stdout;
+            if (display.isTTY) {
+                display.write(`\r⏱️  Next capture in: ${this.countdown}s  `);
+            }
             
             if (this.countdown <= 0) {
                 await this.capture(state);
@@ -300,11 +439,14 @@ export class TimerMonitor {
         console.log('\n📸 Auto-capturing...');
         
         try {
-            const screenshotData = await state.screenshotCapture.capture();
-            const analysis = await state.aiClient.analyzeImage(screenshotData);
-            printAnalysisResult(analysis);
+            await state.triggers.run('timer capture', async () => {
+                const screenshotData = await state.screenshotCapture.captureFrom('screen');
+                await analyzeAndPrint(state, [screenshotData]);
+            });
         } catch (error) {
-            console.error('❌ Auto-capture failed:', error);
+            if (!(error instanceof CancelledError)) {
+                console.error('❌ Auto-capture failed:', error);
+            }
         } finally {
             this.isProcessing = false;
         }
@@ -315,6 +457,10 @@ export class TimerMonitor {
             clearInterval(this.interval);
             this.interval = null;
         }
+        if (this.keyHandler) {
+            process.stdin.removeListener('data', this.keyHandler);
+            this.keyHandler = null;
+        }
         if (process.stdin.isTTY) {
             process.stdin.setRawMode(false);
         }
@@ -368,8 +514,7 @@ export class ClipboardMonitor {
         
         try {
             const screenshotData = await state.screenshotCapture.capture();
-            const analysis = await state.aiClient.analyzeImage(screenshotData, question);
-            printAnalysisResult(analysis);
+            await analyzeAndPrint(state, [screenshotData], question);
         } catch (error) {
             console.error('❌ Capture failed:', error);
         }
@@ -0,0 +1,112 @@
+import { execFile } from 'child_process';
+import { promisify } from 'util';
+
+const execFileAsync = promisify(execFile);
+
+// Long enough for the window manager to repaint without the terminal
+const HIDE_SETTLE_MS = 250;
+
+const windowsShowConsole = (command: number) => `
+Add-Type @'
+using System;
+using System.Runtime.InteropServices;
+public static class Console32 {
+    [DllImport("kernel32.dll")] public static extern IntPtr GetConsoleWindow();
+    [DllImport("user32.dll")] public static extern bool ShowWindow(IntPtr hWnd, int nCmdShow);
+}
+'@
+[void][Console32]::ShowWindow([Console32]::GetConsoleWindow(), ${command})
+`;
+
+// ShowWindow commands; restoring without activating leaves focus where it was
+const SW_MINIMIZE = 6;
+const SW_SHOWNOACTIVATE = 4;
+
+/**
+ * Hides the terminal window the analyzer runs in while the screen is
+ * captured, so the model isn't shown our own output. Finding that window
+ * is best effort: the terminal app on macOS (from __CFBundleIdentifier),
+ * $WINDOWID via xdotool on X11, and the console window on Windows.
+ */
+export class TerminalWindow {
+    private warned = false;
+
+    /**
+     * Run `capture` with the terminal hidden, restoring it afterwards even
+     * if the capture fails.
+     */
+    async hiddenDuring<T>(capture: () => Promise<T>): Promise<T> {
+        const hidden = await this.setVisible(false);
+        if (hidden) {
+            await new Promise(resolve => setTimeout(resolve, HIDE_SETTLE_MS));
+        }
+        try {
+            return await capture();
+        } finally {
+            if (hidden) {
+                await this.setVisible(true);
+            }
+        }
+    }
+
+    private async setVisible(visible: boolean): Promise<boolean> {
+        try {
+            switch (process.platform) {
+                case 'darwin': {
+                    const bundleId = process.env.__CFBundleIdentifier;
+                    if (!bundleId) {
+                        return this.unsupported('terminal app not known (__CFBundleIdentifier is not set)');
+                    }
+                    await execFileAsync('osascript', ['-e',
+                        `tell application "System Events" to set visible of (first process whose bundle identifier is ${JSON.stringify(bundleId)}) to ${visible}`
+                    ]);
+                    return true;
+                }
+                case 'win32':
+                    await execFileAsync('powershell', ['-NoProfile', '-Command', windowsShowConsole(visible ? SW_SHOWNOACTIVATE : SW_MINIMIZE)]);
+                    return true;
+                default: {
+                    const windowId = process.env.WINDOWID;
+                    if (!windowId) {
+                        return this.unsupported('terminal window not known ($WINDOWID is not set)');
+                    }
+                    await execFileAsync('xdotool', visible ? ['windowmap', windowId] : ['windowunmap', windowId]);
+                    return true;
+                }
+            }
+        } catch (error) {
+            return this.unsupported(String(error));
+        }
+    }
+
+    /**
+     * Whether the terminal is the focused window, so keystrokes meant for
+     * another app would land here. Undefined when that can't be told
+     * (Windows, or the terminal isn't known).
+     */
+    async isFocused(): Promise<boolean | undefined> {
+        try {
+            if (process.platform === 'darwin' && process.env.__CFBundleIdentifier) {
+                const { stdout } = await execFileAsync('osascript', ['-e',
+                    'tell application "System Events" to get bundle identifier of first process whose frontmost is true'
+                ]);
+                return stdout.trim() === process.env.__CFBundleIdentifier;
+            }
+            if (process.platform === 'linux' && process.env.WINDOWID) {
+                const { stdout } = await execFileAsync('xdotool', ['getactivewindow']);
+                return stdout.trim() === process.env.WINDOWID;
+            }
+        } catch (error) {
+            // Unknown, as on other platforms
+        }
+        return undefined;
+    }
+
+    private unsupported(reason: string): false {
+        if (!this.warned) {
+            console.warn(`⚠️  Can't hide the terminal during captures: ${reason}`);
+            this.warned = true;
+        }
+        return false;
+    }
+}
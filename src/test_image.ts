@@ -0,0 +1,18 @@
+import sharp from 'sharp';
+
+/**
+ * Word rendered into the connectivity test image. The `test` command
+ * asks the model to read it back, which proves the image actually
+ * reached a vision-capable model.
+ */
+export const TEST_IMAGE_WORD = 'SNAPPER';
+
+const TEST_IMAGE_SVG = `<svg xmlns="http://www.w3.org/2000/svg" width="400" height="160">
+    <rect width="100%" height="100%" fill="#ffffff"/>
+    <text x="50%" y="50%" font-family="Arial, Helvetica, sans-serif" font-size="64" font-weight="bold"
+          fill="#000000" text-anchor="middle" dominant-baseline="middle">${TEST_IMAGE_WORD}</text>
+</svg>`;
+
+export async function renderTestImage(): Promise<Buffer> {
+    return sharp(Buffer.from(TEST_IMAGE_SVG)).png().toBuffer();
+}
@@ -0,0 +1,86 @@
+import { ConfigError } from './errors';
+
+export const THEME_PRESETS = ['dark', 'light', 'solarized'] as const;
+export type ThemePreset = typeof THEME_PRESETS[number];
+
+/** What each color is used for, as the keys of `theme_colors` */
+export const THEME_ROLES = [
+    'header', 'status', 'success', 'error', 'muted', 'text', 'accent', 'inline_code', 'code', 'code_background'
+] as const;
+export type ThemeRole = typeof THEME_ROLES[number];
+
+/** A color name, e.g. `cyan` or `redBright`, a `#rrggbb` hex code, or `default` for the terminal's own */
+export type Theme = Record<ThemeRole, string>;
+
+const BASE_COLORS = ['black', 'red', 'green', 'yellow', 'blue', 'magenta', 'cyan', 'white'];
+const COLOR_NAMES = [...BASE_COLORS, ...BASE_COLORS.map(color => `${color}Bright`), 'gray', 'grey', 'default'];
+
+export const THEMES: Record<ThemePreset, Theme> = {
+    dark: {
+        header: 'cyan',
+        status: 'yellow',
+        success: 'green',
+        error: 'red',
+        muted: 'gray',
+        text: 'white',
+        accent: 'blue',
+        inline_code: 'yellow',
+        code: 'white',
+        code_background: 'black'
+    },
+    // Nothing white or yellow, which vanish on a light background
+    light: {
+        header: 'blue',
+        status: 'magenta',
+        success: 'green',
+        error: 'red',
+        muted: 'gray',
+        text: 'default',
+        accent: 'blue',
+        inline_code: 'magenta',
+        code: 'black',
+        code_background: '#eeeeee'
+    },
+    // Ethan Schoonover's palette, readable on its dark and light backgrounds alike
+    solarized: {
+        header: '#2aa198',
+        status: '#b58900',
+        success: '#859900',
+        error: '#dc322f',
+        muted: '#93a1a1',
+        text: '#839496',
+        accent: '#268bd2',
+        inline_code: '#cb4b16',
+        code: '#93a1a1',
+        code_background: '#073642'
+    }
+};
+
+export function isThemePreset(value: string): value is ThemePreset {
+    return (THEME_PRESETS as readonly string[]).includes(value);
+}
+
+export function isThemeColor(value: string): boolean {
+    return COLOR_NAMES.includes(value) || /^#([0-9a-f]{3}|[0-9a-f]{6})$/i.test(value);
+}
+
+/**
+ * The preset named by `theme` (dark by default) with `theme_colors`
+ * overriding single roles.
+ */
+export function resolveTheme(preset: string = 'dark', overrides: Record<string, string> = {}): Theme {
+    if (!isThemePreset(preset)) {
+        throw new ConfigError(`Unknown theme "${preset}" - use ${THEME_PRESETS.join(', ')}`);
+    }
+    const theme = { ...THEMES[preset] };
+    for (const [role, color] of Object.entries(overrides)) {
+        if (!(THEME_ROLES as readonly string[]).includes(role)) {
+            throw new ConfigError(`Unknown theme color "${role}" - use ${THEME_ROLES.join(', ')}`);
+        }
+        if (typeof color !== 'string' || !isThemeColor(color)) {
+            throw new ConfigError(`Invalid color for ${role}: "${color}" - use a name like cyan or redBright, a #rrggbb code or default`);
+        }
+        theme[role as ThemeRole] = color;
+    }
+    return theme;
+}
@@ -0,0 +1,83 @@
+import SysTray, { ClickEvent, MenuItem } from 'systray2';
+import { TrayStatus, renderTrayIcon } from './tray_icon';
+
+export interface TrayActions {
+    capture(): void;
+    togglePause(): void;
+    openLastResult(): void;
+    clearSticky(): void;
+    openSettings(): void;
+    quit(): void;
+}
+
+const STATUSES: TrayStatus[] = ['idle', 'analyzing', 'paused'];
+
+const STATUS_TEXT: Record<TrayStatus, string> = {
+    idle: 'Ready',
+    analyzing: 'Analyzing...',
+    paused: 'Paused'
+};
+
+interface TrayMenu {
+    icon: string;
+    title: string;
+    tooltip: string;
+    items: MenuItem[];
+}
+
+/**
+ * A tray icon (a menu bar icon on macOS) for the daemon, so it can run
+ * without a terminal window: the icon shows whether it's idle, analyzing
+ * or paused, and its menu triggers captures and opens the settings.
+ */
+export class TrayIcon {
+    private constructor(
+        private systray: SysTray,
+        private menu: TrayMenu,
+        private icons: Record<TrayStatus, string>,
+        private statusItem: MenuItem,
+        private pauseItem: MenuItem
+    ) {}
+
+    static async start(actions: TrayActions): Promise<TrayIcon> {
+        const icons = Object.fromEntries(
+            await Promise.all(STATUSES.map(async status => [status, await renderTrayIcon(status)]))
+        ) as Record<TrayStatus, string>;
+
+        const statusItem: MenuItem = { title: STATUS_TEXT.idle, tooltip: 'Status', enabled: false };
+        const pauseItem: MenuItem = { title: 'Pause', tooltip: 'Ignore hotkeys and timer captures', enabled: true };
+        const menu: TrayMenu = {
+            icon: icons.idle,
+            title: '',
+            tooltip: `AI Screenshot Analyzer - ${STATUS_TEXT.idle}`,
+            items: [
+                statusItem,
+                { title: 'Capture now', tooltip: 'Capture the screen and analyze it', enabled: true },
+                pauseItem,
+                { title: 'Open last result', tooltip: 'Open the last answer', enabled: true },
+                { title: 'Clear sticky question', tooltip: 'Go back to the default question', enabled: true },
+                { title: 'Settings...', tooltip: 'Edit the settings in your browser', enabled: true },
+                { title: 'Quit', tooltip: 'Stop the analyzer', enabled: true }
+            ]
+        };
+        // Click events identify items by their position in the menu
+        const handlers = [undefined, actions.capture, actions.togglePause, actions.openLastResult, actions.clearSticky, actions.openSettings, actions.quit];
+
+        const systray = new SysTray({ menu });
+        await systray.onClick((event: ClickEvent) => handlers[event.seq_id]?.());
+        await systray.ready();
+        return new TrayIcon(systray, menu, icons, statusItem, pauseItem);
+    }
+
+    setStatus(status: TrayStatus): void {
+        this.menu.icon = this.icons[status];
+        this.menu.tooltip = `AI Screenshot Analyzer - ${STATUS_TEXT[status]}`;
+        this.statusItem.title = STATUS_TEXT[status];
+        this.pauseItem.title = status === 'paused' ? 'Resume' : 'Pause';
+        this.systray.sendAction({ type: 'update-menu', menu: this.menu });
+    }
+
+    stop(): void {
+        this.systray.kill(false);
+    }
+}
@@ -0,0 +1,47 @@
+import sharp from 'sharp';
+
+export type TrayStatus = 'idle' | 'analyzing' | 'paused';
+
+// Menu bars and notification areas show icons at 16-22px; render at 2x
+const ICON_SIZE = 32;
+
+const STATUS_COLORS: Record<TrayStatus, string> = {
+    idle: '#9e9e9e',
+    analyzing: '#ff9800',
+    paused: '#616161'
+};
+
+/**
+ * A camera-lens glyph in the status color, as the tray library wants it:
+ * base64 ICO on Windows, base64 PNG elsewhere.
+ */
+export async function renderTrayIcon(status: TrayStatus, platform: string = process.platform): Promise<string> {
+    const color = STATUS_COLORS[status];
+    const center = status === 'paused'
+        ? `<rect x="11" y="10" width="3.5" height="12" fill="${color}"/><rect x="17.5" y="10" width="3.5" height="12" fill="${color}"/>`
+        : `<circle cx="16" cy="16" r="6" fill="${color}"/>`;
+    const svg = `<svg xmlns="http://www.w3.org/2000/svg" width="${ICON_SIZE}" height="${ICON_SIZE}">
+        <circle cx="16" cy="16" r="13" fill="none" stroke="${color}" stroke-width="3"/>${center}</svg>`;
+    const png = await sharp(Buffer.from(svg)).png().toBuffer();
+    return (platform === 'win32' ? pngToIco(png, ICON_SIZE) : png).toString('base64');
+}
+
+/**
+ * Wrap a PNG in an ICO container, which may hold PNG data directly
+ * (Windows Vista and later).
+ */
+export function pngToIco(png: Buffer, size: number): Buffer {
+    const header = Buffer.alloc(6 + 16);
+    header.writeUInt16LE(0, 0); // reserved
+    header.writeUInt16LE(1, 2); // type: icon
+    header.writeUInt16LE(1, 4); // image count
+    header.writeUInt8(size >= 256 ? 0 : size, 6); // width, 0 meaning 256
+    header.writeUInt8(size >= 256 ? 0 : size, 7); // height
+    header.writeUInt8(0, 8); // palette colors
+    header.writeUInt8(0, 9); // reserved
+    header.writeUInt16LE(1, 10); // color planes
+    header.writeUInt16LE(32, 12); // bits per pixel
+    header.writeUInt32LE(png.length, 14); // image size
+    header.writeUInt32LE(header.length, 18); // image offset
+    return Buffer.concat([header, png]);
+}
@@ -0,0 +1,71 @@
+interface PendingTrigger {
+    label: string;
+    task: () => Promise<void>;
+    done: (ran: boolean) => void;
+    fail: (error: unknown) => void;
+}
+
+/**
+ * Runs the daemon's triggers (hotkeys, timer, tray, `ask`) one at a time,
+ * so rapid presses don't interleave output or pay for parallel analyses.
+ * A trigger arriving mid-analysis waits for it; only the newest waiting
+ * trigger is kept, since it reflects the latest screen.
+ */
+export class TriggerQueue {
+    private running: boolean = false;
+    private pending: PendingTrigger | null = null;
+
+    isBusy(): boolean {
+        return this.running;
+    }
+
+    /**
+     * Run `task` now, or after the trigger in progress. Resolves false if a
+     * newer trigger replaced it before it could run.
+     */
+    run(label: string, task: () => Promise<void>): Promise<boolean> {
+        return new Promise((done, fail) => {
+            const trigger = { label, task, done, fail };
+            if (!this.running) {
+                this.start(trigger);
+                return;
+            }
+            if (this.pending) {
+                console.log(`⏭️  Skipping ${this.pending.label} - ${label} replaces it`);
+                this.pending.done(false);
+            } else {
+                console.log(`⏳ Analysis already in progress - ${label} will run next`);
+            }
+            this.pending = trigger;
+        });
+    }
+
+    /** Drop the waiting trigger, e.g. when shutting down */
+    clear(): void {
+        this.pending?.done(false);
+        this.pending = null;
+    }
+
+    private async start(trigger: PendingTrigger): Promise<void> {
+        this.running = true;
+        let failure: { error: unknown } | undefined;
+        try {
+            await trigger.task();
+        } catch (error) {
+            failure = { error };
+        }
+
+        // Hand over before settling, so the caller never sees a stale busy state
+        this.running = false;
+        const next = this.pending;
+        this.pending = null;
+        if (next) {
+            this.start(next);
+        }
+        if (failure) {
+            trigger.fail(failure.error);
+        } else {
+            trigger.done(true);
+        }
+    }
+}
@@ -0,0 +1,253 @@
+import * as readline from 'readline';
+import { format, stripVTControlCharacters } from 'util';
+import { AppState, activeQuestion, analyzeAndPrint, cancelAnalysis } from './main';
+import { CancelledError, ConfigError } from './errors';
+import { redactSecrets } from './redact';
+import { CaptureSource } from './screenshot';
+import { TuiEntry, TuiStyle, TuiView, maxScroll, renderTui } from './tui_view';
+import { themed, themedCode } from './ui';
+
+const SOURCES: CaptureSource[] = ['screen', 'active-window', 'clipboard', 'region'];
+
+/** The TUI's styles in the current theme; bars use the code background as their text color */
+function tuiStyles(): Record<TuiStyle, (text: string) => string> {
+    const onBar = themed('code_background');
+    return {
+        title: text => themed('header', true)(onBar(text)),
+        border: themed('accent'),
+        text: text => text,
+        code: themedCode,
+        fence: themed('inline_code'),
+        question: themed('header'),
+        selected: text => themed('status', true)(onBar(text)),
+        muted: themed('muted'),
+        error: themed('error'),
+        input: themed('success')
+    };
+}
+
+// The alternate screen leaves the shell's scrollback as it was
+const ENTER_SCREEN = '\x1B[?1049h';
+const LEAVE_SCREEN = '\x1B[?25h\x1B[?1049l';
+
+// Tokens arrive faster than it's worth redrawing
+const DRAW_INTERVAL_MS = 30;
+
+type Keypress = { name?: string; ctrl?: boolean; meta?: boolean; shift?: boolean };
+
+/**
+ * Full-screen terminal UI: the capture history beside a scrollable answer
+ * pane that fills in as tokens arrive, above a prompt line. Captures go
+ * through the same trigger queue as every other mode.
+ */
+export class Tui {
+    private view: TuiView = { entries: [], selected: -1, scroll: 0, input: '', source: 'screen', status: 'Ready' };
+    private current?: TuiEntry;
+    /** Keep the answer pane scrolled to the end while it streams */
+    private follow: boolean = true;
+    private drawTimer?: NodeJS.Timeout;
+    /** False once the TUI is closed, while a cancelled capture may still be settling */
+    private active: boolean = false;
+    private consoleMethods = { log: console.log, info: console.info, warn: console.warn, error: console.error, clear: console.clear };
+    private finish?: () => void;
+    private styles = tuiStyles();
+
+    async run(state: AppState): Promise<void> {
+        if (!process.stdin.isTTY || !process.stdout.isTTY) {
+            throw new ConfigError('The TUI needs an interactive terminal');
+        }
+
+        const onToken = (text: string) => {
+            if (this.current) {
+                this.current.answer += text;
+                this.scheduleDraw();
+            }
+        };
+        const onKeypress = (text: string | undefined, key: Keypress | undefined) => {
+            this.handleKey(state, text, key || {});
+        };
+        const onResize = () => this.draw();
+        const onExit = () => process.stdout.write(LEAVE_SCREEN);
+
+        // The answer pane shows answers, and logs would scribble over the
+        // screen: the latest one becomes the status line instead
+        state.printAnswers = false;
+        const toStatus = (...args: unknown[]) => this.setStatus(format(...args));
+        console.log = console.info = console.warn = console.error = toStatus;
+        console.clear = () => {};
+        state.events.on('token', onToken);
+
+        readline.emitKeypressEvents(process.stdin);
+        process.stdin.setRawMode(true);
+        process.stdin.resume();
+        process.stdin.on('keypress', onKeypress);
+        process.stdout.on('resize', onResize);
+        process.on('exit', onExit);
+        process.stdout.write(ENTER_SCREEN);
+        this.active = true;
+        this.draw();
+
+        await new Promise<void>(resolve => {
+            this.finish = resolve;
+        });
+
+        this.active = false;
+        clearTimeout(this.drawTimer);
+        process.stdin.off('keypress', onKeypress);
+        process.stdin.setRawMode(false);
+        process.stdin.pause();
+        process.stdout.off('resize', onResize);
+        process.off('exit', onExit);
+        process.stdout.write(LEAVE_SCREEN);
+        state.events.off('token', onToken);
+        Object.assign(console, this.consoleMethods);
+        state.printAnswers = true;
+    }
+
+    private handleKey(state: AppState, text: string | undefined, key: Keypress): void {
+        const page = Math.max(1, (process.stdout.rows || 24) - 4);
+        if (key.ctrl && key.name === 'c') {
+            cancelAnalysis(state);
+            state.triggers.clear();
+            this.finish?.();
+            return;
+        }
+
+        switch (key.name) {
+            case 'escape':
+                this.setStatus(cancelAnalysis(state) ? 'Cancelling…' : 'Nothing to cancel');
+                return;
+            case 'return':
+            case 'enter':
+                void this.capture(state);
+                return;
+            case 'tab':
+                this.view.source = SOURCES[(SOURCES.indexOf(this.view.source) + (key.shift ? SOURCES.length - 1 : 1)) % SOURCES.length];
+                break;
+            case 'up':
+                this.select(Math.min(this.view.entries.length - 1, this.view.selected + 1));
+                break;
+            case 'down':
+                this.select(Math.max(0, this.view.selected - 1));
+                break;
+            case 'pageup':
+                this.scrollTo(this.view.scroll - page);
+                break;
+            case 'pagedown':
+                this.scrollTo(this.view.scroll + page);
+                break;
+            case 'home':
+                this.scrollTo(0);
+                break;
+            case 'end':
+                this.scrollTo(Infinity);
+                break;
+            case 'backspace':
+                this.view.input = this.view.input.slice(0, -1);
+                break;
+            default:
+                if (key.ctrl && key.name === 'u') {
+                    this.view.input = '';
+                } else if (text && !key.ctrl && !key.meta && text >= ' ') {
+                    this.view.input += text;
+                }
+        }
+        this.draw();
+    }
+
+    private async capture(state: AppState): Promise<void> {
+        const typed = this.view.input.trim();
+        const question = typed || activeQuestion(state);
+        const entry: TuiEntry = {
+            question: (Array.isArray(question) ? question.join(' / ') : question) || 'What is on screen?',
+            source: this.view.source,
+            at: new Date(),
+            answer: '',
+            status: 'capturing'
+        };
+        this.view.input = '';
+        this.draw();
+
+        try {
+            const ran = await state.triggers.run('TUI capture', async () => {
+                this.current = entry;
+                this.view.entries.push(entry);
+                this.select(this.view.entries.length - 1);
+                this.follow = true;
+
+                const screenshotData = await state.screenshotCapture.captureFrom(entry.source);
+                entry.status = 'analyzing';
+                this.setStatus('Analyzing with AI…');
+                // Structured (--json) answers are reformatted once complete
+                entry.answer = await analyzeAndPrint(state, [screenshotData], question) || entry.answer;
+                entry.status = 'done';
+                this.setStatus('Ready');
+            });
+            if (!ran) {
+                this.setStatus('Skipped - a newer capture was requested');
+            }
+        } catch (error) {
+            const cancelled = error instanceof CancelledError;
+            entry.status = cancelled ? 'cancelled' : 'failed';
+            if (!cancelled) {
+                entry.answer = error instanceof Error ? error.message : String(error);
+            }
+            this.setStatus(cancelled ? 'Cancelled' : `✗ ${entry.answer}`);
+        } finally {
+            if (this.current === entry) {
+                this.current = undefined;
+            }
+        }
+    }
+
+    private select(index: number): void {
+        this.view.selected = index;
+        this.view.scroll = 0;
+        this.follow = false;
+    }
+
+    private scrollTo(line: number): void {
+        const max = this.maxScroll();
+        this.view.scroll = Math.max(0, Math.min(line, max));
+        this.follow = this.view.scroll === max;
+    }
+
+    private maxScroll(): number {
+        return maxScroll(this.view, process.stdout.columns || 80, process.stdout.rows || 24);
+    }
+
+    private setStatus(message: string): void {
+        const lines = stripVTControlCharacters(redactSecrets(message)).split('\n').map(line => line.trim()).filter(line => line.length > 0);
+        if (lines.length > 0) {
+            this.view.status = lines[lines.length - 1];
+            this.scheduleDraw();
+        }
+    }
+
+    private scheduleDraw(): void {
+        if (!this.drawTimer) {
+            this.drawTimer = setTimeout(() => {
+                this.drawTimer = undefined;
+                this.draw();
+            }, DRAW_INTERVAL_MS);
+        }
+    }
+
+    private draw(): void {
+        if (!this.active) {
+            return;
+        }
+        const cols = process.stdout.columns || 80;
+        const rows = process.stdout.rows || 24;
+        if (this.follow && this.current && this.view.entries[this.view.selected] === this.current) {
+            this.view.scroll = this.maxScroll();
+        }
+
+        const screen = renderTui(this.view, cols, rows)
+            .map((row, index) => `\x1B[${index + 1};1H${row.map(segment => this.styles[segment.style](segment.text)).join('')}`)
+            .join('');
+        const cursor = Math.min(cols, `${this.view.source} › ${this.view.input}`.length + 1);
+        // Hide the cursor while drawing, then park it at the end of the prompt
+        process.stdout.write(`\x1B[?25l${screen}\x1B[${rows};${cursor}H\x1B[?25h`);
+    }
+}
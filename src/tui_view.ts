@@ -0,0 +1,167 @@
+import { CaptureSource } from './screenshot';
+
+export type TuiEntryStatus = 'capturing' | 'analyzing' | 'done' | 'failed' | 'cancelled';
+
+/** One capture in the TUI's history pane */
+export interface TuiEntry {
+    question: string;
+    source: CaptureSource;
+    at: Date;
+    /** Grows as tokens arrive; the error message for failed captures */
+    answer: string;
+    status: TuiEntryStatus;
+}
+
+export type TuiStyle = 'title' | 'border' | 'text' | 'code' | 'fence' | 'question' | 'selected' | 'muted' | 'error' | 'input';
+
+/** A run of text on a row, drawn in one style */
+export interface TuiSegment {
+    text: string;
+    style: TuiStyle;
+}
+
+export interface TuiView {
+    entries: TuiEntry[];
+    /** Index into `entries` of the capture shown in the answer pane */
+    selected: number;
+    /** First answer line shown */
+    scroll: number;
+    input: string;
+    source: CaptureSource;
+    /** The latest status message */
+    status: string;
+}
+
+const STATUS_MARKS: Record<TuiEntryStatus, string> = {
+    capturing: '○',
+    analyzing: '…',
+    done: '✓',
+    failed: '✗',
+    cancelled: '–'
+};
+
+export const TUI_KEYS = 'Enter capture · Tab source · ↑↓ history · PgUp/PgDn scroll · Esc cancel · Ctrl+C quit';
+
+/**
+ * Word-wrap `text` to `width` columns. Long words are split, blank lines
+ * and indentation are kept.
+ */
+export function wrapText(text: string, width: number): string[] {
+    width = Math.max(1, width);
+    const lines: string[] = [];
+    for (const line of text.split('\n')) {
+        if (line.length <= width) {
+            lines.push(line);
+            continue;
+        }
+        const indent = line.match(/^\s*/)![0].slice(0, Math.floor(width / 2));
+        let current = '';
+        for (const word of line.trim().split(/\s+/)) {
+            let rest = word;
+            while (rest.length > 0) {
+                const prefix = current ? `${current} ` : indent;
+                if (prefix.length + rest.length <= width) {
+                    current = prefix + rest;
+                    rest = '';
+                } else if (current) {
+                    lines.push(current);
+                    current = '';
+                } else {
+                    const room = width - indent.length;
+                    lines.push(indent + rest.slice(0, room));
+                    rest = rest.slice(room);
+                }
+            }
+        }
+        lines.push(current);
+    }
+    return lines;
+}
+
+/** Fit `text` to exactly `width` columns */
+function fit(text: string, width: number): string {
+    if (width <= 0) {
+        return '';
+    }
+    return text.length > width ? `${text.slice(0, width - 1)}…` : text.padEnd(width);
+}
+
+function paneWidths(cols: number): [number, number] {
+    const history = Math.min(32, Math.max(16, Math.floor(cols * 0.3)));
+    return [history, Math.max(0, cols - history - 1)];
+}
+
+function bodyHeight(rows: number): number {
+    // Title above; status and input lines below
+    return Math.max(0, rows - 3);
+}
+
+/** The answer pane's content for an entry, wrapped to `width` */
+export function answerLines(entry: TuiEntry | undefined, width: number): TuiSegment[] {
+    if (!entry) {
+        return wrapText('Type a question (or nothing for the default prompt) and press Enter to capture the screen.', width)
+            .map(text => ({ text, style: 'muted' as TuiStyle }));
+    }
+
+    const lines: TuiSegment[] = wrapText(`Q: ${entry.question}`, width).map(text => ({ text, style: 'question' as TuiStyle }));
+    lines.push({ text: '', style: 'text' });
+    if (entry.status === 'capturing' || (entry.status === 'analyzing' && !entry.answer)) {
+        lines.push({ text: entry.status === 'capturing' ? 'Capturing…' : 'Waiting for the answer…', style: 'muted' });
+        return lines;
+    }
+
+    let inCode = false;
+    for (const line of entry.answer.split('\n')) {
+        const fence = line.trim().startsWith('```');
+        const style: TuiStyle = entry.status === 'failed' ? 'error' : fence ? 'fence' : inCode ? 'code' : 'text';
+        if (fence) {
+            inCode = !inCode;
+        }
+        for (const text of wrapText(line, width)) {
+            lines.push({ text, style });
+        }
+    }
+    return lines;
+}
+
+/** The furthest the answer pane can scroll */
+export function maxScroll(view: TuiView, cols: number, rows: number): number {
+    const [, answerWidth] = paneWidths(cols);
+    return Math.max(0, answerLines(view.entries[view.selected], answerWidth).length - bodyHeight(rows));
+}
+
+function historyLine(entry: TuiEntry): string {
+    const time = entry.at.toTimeString().slice(0, 5);
+    return `${STATUS_MARKS[entry.status]} ${time} ${entry.question}`;
+}
+
+/**
+ * Lay out the whole screen: a title row, the history pane (newest first)
+ * beside the answer pane, then the status and input lines. Every row is
+ * exactly `cols` wide.
+ */
+export function renderTui(view: TuiView, cols: number, rows: number): TuiSegment[][] {
+    const [historyWidth, answerWidth] = paneWidths(cols);
+    const height = bodyHeight(rows);
+    const screen: TuiSegment[][] = [[{ text: fit(` AI Screenshot Analyzer  ${TUI_KEYS}`, cols), style: 'title' }]];
+
+    // Newest first, scrolled so the selected entry stays in view
+    const order = view.entries.map((entry, index) => index).reverse();
+    const position = order.indexOf(view.selected);
+    const first = Math.max(0, position - height + 1);
+    const history = order.slice(first, first + height);
+
+    const answer = answerLines(view.entries[view.selected], answerWidth).slice(view.scroll, view.scroll + height);
+    for (let row = 0; row < height; row++) {
+        const index = history[row];
+        const left: TuiSegment = index === undefined
+            ? { text: fit('', historyWidth), style: 'text' }
+            : { text: fit(historyLine(view.entries[index]), historyWidth), style: index === view.selected ? 'selected' : 'muted' };
+        const right = answer[row] ?? { text: '', style: 'text' as TuiStyle };
+        screen.push([left, { text: '│', style: 'border' }, { text: fit(right.text, answerWidth), style: right.style }]);
+    }
+
+    screen.push([{ text: fit(view.status, cols), style: view.status.startsWith('✗') ? 'error' : 'muted' }]);
+    screen.push([{ text: fit(`${view.source} › ${view.input}`, cols), style: 'input' }]);
+    return screen.slice(0, rows);
+}
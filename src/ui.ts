@@ -1,67 +1,266 @@
-import chalk from 'chalk';
+import chalk, { BackgroundColorName, ForegroundColorName, supportsColor, supportsColorStderr } from 'chalk';
 import ora from 'ora';
+import { TokenUsage } from './ai_client';
+import { PaletteColor } from './palette';
+import { colorEnabled, writeText } from './output';
+import { MarkdownRenderer, MarkdownStyles } from './markdown';
+import { AnswerProgress, StageTimer } from './progress';
+import { Theme, ThemeRole, resolveTheme } from './theme';
+
+// --quiet / --plain: answers only, without formatting
+let plain = false;
+
+let theme: Theme = resolveTheme();
+
+/**
+ * Color output written to `stream` only if it's a terminal and NO_COLOR
+ * isn't set. Applied to stdout at startup; `--output json` logs to stderr.
+ */
+export function useColorsFor(stream: NodeJS.WriteStream): void {
+    const detected = stream === process.stderr ? supportsColorStderr : supportsColor;
+    chalk.level = colorEnabled(process.env, stream.isTTY) ? (detected ? detected.level : 1) : 0;
+}
+
+useColorsFor(process.stdout);
+
+/** Draw everything from here on in `next`'s colors, from the theme setting */
+export function useTheme(next: Theme): void {
+    theme = next;
+}
+
+/** The current theme's color for `role`, as a text or background color */
+export function themed(role: ThemeRole, background: boolean = false): (text: string) => string {
+    const color = theme[role];
+    if (color === 'default') {
+        return text => text;
+    }
+    if (color.startsWith('#')) {
+        return background ? chalk.bgHex(color) : chalk.hex(color);
+    }
+    return background
+        ? chalk[`bg${color[0].toUpperCase()}${color.slice(1)}` as BackgroundColorName]
+        : chalk[color as ForegroundColorName];
+}
+
+/** Code block content: the code color on the code background */
+export function themedCode(text: string): string {
+    return themed('code_background', true)(themed('code')(text));
+}
+
+/**
+ * Print nothing but the answers, bare: no header, status lines, spinners
+ * or colors. Console output is dropped; errors still reach stderr.
+ */
+export function usePlainOutput(): void {
+    plain = true;
+    chalk.level = 0;
+    console.log = () => {};
+    console.info = () => {};
+    console.clear = () => {};
+}
+
+// Where output isn't a terminal (a log, a pipe), separators keep this width and nothing is wrapped
+const DEFAULT_WIDTH = 50;
+
+/** Columns of the terminal, or undefined when stdout isn't one */
+function terminalWidth(): number | undefined {
+    return process.stdout.isTTY ? process.stdout.columns : undefined;
+}
+
+/** A line across the terminal */
+export function separator(character: string = '─'): string {
+    return character.repeat(terminalWidth() ?? DEFAULT_WIDTH);
+}
 
 export function printHeader(): void {
     console.clear();
-    console.log(chalk.cyan('🤖 AI Screenshot Analyzer - Node.js Edition'));
-    console.log(chalk.cyan('━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━'));
+    console.log(themed('header')('🤖 AI Screenshot Analyzer - Node.js Edition'));
+    console.log(themed('header')(separator('━')));
 }
 
 export function printStatus(message: string): void {
-    console.log(chalk.yellow(message));
+    console.log(themed('status')(message));
 }
 
 export function printSuccess(message: string): void {
-    console.log(chalk.green(message));
+    console.log(themed('success')(message));
 }
 
 export function printError(message: string): void {
-    console.log(chalk.red(message));
+    if (plain) {
+        console.error(message.replace(/^[\p{Extended_Pictographic}\uFE0F\s]+/u, ''));
+        return;
+    }
+    console.log(themed('error')(message));
+}
+
+export function printCacheUsage(usage: TokenUsage | null): void {
+    if (usage && (usage.cacheReadTokens || usage.cacheWriteTokens)) {
+        console.log(themed('muted')(`💾 Prompt cache: ${usage.cacheReadTokens || 0} tokens read, ` +
+            `${usage.cacheWriteTokens || 0} written, ${usage.inputTokens} uncached`));
+    }
+}
+
+export function printPalette(colors: PaletteColor[]): void {
+    for (const color of colors) {
+        const percent = `${(color.share * 100).toFixed(1)}%`.padStart(6);
+        // Without colors the swatch would only be blank space
+        const swatch = chalk.level > 0 ? `${chalk.bgHex(color.hex)('      ')} ` : '';
+        console.log(`${swatch}${color.hex}  rgb(${color.rgb.join(', ')})  ${themed('muted')(percent)}`);
+    }
 }
 
 export function printAnalysisResult(analysis: string): void {
+    if (plain) {
+        writeText(analysis.replace(/\n*$/, '\n'));
+        return;
+    }
+    
     // Simple, clean formatting for the analysis result
-    const lines = analysis.split('\n');
-    let inCodeBlock = false;
+    const formatter = new LineFormatter();
     
-    for (const line of lines) {
-        if (line.trim().startsWith('┌─ CODE SOLUTION')) {
-            // Code block header - make it bright and noticeable
-            console.log(chalk.green(line));
-        } else if (line.trim().startsWith('└─')) {
-            // Code block footer
-            console.log(chalk.green(line));
-        } else if (line.trim().startsWith('```')) {
-            if (!inCodeBlock) {
-                // Starting code block
-                console.log(chalk.yellow(line));
-                inCodeBlock = true;
-            } else {
-                // Ending code block
-                console.log(chalk.yellow(line));
-                inCodeBlock = false;
-            }
-        } else if (inCodeBlock) {
-            // Code content - bright white on black for visibility
-            console.log(chalk.bgBlack.white(line));
-        } else if (line.trim().startsWith('─')) {
-            // Separator lines
-            console.log(chalk.blue(line));
-        } else if (line.includes('🤖 ChatGPT Analysis')) {
-            // Header
-            console.log(chalk.cyan(line));
+    for (const line of analysis.split('\n')) {
+        formatter.format(line).forEach(formatted => console.log(formatted));
+    }
+    formatter.end().forEach(formatted => console.log(formatted));
+    
+    printCopyTip();
+}
+
+/**
+ * The elapsed time, the stage (then the tokens so far), the time left and
+ * the newest words of an answer on one line, while waiting for it. Only on
+ * a terminal, and not with --quiet.
+ */
+export function createAnswerProgress(stages?: StageTimer, expectedMs?: number): AnswerProgress {
+    return new AnswerProgress(!plain && !!process.stdout.isTTY, themed('muted'), stages, expectedMs);
+}
+
+/** The time each stage took, after an answer - on a terminal, like the progress line */
+export function printStageTimings(summary: string): void {
+    if (!plain && process.stdout.isTTY && summary) {
+        console.log(themed('muted')(`⏱️  ${summary}`));
+    }
+}
+
+/**
+ * Prints a streamed answer as it arrives, one complete line at a time,
+ * with the same formatting as `printAnalysisResult`. The line in progress
+ * shows on the progress line below.
+ */
+export class StreamPrinter {
+    private formatter = new LineFormatter();
+    private pending: string = '';
+    private progress: AnswerProgress;
+
+    constructor(stages?: StageTimer, expectedMs?: number) {
+        this.progress = createAnswerProgress(stages, expectedMs);
+        this.progress.start();
+    }
+
+    write(text: string): void {
+        this.pending += text;
+        this.progress.add(text);
+        
+        let newline = this.pending.indexOf('\n');
+        while (newline !== -1) {
+            this.print(this.pending.slice(0, newline));
+            this.pending = this.pending.slice(newline + 1);
+            newline = this.pending.indexOf('\n');
+        }
+    }
+
+    end(): void {
+        this.progress.stop();
+        if (this.pending) {
+            this.print(this.pending);
+            this.pending = '';
+        }
+        if (!plain) {
+            this.formatter.end().forEach(formatted => console.log(formatted));
+            printCopyTip();
+        }
+    }
+
+    private print(line: string): void {
+        if (plain) {
+            writeText(line + '\n');
         } else {
-            // Regular text
-            console.log(chalk.white(line));
+            this.progress.clear();
+            this.formatter.format(line).forEach(formatted => console.log(formatted));
+            this.progress.draw();
         }
     }
-    
+}
+
+function markdownStyles(): MarkdownStyles {
+    return {
+        text: themed('text'),
+        heading: (text, level) => themed('header')(level <= 2 ? chalk.bold.underline(text) : chalk.bold(text)),
+        bold: chalk.bold,
+        italic: chalk.italic,
+        inlineCode: themed('inline_code'),
+        link: (text, url) => `${chalk.underline(text)} ${themed('muted')(`(${url})`)}`,
+        bullet: themed('header'),
+        quote: themed('muted'),
+        rule: themed('accent'),
+        tableBorder: themed('muted'),
+        fence: themed('inline_code'),
+        // Code content - on its own background for visibility
+        code: themedCode
+    };
+}
+
+class LineFormatter {
+    private markdown = new MarkdownRenderer(markdownStyles(), terminalWidth());
+
+    /** The lines to print for `line`; a table is printed once it's complete */
+    format(line: string): string[] {
+        if (line.trim().startsWith('┌─ CODE SOLUTION') || line.trim().startsWith('└─')) {
+            // Code block header and footer - make them bright and noticeable
+            return [...this.markdown.end(), themed('success')(line)];
+        }
+        if (!this.markdown.isInCodeBlock()) {
+            if (/^\s*─+\s*$/.test(line)) {
+                // Separator lines, across the terminal
+                return [...this.markdown.end(), themed('accent')(separator())];
+            }
+            if (line.trim().startsWith('─')) {
+                return [...this.markdown.end(), themed('accent')(line)];
+            }
+            if (line.includes('🤖 ChatGPT Analysis')) {
+                // Header
+                return [...this.markdown.end(), themed('header')(line)];
+            }
+        }
+        return this.markdown.line(line);
+    }
+
+    end(): string[] {
+        return this.markdown.end();
+    }
+}
+
+function printCopyTip(): void {
     // Add copy instruction
-    console.log(chalk.gray('\n💡 Tip: Select and copy code between the ``` markers'));
+    console.log(themed('muted')('\n💡 Tip: Select and copy code between the ``` markers'));
+}
+
+/**
+ * Leave raw mode and show the cursor again, in case a key monitor or a
+ * spinner was interrupted.
+ */
+export function restoreTerminal(): void {
+    if (process.stdin.isTTY && process.stdin.isRaw) {
+        process.stdin.setRawMode(false);
+    }
+    if (process.stdout.isTTY) {
+        process.stdout.write('\x1B[?25h');
+    }
 }
 
 export function createSpinner(message: string): any {
-    return ora(message).start();
+    return ora({ text: message, isSilent: plain }).start();
 }
 
 export function updateSpinner(spinner: any, message: string): void {
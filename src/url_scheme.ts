@@ -0,0 +1,189 @@
+import { execFile } from 'child_process';
+import * as fs from 'fs/promises';
+import * as os from 'os';
+import * as path from 'path';
+import { promisify } from 'util';
+import { CancelledError, ConfigError } from './errors';
+import { CaptureSource } from './screenshot';
+import { cliCommand, desktopQuote, logDir, passedEnvironment } from './service';
+
+const execFileAsync = promisify(execFile);
+
+export const URL_SCHEME = 'aisnapper';
+
+const URL_SOURCES: CaptureSource[] = ['screen', 'clipboard', 'active-window', 'region'];
+
+/** Callback schemes answered without asking, unless `url_callback_schemes` lists others */
+export const DEFAULT_CALLBACK_SCHEMES = ['shortcuts', 'raycast', 'alfred'];
+
+// A link could otherwise capture the screen and post the answer to any web server
+const REFUSED_CALLBACK_SCHEMES = ['http', 'https', 'file', 'ftp', 'data', 'javascript'];
+
+const HANDLER_APP = path.join(os.homedir(), 'Applications', 'AI Snapper URL Handler.app');
+const HANDLER_DESKTOP = 'ai-screenshot-analyzer-url.desktop';
+const LSREGISTER = '/System/Library/Frameworks/CoreServices.framework/Frameworks/LaunchServices.framework/Support/lsregister';
+
+/** An `aisnapper://capture?...` request, with its x-callback-url targets */
+export interface SnapperUrlRequest {
+    prompt?: string;
+    source: CaptureSource;
+    xSuccess?: string;
+    xError?: string;
+    xCancel?: string;
+}
+
+/**
+ * Parse `aisnapper://capture?prompt=...&source=...`, optionally with
+ * `x-success`, `x-error` and `x-cancel` callbacks (x-callback-url), as
+ * sent by Shortcuts, Raycast or Alfred. Throws a `ConfigError` for
+ * anything else, including web and file callbacks.
+ */
+export function parseSnapperUrl(url: string): SnapperUrlRequest {
+    let parsed: URL;
+    try {
+        parsed = new URL(url);
+    } catch (error) {
+        throw new ConfigError(`Not a URL: ${url}`);
+    }
+    // x-callback-url puts the action after an `x-callback-url/` host
+    const action = parsed.host === 'x-callback-url' ? parsed.pathname.replace(/^\//, '') : parsed.host || parsed.pathname.replace(/^\/+/, '');
+    if (parsed.protocol !== `${URL_SCHEME}:` || action !== 'capture') {
+        throw new ConfigError(`Unsupported URL: ${url} (expected ${URL_SCHEME}://capture?prompt=...)`);
+    }
+
+    const params = parsed.searchParams;
+    const source = (params.get('source') || 'screen') as CaptureSource;
+    if (!URL_SOURCES.includes(source)) {
+        throw new ConfigError(`Invalid source: ${source} (expected ${URL_SOURCES.join(', ')})`);
+    }
+    const request: SnapperUrlRequest = {
+        prompt: params.get('prompt') || undefined,
+        source,
+        xSuccess: params.get('x-success') || undefined,
+        xError: params.get('x-error') || undefined,
+        xCancel: params.get('x-cancel') || undefined
+    };
+    for (const scheme of callbackSchemes(request)) {
+        if (REFUSED_CALLBACK_SCHEMES.includes(scheme)) {
+            throw new ConfigError(`Refusing to send the answer to a ${scheme}: callback - use an app's URL scheme`);
+        }
+    }
+    return request;
+}
+
+function callbackSchemes(request: SnapperUrlRequest): string[] {
+    const schemes = [request.xSuccess, request.xError, request.xCancel].flatMap(callback => {
+        if (!callback) {
+            return [];
+        }
+        try {
+            return [new URL(callback).protocol.replace(/:$/, '').toLowerCase()];
+        } catch (error) {
+            throw new ConfigError(`Not a callback URL: ${callback}`);
+        }
+    });
+    return [...new Set(schemes)];
+}
+
+/**
+ * Before capturing for a URL whose callbacks go to apps not on `allowed`
+ * (`url_callback_schemes`), ask with `confirm` - any page can open an
+ * `aisnapper://` link. Throws a `CancelledError` when the user says no.
+ */
+export async function confirmSnapperUrl(request: SnapperUrlRequest, allowed: string[], confirm: (message: string) => Promise<boolean>): Promise<void> {
+    const unlisted = callbackSchemes(request).filter(scheme => !allowed.map(name => name.toLowerCase()).includes(scheme));
+    if (unlisted.length === 0) {
+        return;
+    }
+    const targets = unlisted.map(scheme => `${scheme}://`).join(', ');
+    if (!await confirm(`A link wants to capture your screen and send the answer to ${targets}. Allow it?`)) {
+        throw new CancelledError(`Capture for ${targets} not allowed`);
+    }
+}
+
+/** A callback URL with `params` added to its query */
+export function callbackUrl(base: string, params: Record<string, string>): string {
+    const url = new URL(base);
+    for (const [name, value] of Object.entries(params)) {
+        url.searchParams.set(name, value);
+    }
+    return url.toString();
+}
+
+/** Quote for `/bin/sh`: single quotes, with embedded ones closed and escaped */
+export function shellQuote(value: string): string {
+    return `'${value.replace(/'/g, `'\\''`)}'`;
+}
+
+/** The AppleScript applet that hands each opened URL to `open-url` */
+export function handlerAppleScript(command: string[], logFile: string): string {
+    const shell = `${command.map(shellQuote).join(' ')} `;
+    const asString = (value: string) => `"${value.replace(/\\/g, '\\\\').replace(/"/g, '\\"')}"`;
+    // Backgrounded, so the applet quits while the capture runs
+    return [
+        'on open location theURL',
+        `    do shell script ${asString(shell)} & quoted form of theURL & ${asString(` >> ${shellQuote(logFile)} 2>&1 &`)}`,
+        'end open location',
+        ''
+    ].join('\n');
+}
+
+/**
+ * Register this CLI as the handler for `aisnapper://` URLs: a small
+ * AppleScript applet in ~/Applications on macOS, a desktop entry on Linux.
+ * Returns where it was installed.
+ */
+export async function installUrlHandler(): Promise<string> {
+    const env = Object.entries(passedEnvironment()).map(([name, value]) => `${name}=${value}`);
+    const command = [...(env.length > 0 ? ['env', ...env] : []), ...cliCommand(['open-url'])];
+
+    if (process.platform === 'darwin') {
+        const logFile = path.join(logDir(), 'url-handler.log');
+        const script = path.join(os.tmpdir(), `aisnapper-${process.pid}.applescript`);
+        await fs.mkdir(path.dirname(logFile), { recursive: true });
+        await fs.writeFile(script, handlerAppleScript(command, logFile));
+        try {
+            await fs.rm(HANDLER_APP, { recursive: true, force: true });
+            await fs.mkdir(path.dirname(HANDLER_APP), { recursive: true });
+            await execFileAsync('osacompile', ['-o', HANDLER_APP, script]);
+        } finally {
+            await fs.rm(script, { force: true });
+        }
+
+        await execFileAsync('/usr/libexec/PlistBuddy', [
+            '-c', 'Set :CFBundleIdentifier com.ai-screenshot-analyzer.url-handler',
+            '-c', 'Add :LSUIElement bool true',
+            '-c', 'Add :CFBundleURLTypes array',
+            '-c', 'Add :CFBundleURLTypes:0 dict',
+            '-c', 'Add :CFBundleURLTypes:0:CFBundleURLName string com.ai-screenshot-analyzer.url',
+            '-c', 'Add :CFBundleURLTypes:0:CFBundleURLSchemes array',
+            '-c', `Add :CFBundleURLTypes:0:CFBundleURLSchemes:0 string ${URL_SCHEME}`,
+            path.join(HANDLER_APP, 'Contents', 'Info.plist')
+        ]);
+        // The applet holds API keys from the environment
+        await fs.chmod(HANDLER_APP, 0o700);
+        await execFileAsync(LSREGISTER, ['-f', HANDLER_APP]);
+        return HANDLER_APP;
+    }
+
+    if (process.platform === 'linux') {
+        const dataHome = process.env.XDG_DATA_HOME || path.join(os.homedir(), '.local', 'share');
+        const file = path.join(dataHome, 'applications', HANDLER_DESKTOP);
+        const entry = [
+            '[Desktop Entry]',
+            'Type=Application',
+            'Name=AI Screenshot Analyzer URL Handler',
+            `Exec=${command.map(desktopQuote).join(' ')} %u`,
+            `MimeType=x-scheme-handler/${URL_SCHEME};`,
+            'NoDisplay=true',
+            'Terminal=false'
+        ].join('\n') + '\n';
+        await fs.mkdir(path.dirname(file), { recursive: true });
+        await fs.writeFile(file, entry, { mode: 0o600 });
+        await fs.chmod(file, 0o600);
+        await execFileAsync('xdg-mime', ['default', HANDLER_DESKTOP, `x-scheme-handler/${URL_SCHEME}`]);
+        return file;
+    }
+
+    throw new ConfigError(`${URL_SCHEME}:// URLs can only be registered on macOS and Linux`);
+}
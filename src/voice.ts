@@ -0,0 +1,109 @@
+import { execFile } from 'child_process';
+import * as fs from 'fs/promises';
+import * as os from 'os';
+import * as path from 'path';
+import { promisify } from 'util';
+import { transcribeAudio } from './ai_client';
+import { AppConfig } from './config';
+import { CaptureError } from './errors';
+
+const execFileAsync = promisify(execFile);
+
+// Long enough for a question, short enough not to keep the user waiting
+export const DEFAULT_VOICE_DURATION_MS = 5000;
+
+/** How voice questions are recorded and transcribed */
+export interface VoiceOptions {
+    durationMs: number;
+    /** Local speech-to-text command from `whisper_command`; the WAV path is appended */
+    whisperCommand?: string;
+    /** OpenAI key for the transcription API, when there is no local command */
+    apiKey?: string;
+    config: AppConfig;
+}
+
+/**
+ * Recorders tried in order, each writing 16kHz mono WAV (what whisper
+ * expects) to `file` for `seconds`. sox's `rec` works everywhere it's
+ * installed; ffmpeg needs the platform's capture device.
+ */
+export function recorderCommands(file: string, seconds: number, platform: NodeJS.Platform = process.platform): Array<[string, string[]]> {
+    const commands: Array<[string, string[]]> = [['rec', ['-q', '-c', '1', '-r', '16000', file, 'trim', '0', String(seconds)]]];
+    const ffmpegOutput = ['-t', String(seconds), '-ac', '1', '-ar', '16000', '-y', file];
+    if (platform === 'darwin') {
+        commands.push(['ffmpeg', ['-loglevel', 'error', '-f', 'avfoundation', '-i', ':0', ...ffmpegOutput]]);
+    } else if (platform === 'linux') {
+        commands.push(['ffmpeg', ['-loglevel', 'error', '-f', 'pulse', '-i', 'default', ...ffmpegOutput]]);
+    }
+    return commands;
+}
+
+/**
+ * Split a configured command line into arguments, honoring single and
+ * double quotes and expanding a leading `~/` the way a shell would, e.g.
+ * `whisper-cli -m "~/models/ggml base.bin" -nt -f`.
+ */
+export function parseCommandLine(command: string): string[] {
+    const args: string[] = [];
+    for (const match of command.matchAll(/"([^"]*)"|'([^']*)'|(\S+)/g)) {
+        const arg = match[1] ?? match[2] ?? match[3];
+        args.push(arg.startsWith('~/') ? path.join(os.homedir(), arg.slice(2)) : arg);
+    }
+    return args;
+}
+
+/**
+ * Tidy a transcription: drop the timestamps and markers like
+ * `[BLANK_AUDIO]` that local whisper builds print, and join the lines.
+ */
+export function cleanTranscript(text: string): string {
+    return text
+        .replace(/\[\d{2}:\d{2}[:.\d]*\s*-->\s*\d{2}:\d{2}[:.\d]*\]/g, '')
+        .replace(/\[[A-Z_ ]+\]/g, '')
+        .split('\n')
+        .map(line => line.trim())
+        .filter(line => line.length > 0)
+        .join(' ');
+}
+
+async function recordAudio(file: string, durationMs: number): Promise<void> {
+    const seconds = Math.max(1, Math.round(durationMs / 1000));
+    for (const [command, args] of recorderCommands(file, seconds)) {
+        try {
+            await execFileAsync(command, args, { timeout: durationMs + 10000 });
+            return;
+        } catch (error) {
+            if ((error as NodeJS.ErrnoException).code !== 'ENOENT') {
+                throw new CaptureError(`Recording with ${command} failed - is microphone access allowed? ${error}`);
+            }
+        }
+    }
+    throw new CaptureError('Voice questions need sox (`rec`) or ffmpeg to record the microphone');
+}
+
+async function transcribe(file: string, options: VoiceOptions): Promise<string> {
+    if (options.whisperCommand) {
+        const [command, ...args] = parseCommandLine(options.whisperCommand);
+        try {
+            const { stdout } = await execFileAsync(command, [...args, file]);
+            return cleanTranscript(stdout);
+        } catch (error) {
+            throw new CaptureError(`Transcription with ${command} failed: ${error}`);
+        }
+    }
+    return cleanTranscript(await transcribeAudio(await fs.readFile(file), options.apiKey!, options.config));
+}
+
+/**
+ * Record a spoken question from the microphone and transcribe it. Returns
+ * an empty string when nothing was said.
+ */
+export async function listenForQuestion(options: VoiceOptions): Promise<string> {
+    const file = path.join(os.tmpdir(), `ai-screenshot-voice-${process.pid}-${Date.now()}.wav`);
+    try {
+        await recordAudio(file, options.durationMs);
+        return await transcribe(file, options);
+    } finally {
+        await fs.rm(file, { force: true });
+    }
+}
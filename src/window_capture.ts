@@ -0,0 +1,241 @@
+import { execFile } from 'child_process';
+import * as fs from 'fs/promises';
+import * as os from 'os';
+import * as path from 'path';
+import { promisify } from 'util';
+import { CaptureError } from './errors';
+import { MaskRect } from './privacy_mask';
+import { captureGdiWindow } from './gdi_capture';
+
+const execFileAsync = promisify(execFile);
+
+// Screenshots of large windows easily exceed execFile's 1MB default
+const MAX_OUTPUT_BYTES = 200 * 1024 * 1024;
+
+/** A top-level window, as reported by the platform's window list */
+export interface WindowInfo {
+    /** Platform window id: CGWindowID, X11 window id or HWND */
+    id: string;
+    /** Application (process or window class) owning the window */
+    app: string;
+    title: string;
+    /** Position on the desktop in logical pixels, relative to the primary display */
+    bounds?: MaskRect;
+}
+
+// Front-to-back list of normal (layer 0) windows currently on screen
+const MAC_LIST_WINDOWS = `
+ObjC.import('CoreGraphics');
+const windows = ObjC.deepUnwrap(ObjC.castRefToObject(
+    $.CGWindowListCopyWindowInfo($.kCGWindowListOptionOnScreenOnly | $.kCGWindowListExcludeDesktopElements, $.kCGNullWindowID)
+));
+JSON.stringify(windows
+    .filter(w => w.kCGWindowLayer === 0)
+    .map(w => ({
+        id: String(w.kCGWindowNumber),
+        app: w.kCGWindowOwnerName || '',
+        title: w.kCGWindowName || '',
+        bounds: { x: w.kCGWindowBounds.X, y: w.kCGWindowBounds.Y, width: w.kCGWindowBounds.Width, height: w.kCGWindowBounds.Height }
+    })));
+`;
+
+// Minimized windows are left out: they can't be captured and aren't on screen
+const WINDOWS_LIST_WINDOWS = `
+Add-Type @'
+using System;
+using System.Runtime.InteropServices;
+public static class WindowList {
+    [StructLayout(LayoutKind.Sequential)] public struct RECT { public int Left, Top, Right, Bottom; }
+    [DllImport("user32.dll")] public static extern bool GetWindowRect(IntPtr hWnd, out RECT rect);
+    [DllImport("user32.dll")] public static extern bool IsIconic(IntPtr hWnd);
+}
+'@
+Get-Process | Where-Object { $_.MainWindowHandle -ne 0 -and $_.MainWindowTitle -and -not [WindowList]::IsIconic($_.MainWindowHandle) } |
+    ForEach-Object {
+        $rect = New-Object WindowList+RECT
+        [void][WindowList]::GetWindowRect($_.MainWindowHandle, [ref]$rect)
+        [pscustomobject]@{
+            id = [string]$_.MainWindowHandle; app = $_.ProcessName; title = $_.MainWindowTitle
+            bounds = @{ x = $rect.Left; y = $rect.Top; width = $rect.Right - $rect.Left; height = $rect.Bottom - $rect.Top }
+        }
+    } |
+    ConvertTo-Json -Compress
+`;
+
+// Copies the window's screen area, so the window should be in front
+const windowsCaptureWindow = (hwnd: number) => `
+Add-Type -AssemblyName System.Drawing
+Add-Type @'
+using System;
+using System.Runtime.InteropServices;
+public struct RECT { public int Left, Top, Right, Bottom; }
+public static class Win32 {
+    [DllImport("user32.dll")] public static extern bool GetWindowRect(IntPtr hWnd, out RECT rect);
+}
+'@
+$rect = New-Object RECT
+if (-not [Win32]::GetWindowRect([IntPtr]${hwnd}, [ref]$rect)) { throw 'window not found' }
+$bitmap = New-Object System.Drawing.Bitmap ($rect.Right - $rect.Left), ($rect.Bottom - $rect.Top)
+$graphics = [System.Drawing.Graphics]::FromImage($bitmap)
+$graphics.CopyFromScreen($rect.Left, $rect.Top, 0, 0, $bitmap.Size)
+$stream = New-Object System.IO.MemoryStream
+$bitmap.Save($stream, [System.Drawing.Imaging.ImageFormat]::Png)
+[Convert]::ToBase64String($stream.ToArray())
+`;
+
+/**
+ * List the top-level windows on screen, frontmost first where the
+ * platform reports an order.
+ */
+export async function listWindows(): Promise<WindowInfo[]> {
+    try {
+        switch (process.platform) {
+            case 'darwin': {
+                const { stdout } = await execFileAsync('osascript', ['-l', 'JavaScript', '-e', MAC_LIST_WINDOWS]);
+                return JSON.parse(stdout);
+            }
+            case 'win32': {
+                const { stdout } = await execFileAsync('powershell', ['-NoProfile', '-Command', WINDOWS_LIST_WINDOWS]);
+                // ConvertTo-Json emits a bare object for a single window
+                const windows = stdout.trim() ? JSON.parse(stdout) : [];
+                return Array.isArray(windows) ? windows : [windows];
+            }
+            default: {
+                const { stdout } = await execFileAsync('wmctrl', ['-lGx']);
+                return parseWmctrl(stdout);
+            }
+        }
+    } catch (error) {
+        throw new CaptureError(`Failed to list windows: ${error}${process.platform === 'linux' ? ' (is wmctrl installed?)' : ''}`);
+    }
+}
+
+const WINDOWS_ACTIVE_WINDOW = `
+Add-Type @'
+using System;
+using System.Runtime.InteropServices;
+using System.Text;
+public static class Foreground {
+    [DllImport("user32.dll")] public static extern IntPtr GetForegroundWindow();
+    [DllImport("user32.dll")] public static extern int GetWindowText(IntPtr hWnd, StringBuilder text, int count);
+}
+'@
+$hwnd = [Foreground]::GetForegroundWindow()
+$title = New-Object System.Text.StringBuilder 512
+[void][Foreground]::GetWindowText($hwnd, $title, 512)
+@{ id = [string]$hwnd; app = ''; title = $title.ToString() } | ConvertTo-Json -Compress
+`;
+
+/**
+ * The window that currently has focus.
+ */
+export async function activeWindow(): Promise<WindowInfo> {
+    try {
+        switch (process.platform) {
+            case 'darwin': {
+                // The window list is front to back
+                const [front] = await listWindows();
+                if (!front) {
+                    throw new Error('no windows on screen');
+                }
+                return front;
+            }
+            case 'win32': {
+                const { stdout } = await execFileAsync('powershell', ['-NoProfile', '-Command', WINDOWS_ACTIVE_WINDOW]);
+                return JSON.parse(stdout);
+            }
+            default: {
+                const { stdout: id } = await execFileAsync('xdotool', ['getactivewindow']);
+                const { stdout: title } = await execFileAsync('xdotool', ['getwindowname', id.trim()]);
+                return { id: id.trim(), app: '', title: title.trim() };
+            }
+        }
+    } catch (error) {
+        throw new CaptureError(`Failed to find the active window: ${error}${process.platform === 'linux' ? ' (is xdotool installed?)' : ''}`);
+    }
+}
+
+/**
+ * Parse `wmctrl -lx` output: id, desktop, WM_CLASS (instance.Class),
+ * host, then the title. With `-lGx`, the geometry follows the desktop.
+ */
+export function parseWmctrl(output: string): WindowInfo[] {
+    const windows: WindowInfo[] = [];
+    for (const line of output.split('\n')) {
+        const match = line.match(/^(0x[0-9a-f]+)\s+-?\d+\s+(?:(-?\d+)\s+(-?\d+)\s+(\d+)\s+(\d+)\s+)?(\S+)\s+\S+\s?(.*)$/i);
+        if (match) {
+            const windowClass = match[6];
+            const window: WindowInfo = {
+                id: match[1],
+                app: windowClass.slice(windowClass.indexOf('.') + 1),
+                title: match[7].trim()
+            };
+            if (match[2] !== undefined) {
+                window.bounds = { x: Number(match[2]), y: Number(match[3]), width: Number(match[4]), height: Number(match[5]) };
+            }
+            windows.push(window);
+        }
+    }
+    return windows;
+}
+
+/**
+ * The windows belonging to a blocked app, matched case-insensitively
+ * against the app name (a partial name is enough).
+ */
+export function findBlockedWindows(windows: WindowInfo[], blockedApps: string[]): WindowInfo[] {
+    const blocked = blockedApps.map(app => app.trim().toLowerCase()).filter(app => app);
+    return windows.filter(window => blocked.some(app => window.app.toLowerCase().includes(app)));
+}
+
+/**
+ * Find the window for `matcher`, case-insensitively: an exact app name or
+ * title wins, otherwise the first window whose app or title contains it.
+ */
+export function matchWindow(windows: WindowInfo[], matcher: string): WindowInfo | undefined {
+    const needle = matcher.trim().toLowerCase();
+    const fields = (window: WindowInfo) => [window.app.toLowerCase(), window.title.toLowerCase()];
+
+    return windows.find(window => fields(window).some(field => field === needle))
+        || windows.find(window => fields(window).some(field => field.includes(needle)));
+}
+
+/**
+ * Capture just one window as a PNG. `native` uses the GDI backend on
+ * Windows, which also captures covered windows.
+ */
+export async function captureWindowImage(window: WindowInfo, native: boolean = false): Promise<Buffer> {
+    switch (process.platform) {
+        case 'darwin': {
+            // screencapture only writes to files
+            const file = path.join(os.tmpdir(), `ai-screenshot-window-${process.pid}-${Date.now()}.png`);
+            try {
+                // -o leaves out the window shadow, -x the shutter sound
+                await execFileAsync('screencapture', ['-x', '-o', '-t', 'png', '-l', window.id, file]);
+                return await fs.readFile(file);
+            } finally {
+                await fs.rm(file, { force: true });
+            }
+        }
+        case 'win32': {
+            if (native) {
+                return captureGdiWindow(Number(window.id));
+            }
+            const { stdout } = await execFileAsync(
+                'powershell',
+                ['-NoProfile', '-Command', windowsCaptureWindow(Number(window.id))],
+                { maxBuffer: MAX_OUTPUT_BYTES }
+            );
+            return Buffer.from(stdout.trim(), 'base64');
+        }
+        default: {
+            // ImageMagick, which screenshot-desktop already needs on Linux
+            const { stdout } = await execFileAsync(
+                'import',
+                ['-window', window.id, 'png:-'],
+                { encoding: 'buffer', maxBuffer: MAX_OUTPUT_BYTES }
+            );
+            return stdout;
+        }
+    }
+}
@@ -0,0 +1,26 @@
+import { summarizeAnswer } from '../src/annotate';
+
+describe('summarizeAnswer', () => {
+  test('should use the first prose line', () => {
+    const analysis = '### 1. What is this?\n\nA React component that renders a login form.\n\nMore detail here.';
+
+    expect(summarizeAnswer(analysis)).toBe('A React component that renders a login form.');
+  });
+
+  test('should skip code blocks', () => {
+    const analysis = '```python\nprint("hi")\n```\nPrints a greeting.';
+
+    expect(summarizeAnswer(analysis)).toBe('Prints a greeting.');
+  });
+
+  test('should fall back when the answer is only code', () => {
+    expect(summarizeAnswer('```js\nconsole.log(1);\n```')).toContain('full answer');
+  });
+
+  test('should truncate very long lines', () => {
+    const summary = summarizeAnswer('x'.repeat(1000));
+
+    expect(summary.length).toBe(300);
+    expect(summary.endsWith('…')).toBe(true);
+  });
+});
@@ -0,0 +1,27 @@
+import { parseLocatedRegion } from '../src/auto_crop';
+
+describe('parseLocatedRegion', () => {
+  test('should add a margin to the located region', () => {
+    expect(parseLocatedRegion('10%,20%,50%,60%')).toEqual({
+      x: { value: 8, percent: true },
+      y: { value: 18, percent: true },
+      width: { value: 54, percent: true },
+      height: { value: 64, percent: true },
+    });
+  });
+
+  test('should find the region inside a wordier answer and clip the margin', () => {
+    expect(parseLocatedRegion('The editor is at `0, 0, 40, 100`.')).toEqual({
+      x: { value: 0, percent: true },
+      y: { value: 0, percent: true },
+      width: { value: 42, percent: true },
+      height: { value: 100, percent: true },
+    });
+  });
+
+  test('should skip answers without a useful region', () => {
+    expect(parseLocatedRegion('none')).toBeUndefined();
+    expect(parseLocatedRegion('0%,0%,100%,100%')).toBeUndefined();
+    expect(parseLocatedRegion('10%,10%,0%,50%')).toBeUndefined();
+  });
+});
@@ -0,0 +1,43 @@
+import { signAwsRequest } from '../src/aws_sigv4';
+
+describe('signAwsRequest', () => {
+  const credentials = {
+    accessKeyId: 'AKIDEXAMPLE',
+    secretAccessKey: 'wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY',
+  };
+  const now = new Date('2015-08-30T12:36:00Z');
+
+  // Vectors from the AWS Signature Version 4 test suite
+  test('should match the get-vanilla test vector', () => {
+    const headers = signAwsRequest(
+      { method: 'GET', url: 'https://example.amazonaws.com/', headers: {}, body: '' },
+      credentials, 'us-east-1', 'service', now
+    );
+
+    expect(headers['x-amz-date']).toBe('20150830T123600Z');
+    expect(headers.authorization).toBe(
+      'AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, ' +
+      'SignedHeaders=host;x-amz-date, ' +
+      'Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31'
+    );
+  });
+
+  test('should sort query parameters', () => {
+    const headers = signAwsRequest(
+      { method: 'GET', url: 'https://example.amazonaws.com/?Param2=value2&Param1=value1', headers: {}, body: '' },
+      credentials, 'us-east-1', 'service', now
+    );
+
+    expect(headers.authorization).toContain('Signature=b97d918cfa904a5beff61c982a1b6f458b799221646efd99d3219ec94cdf2500');
+  });
+
+  test('should sign the session token when present', () => {
+    const headers = signAwsRequest(
+      { method: 'POST', url: 'https://example.amazonaws.com/', headers: {}, body: '{}' },
+      { ...credentials, sessionToken: 'token' }, 'us-east-1', 'service', now
+    );
+
+    expect(headers['x-amz-security-token']).toBe('token');
+    expect(headers.authorization).toContain('SignedHeaders=host;x-amz-date;x-amz-security-token');
+  });
+});
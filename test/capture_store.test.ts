@@ -0,0 +1,69 @@
+import * as fs from 'fs/promises';
+import * as os from 'os';
+import * as path from 'path';
+import sharp from 'sharp';
+import { CaptureStore, captureName } from '../src/capture_store';
+
+describe('captureName', () => {
+  test('should build a sortable local timestamp', () => {
+    expect(captureName(new Date(2025, 0, 31, 14, 5, 9))).toBe('2025-01-31_14-05-09');
+  });
+});
+
+describe('CaptureStore', () => {
+  test('should save the image and a markdown record of the analysis', async () => {
+    const dir = await fs.mkdtemp(path.join(os.tmpdir(), 'captures-'));
+    const image = await sharp({ create: { width: 8, height: 8, channels: 3, background: { r: 0, g: 0, b: 0 } } }).png().toBuffer();
+
+    const files = await new CaptureStore(dir, 'jpeg').save([image], 'What is this?', 'A black square.', new Date(2025, 0, 31, 14, 5, 9));
+
+    expect(files).toEqual([path.join(dir, '2025-01-31_14-05-09.jpg')]);
+    expect((await sharp(files[0]).metadata()).format).toBe('jpeg');
+    const record = await fs.readFile(path.join(dir, '2025-01-31_14-05-09.md'), 'utf8');
+    expect(record).toContain('# What is this?');
+    expect(record).toContain('A black square.');
+    expect(record).toContain('[![capture](2025-01-31_14-05-09.thumb.jpg)](2025-01-31_14-05-09.jpg)');
+
+    await fs.rm(dir, { recursive: true, force: true });
+  });
+
+  test('should record the model, and leave out links when no capture was kept', async () => {
+    const dir = await fs.mkdtemp(path.join(os.tmpdir(), 'captures-'));
+
+    const file = await new CaptureStore(dir).saveRecord('Why?', 'Because.', [], new Date(2025, 0, 31, 14, 5, 9), 'openai / gpt-4o');
+
+    expect(file).toBe(path.join(dir, '2025-01-31_14-05-09.md'));
+    const record = await fs.readFile(file, 'utf8');
+    expect(record).toMatch(/^# Why\?\n\n\*.+ · `openai \/ gpt-4o`\*\n\nBecause\.\n$/);
+    expect(await fs.readdir(dir)).toEqual(['2025-01-31_14-05-09.md']);
+
+    await fs.rm(dir, { recursive: true, force: true });
+  });
+
+  test('should write a small thumbnail next to each capture', async () => {
+    const dir = await fs.mkdtemp(path.join(os.tmpdir(), 'captures-'));
+    const image = await sharp({ create: { width: 1600, height: 900, channels: 3, background: { r: 0, g: 0, b: 0 } } }).png().toBuffer();
+
+    await new CaptureStore(dir).save([image], undefined, 'Nothing.', new Date(2025, 0, 31, 14, 5, 9));
+
+    const metadata = await sharp(path.join(dir, '2025-01-31_14-05-09.thumb.jpg')).metadata();
+    expect([metadata.width, metadata.height]).toEqual([320, 180]);
+
+    await fs.rm(dir, { recursive: true, force: true });
+  });
+
+  test('should find the latest saved capture', async () => {
+    const dir = await fs.mkdtemp(path.join(os.tmpdir(), 'captures-'));
+    const store = new CaptureStore(dir);
+    expect(await store.latest()).toBeUndefined();
+
+    await fs.writeFile(path.join(dir, '2025-01-31_14-05-09.png'), '');
+    await fs.writeFile(path.join(dir, '2025-02-01_09-00-00.jpg'), '');
+    await fs.writeFile(path.join(dir, '2025-02-01_09-00-00.md'), '');
+    await fs.writeFile(path.join(dir, 'notes.png'), '');
+
+    expect(await store.latest()).toBe(path.join(dir, '2025-02-01_09-00-00.jpg'));
+
+    await fs.rm(dir, { recursive: true, force: true });
+  });
+});
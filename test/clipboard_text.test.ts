@@ -0,0 +1,29 @@
+import { clipboardWriteCommands, firstCodeBlock } from '../src/clipboard_text';
+
+describe('firstCodeBlock', () => {
+  test('should extract the first fenced block and its language', () => {
+    const answer = 'Use a set:\n\n```python\ndef dedupe(xs):\n    return list(set(xs))\n```\n\nOr:\n```js\n[...new Set(xs)]\n```';
+    expect(firstCodeBlock(answer)).toEqual({ language: 'python', code: 'def dedupe(xs):\n    return list(set(xs))' });
+  });
+
+  test('should handle tildes, longer fences and indented blocks', () => {
+    expect(firstCodeBlock('~~~\na\n~~~')).toEqual({ language: '', code: 'a' });
+    expect(firstCodeBlock('````md\n```\ninner\n```\n````')).toEqual({ language: 'md', code: '```\ninner\n```' });
+    expect(firstCodeBlock('1. Run:\n   ```sh\n   npm test\n   ```')).toEqual({ language: 'sh', code: 'npm test' });
+  });
+
+  test('should run an unclosed block to the end', () => {
+    expect(firstCodeBlock('```go\nfunc main() {\n')).toEqual({ language: 'go', code: 'func main() {' });
+  });
+
+  test('should return undefined without a block', () => {
+    expect(firstCodeBlock('Just an explanation with `inline` code.')).toBeUndefined();
+  });
+});
+
+describe('clipboardWriteCommands', () => {
+  test('should use the platform clipboard tool', () => {
+    expect(clipboardWriteCommands('darwin')).toEqual([['pbcopy', []]]);
+    expect(clipboardWriteCommands('win32')[0][0]).toBe('powershell');
+  });
+});
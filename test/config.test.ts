@@ -1,4 +1,5 @@
 import { AppConfig } from '../src/config';
+import { ConfigError } from '../src/errors';
 import * as fs from 'fs/promises';
 import * as path from 'path';
 import * as os from 'os';
@@ -66,4 +67,120 @@ describe('AppConfig', () => {
     expect(config.maxImageSizeMb).toBe(10); // default
     expect(config.defaultProvider).toBe('claude'); // default
   });
-});
\ No newline at end of file
+});
+
+describe('AppConfig.fromToml', () => {
+  test('should map snake_case keys from the config file', () => {
+    const config = AppConfig.fromToml({
+      screenshots_dir: '/tmp/shots',
+      image_format: 'jpeg',
+      jpeg_quality: 80,
+      max_image_size_mb: 4,
+      api_key: 'sk-ant-test',
+      default_provider: 'claude'
+    });
+
+    expect(config.screenshotsDir).toBe('/tmp/shots');
+    expect(config.imageFormat).toBe('jpeg');
+    expect(config.jpegQuality).toBe(80);
+    expect(config.maxImageSizeMb).toBe(4);
+    expect(config.apiKey).toBe('sk-ant-test');
+  });
+
+  test('should expand ~ in screenshots_dir', () => {
+    const config = AppConfig.fromToml({ screenshots_dir: '~/shots' });
+
+    expect(config.screenshotsDir).toBe(path.join(os.homedir(), 'shots'));
+  });
+
+  test('should read network timeouts in seconds', () => {
+    const config = AppConfig.fromToml({ request_timeout_secs: 300, connect_timeout_secs: 2 });
+
+    expect(config.requestTimeoutSecs).toBe(300);
+    expect(config.connectTimeoutSecs).toBe(2);
+  });
+
+  test('should read the upload encoding settings', () => {
+    const config = AppConfig.fromToml({ upload_format: 'webp', webp_quality: 70 });
+
+    expect(config.uploadFormat).toBe('webp');
+    expect(config.webpQuality).toBe(70);
+    expect(new AppConfig().uploadFormat).toBe('auto');
+  });
+
+  test('should read the capture downscaling settings', () => {
+    const config = AppConfig.fromToml({ max_dimension: 2048, keep_originals: true });
+
+    expect(config.maxDimension).toBe(2048);
+    expect(config.keepOriginals).toBe(true);
+    expect(new AppConfig().maxDimension).toBeUndefined();
+  });
+
+  test('should read HiDPI downsampling with per-display scale factors', () => {
+    const config = AppConfig.fromToml({ downsample_hidpi: true, display_scale: { '0': 2, 'DELL U2720Q': 1 } });
+
+    expect(config.downsampleHiDpi).toBe(true);
+    expect(config.displayScale).toEqual({ '0': 2, 'DELL U2720Q': 1 });
+  });
+
+  test('should read the theme and its color overrides', () => {
+    const config = AppConfig.fromToml({ theme: 'light', theme_colors: { error: '#d70000' } });
+
+    expect(config.theme).toBe('light');
+    expect(config.themeColors).toEqual({ error: '#d70000' });
+  });
+
+  test('should read privacy mask areas and regions', () => {
+    const config = AppConfig.fromToml({
+      mask_areas: ['menu bar', 'notifications'],
+      mask_regions: [{ x: -400, y: 0, width: 400, height: 300 }],
+    });
+
+    expect(config.maskAreas).toEqual(['menu bar', 'notifications']);
+    expect(config.maskRegions).toEqual([{ x: -400, y: 0, width: 400, height: 300 }]);
+  });
+
+  test('should read blocked apps, refusing captures by default', () => {
+    const config = AppConfig.fromToml({ blocked_apps: ['1Password'] });
+
+    expect(config.blockedApps).toEqual(['1Password']);
+    expect(config.blockedAppsAction).toBe('abort');
+    expect(AppConfig.fromToml({ blocked_apps_action: 'mask' }).blockedAppsAction).toBe('mask');
+  });
+
+  test('should accept default_screen as an index or a name', () => {
+    expect(AppConfig.fromToml({ default_screen: 1 }).defaultScreen).toBe('1');
+    expect(AppConfig.fromToml({ default_screen: 'DELL U2720Q' }).defaultScreen).toBe('DELL U2720Q');
+    expect(AppConfig.fromToml({}).defaultScreen).toBeUndefined();
+  });
+});
+
+describe('AppConfig.withSetting', () => {
+  test('should set a value by its config file key', () => {
+    const config = new AppConfig().withSetting('run_at_login', 'true');
+
+    expect(config.runAtLogin).toBe(true);
+    expect(config.withSetting('max_tokens', '800').maxTokens).toBe(800);
+    expect(config.withSetting('model', 'gpt-4o-mini').model).toBe('gpt-4o-mini');
+  });
+
+  test('should reject unknown keys', () => {
+    expect(() => new AppConfig().withSetting('run_at_logon', 'true')).toThrow(ConfigError);
+  });
+});
+
+describe('Ollama settings', () => {
+  test('should default to a local llava model', () => {
+    const config = new AppConfig();
+
+    expect(config.ollamaBaseUrl).toBe('http://localhost:11434');
+    expect(config.ollamaModel).toBe('llava');
+  });
+
+  test('should read Ollama settings from TOML', () => {
+    const config = AppConfig.fromToml({ ollama_base_url: 'http://gpu-box:11434', ollama_model: 'gemma3' });
+
+    expect(config.ollamaBaseUrl).toBe('http://gpu-box:11434');
+    expect(config.ollamaModel).toBe('gemma3');
+  });
+});
@@ -0,0 +1,86 @@
+import * as fs from 'fs/promises';
+import * as net from 'net';
+import * as os from 'os';
+import * as path from 'path';
+import { controlSocketPath, parseControlRequest, startControlServer } from '../src/control_socket';
+
+describe('parseControlRequest', () => {
+  test('should accept ask requests', () => {
+    expect(parseControlRequest('{"command":"ask","question":"Why is this test failing?"}')).toEqual({
+      command: 'ask',
+      question: 'Why is this test failing?',
+      source: undefined,
+    });
+    expect(parseControlRequest('{"command":"ask","source":"active-window"}')).toEqual({
+      command: 'ask',
+      question: undefined,
+      source: 'active-window',
+    });
+  });
+
+  test('should accept status and clear-sticky requests', () => {
+    expect(parseControlRequest('{"command":"status"}')).toEqual({ command: 'status' });
+    expect(parseControlRequest('{"command":"clear-sticky"}')).toEqual({ command: 'clear-sticky' });
+  });
+
+  test('should accept notification actions', () => {
+    expect(parseControlRequest('{"command":"copy-code"}')).toEqual({ command: 'copy-code' });
+    expect(parseControlRequest('{"command":"open-editor"}')).toEqual({ command: 'open-editor' });
+    expect(parseControlRequest('{"command":"follow-up","question":"Why O(n log n)?"}')).toEqual({
+      command: 'follow-up',
+      question: 'Why O(n log n)?',
+    });
+  });
+
+  test('should reject anything else', () => {
+    expect(parseControlRequest('not json')).toBeUndefined();
+    expect(parseControlRequest('{"command":"shutdown"}')).toBeUndefined();
+    expect(parseControlRequest('{"command":"ask","question":42}')).toBeUndefined();
+    expect(parseControlRequest('{"command":"ask","source":"window"}')).toBeUndefined();
+    expect(parseControlRequest('{"command":"follow-up"}')).toBeUndefined();
+    expect(parseControlRequest('{"command":"follow-up","question":"  "}')).toBeUndefined();
+  });
+});
+
+describe('startControlServer', () => {
+  let dir: string;
+
+  beforeEach(async () => {
+    dir = await fs.mkdtemp(path.join(os.tmpdir(), 'control-'));
+    jest.spyOn(os, 'homedir').mockReturnValue(dir);
+  });
+
+  afterEach(async () => {
+    jest.restoreAllMocks();
+    await fs.rm(dir, { recursive: true });
+  });
+
+  test('should answer only the first line, even when it arrives in pieces', async () => {
+    const handle = jest.fn().mockResolvedValue({ ok: true, answer: 'ready' });
+    const server = await startControlServer(handle);
+    try {
+      const response = await new Promise<string>((resolve, reject) => {
+        let received = '';
+        const connection = net.createConnection(controlSocketPath(), () => {
+          connection.write('{"command":"st');
+          setTimeout(() => {
+            connection.write('atus"}\n{"command":"ask"}\n');
+            connection.write('{"command":"copy-code"}\n');
+          }, 20);
+        });
+        connection.setEncoding('utf8');
+        connection.on('data', (chunk: string) => {
+          received += chunk;
+        });
+        connection.on('end', () => resolve(received));
+        connection.on('error', reject);
+      });
+
+      expect(JSON.parse(response)).toEqual({ ok: true, answer: 'ready' });
+      expect(handle).toHaveBeenCalledTimes(1);
+      expect(handle).toHaveBeenCalledWith({ command: 'status' });
+    } finally {
+      await new Promise(resolve => server.close(resolve));
+    }
+  });
+});
@@ -0,0 +1,32 @@
+import { cropRegion, parseCrop } from '../src/crop';
+
+describe('parseCrop', () => {
+  test('should parse pixels and percentages', () => {
+    expect(parseCrop('50%, 0, 50%, 100%')).toEqual({
+      x: { value: 50, percent: true },
+      y: { value: 0, percent: false },
+      width: { value: 50, percent: true },
+      height: { value: 100, percent: true },
+    });
+  });
+
+  test('should reject malformed crops', () => {
+    expect(parseCrop('0,0,100')).toBeUndefined();
+    expect(parseCrop('0,0,-100,100')).toBeUndefined();
+    expect(parseCrop('a,b,c,d')).toBeUndefined();
+  });
+});
+
+describe('cropRegion', () => {
+  test('should resolve the right half of the screen', () => {
+    expect(cropRegion(parseCrop('50%,0,50%,100%')!, 1920, 1080)).toEqual({ left: 960, top: 0, width: 960, height: 1080 });
+  });
+
+  test('should clip to the image', () => {
+    expect(cropRegion(parseCrop('1800,1000,400,400')!, 1920, 1080)).toEqual({ left: 1800, top: 1000, width: 120, height: 80 });
+  });
+
+  test('should report a crop outside the image', () => {
+    expect(cropRegion(parseCrop('2000,0,100,100')!, 1920, 1080)).toBeUndefined();
+  });
+});
@@ -0,0 +1,34 @@
+import sharp from 'sharp';
+import { drawCursor } from '../src/cursor';
+
+const white = (width: number, height: number) =>
+  sharp({ create: { width, height, channels: 3, background: { r: 255, g: 255, b: 255 } } }).png().toBuffer();
+
+const pixel = async (image: Buffer, x: number, y: number) => {
+  const { data, info } = await sharp(image).raw().toBuffer({ resolveWithObject: true });
+  return data[(y * info.width + x) * info.channels];
+};
+
+describe('drawCursor', () => {
+  test('should scale the pointer position onto HiDPI captures', async () => {
+    const image = await drawCursor(await white(200, 100), { x: 50, y: 20, screenWidth: 100, screenHeight: 50 });
+
+    // The pointer's outline starts at (100, 40) at 2x
+    expect(await pixel(image, 104, 56)).toBeLessThan(128);
+    expect(await pixel(image, 20, 20)).toBe(255);
+  });
+
+  test('should leave the image alone when the pointer is elsewhere', async () => {
+    const original = await white(100, 50);
+
+    expect(await drawCursor(original, { x: 150, y: 20, screenWidth: 100, screenHeight: 50 })).toBe(original);
+  });
+
+  test('should clip a pointer near the bottom-right corner', async () => {
+    const image = await drawCursor(await white(100, 50), { x: 98, y: 48, screenWidth: 100, screenHeight: 50 });
+    const metadata = await sharp(image).metadata();
+
+    expect(metadata.width).toBe(100);
+    expect(metadata.height).toBe(50);
+  });
+});
@@ -0,0 +1,53 @@
+import sharp from 'sharp';
+import { DisplayInfo, configuredScale, isAllScreens, selectDisplay, stitchImages } from '../src/displays';
+
+describe('selectDisplay', () => {
+  const displays: DisplayInfo[] = [
+    { index: 0, id: 69732928, name: 'Built-in Retina Display' },
+    { index: 1, id: 724042646, name: 'DELL U2720Q' },
+    { index: 2, id: 'HDMI-1', name: 'HDMI-1' }
+  ];
+
+  test('should select by index', () => {
+    expect(selectDisplay(displays, '1')?.name).toBe('DELL U2720Q');
+    expect(selectDisplay(displays, '5')).toBeUndefined();
+  });
+
+  test('should select by exact or partial name, ignoring case', () => {
+    expect(selectDisplay(displays, 'hdmi-1')?.index).toBe(2);
+    expect(selectDisplay(displays, 'dell')?.index).toBe(1);
+    expect(selectDisplay(displays, 'LG')).toBeUndefined();
+  });
+});
+
+describe('stitchImages', () => {
+  const solid = (width: number, height: number) =>
+    sharp({ create: { width, height, channels: 3, background: { r: 255, g: 255, b: 255 } } }).png().toBuffer();
+
+  test('should place displays side by side in one image', async () => {
+    const stitched = await stitchImages([await solid(40, 20), await solid(30, 32)]);
+    const metadata = await sharp(stitched).metadata();
+
+    expect(metadata.width).toBe(70);
+    expect(metadata.height).toBe(32);
+  });
+
+  test('should recognize the all selector', () => {
+    expect(isAllScreens(' ALL ')).toBe(true);
+    expect(isAllScreens('1')).toBe(false);
+    expect(isAllScreens(undefined)).toBe(false);
+  });
+});
+
+describe('configuredScale', () => {
+  const display: DisplayInfo = { index: 1, id: 724042646, name: 'DELL U2720Q' };
+
+  test('should look up the display by index or name', () => {
+    expect(configuredScale({ '1': 2 }, display)).toBe(2);
+    expect(configuredScale({ 'dell u2720q': 1.5 }, display)).toBe(1.5);
+  });
+
+  test('should return undefined for other displays', () => {
+    expect(configuredScale({ '0': 2, 'Built-in Retina Display': 2 }, display)).toBeUndefined();
+  });
+});
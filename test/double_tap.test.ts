@@ -0,0 +1,47 @@
+import { DoubleTapDetector, parseModifierKey } from '../src/double_tap';
+
+describe('parseModifierKey', () => {
+  test('should accept common spellings', () => {
+    expect(parseModifierKey('right cmd')).toBe('RIGHT META');
+    expect(parseModifierKey('Right-Command')).toBe('RIGHT META');
+    expect(parseModifierKey('left_control')).toBe('LEFT CTRL');
+    expect(parseModifierKey('RIGHT OPTION')).toBe('RIGHT ALT');
+  });
+
+  test('should reject keys that are not a sided modifier', () => {
+    expect(parseModifierKey('cmd')).toBeUndefined();
+    expect(parseModifierKey('right space')).toBeUndefined();
+    expect(parseModifierKey('right cmd shift')).toBeUndefined();
+  });
+});
+
+describe('DoubleTapDetector', () => {
+  const tap = (detector: DoubleTapDetector, key: string, at: number) =>
+    [detector.handle(key, true, at), detector.handle(key, false, at + 50)];
+
+  test('should fire on two quick taps', () => {
+    const detector = new DoubleTapDetector('RIGHT META');
+
+    expect(tap(detector, 'RIGHT META', 0)).toEqual([false, false]);
+    expect(tap(detector, 'RIGHT META', 200)).toEqual([false, true]);
+  });
+
+  test('should ignore slow taps and held keys', () => {
+    const detector = new DoubleTapDetector('RIGHT META');
+    tap(detector, 'RIGHT META', 0);
+    expect(tap(detector, 'RIGHT META', 1000)).toEqual([false, false]);
+
+    detector.handle('RIGHT META', true, 2000);
+    detector.handle('RIGHT META', true, 2300);
+    expect(detector.handle('RIGHT META', false, 2600)).toBe(false);
+    expect(tap(detector, 'RIGHT META', 2700)).toEqual([false, false]);
+  });
+
+  test('should not fire when the modifier is used in a shortcut', () => {
+    const detector = new DoubleTapDetector('RIGHT META');
+    tap(detector, 'RIGHT META', 0);
+    detector.handle('C', true, 100);
+
+    expect(tap(detector, 'RIGHT META', 150)).toEqual([false, false]);
+  });
+});
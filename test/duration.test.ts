@@ -0,0 +1,43 @@
+import { formatDuration, formatUptime, parseDuration } from '../src/duration';
+
+describe('parseDuration', () => {
+  it('parses seconds, milliseconds and minutes', () => {
+    expect(parseDuration('2s')).toBe(2000);
+    expect(parseDuration('1.5s')).toBe(1500);
+    expect(parseDuration('500ms')).toBe(500);
+    expect(parseDuration('1m')).toBe(60000);
+  });
+
+  it('takes a bare number as seconds', () => {
+    expect(parseDuration('5')).toBe(5000);
+  });
+
+  it('rejects anything else', () => {
+    expect(parseDuration('')).toBeNaN();
+    expect(parseDuration('-1s')).toBeNaN();
+    expect(parseDuration('2h')).toBeNaN();
+    expect(parseDuration('soon')).toBeNaN();
+  });
+});
+
+describe('formatDuration', () => {
+  it('uses the shortest readable unit', () => {
+    expect(formatDuration(500)).toBe('500ms');
+    expect(formatDuration(2000)).toBe('2s');
+    expect(formatDuration(1500)).toBe('1.5s');
+  });
+});
+
+describe('formatUptime', () => {
+  it('shows the two largest units', () => {
+    expect(formatUptime(45000)).toBe('45s');
+    expect(formatUptime((12 * 60 + 5) * 1000)).toBe('12m 5s');
+    expect(formatUptime((3 * 3600 + 2 * 60 + 30) * 1000)).toBe('3h 2m');
+    expect(formatUptime((2 * 86400 + 4 * 3600) * 1000)).toBe('2d 4h');
+  });
+
+  it('skips empty units', () => {
+    expect(formatUptime(3600 * 1000 + 5000)).toBe('1h');
+    expect(formatUptime(0)).toBe('0s');
+  });
+});
@@ -0,0 +1,35 @@
+import {
+  AppError,
+  CaptureError,
+  ConfigError,
+  ExitCode,
+  PermissionDeniedError,
+  ProviderAuthError,
+  RateLimitError,
+  TimeoutError,
+  exitCodeFor,
+} from '../src/errors';
+
+describe('exitCodeFor', () => {
+  test('should map each error class to its documented exit code', () => {
+    expect(exitCodeFor(new ConfigError('bad config'))).toBe(2);
+    expect(exitCodeFor(new PermissionDeniedError('no access'))).toBe(3);
+    expect(exitCodeFor(new CaptureError('capture failed'))).toBe(4);
+    expect(exitCodeFor(new ProviderAuthError('invalid key'))).toBe(5);
+    expect(exitCodeFor(new RateLimitError('slow down'))).toBe(6);
+    expect(exitCodeFor(new TimeoutError('timed out'))).toBe(7);
+  });
+
+  test('should fall back to a general error for unknown errors', () => {
+    expect(exitCodeFor(new Error('boom'))).toBe(ExitCode.GeneralError);
+    expect(exitCodeFor('not an error')).toBe(ExitCode.GeneralError);
+  });
+
+  test('should keep the error name and message', () => {
+    const error = new RateLimitError('slow down');
+
+    expect(error).toBeInstanceOf(AppError);
+    expect(error.name).toBe('RateLimitError');
+    expect(error.message).toBe('slow down');
+  });
+});
@@ -0,0 +1,40 @@
+import { EventEmitter } from 'events';
+import { DaemonEvent, subscribeDaemonEvents } from '../src/event_server';
+
+describe('subscribeDaemonEvents', () => {
+  test('should translate a capture and a streamed analysis into messages', () => {
+    const events = new EventEmitter();
+    const sent: DaemonEvent[] = [];
+    subscribeDaemonEvents(events, event => sent.push(event));
+
+    events.emit('captureStart', 'screen');
+    events.emit('captureEnd', 'screen');
+    events.emit('analysisStart');
+    events.emit('token', 'Hello');
+    events.emit('analysisEnd', 'Hello');
+
+    expect(sent).toEqual([
+      { type: 'capture_start', source: 'screen' },
+      { type: 'capture_end', source: 'screen' },
+      { type: 'analysis_start' },
+      { type: 'token', text: 'Hello' },
+      { type: 'analysis_end', answer: 'Hello' },
+    ]);
+  });
+
+  test('should report failures and stop after unsubscribing', () => {
+    const events = new EventEmitter();
+    const sent: DaemonEvent[] = [];
+    const unsubscribe = subscribeDaemonEvents(events, event => sent.push(event));
+
+    events.emit('captureEnd', 'clipboard', new Error('No image on the clipboard'));
+    events.emit('analysisEnd', undefined, new Error('Analysis cancelled'));
+    unsubscribe();
+    events.emit('analysisStart');
+
+    expect(sent).toEqual([
+      { type: 'capture_error', source: 'clipboard', error: 'No image on the clipboard' },
+      { type: 'analysis_error', error: 'Analysis cancelled' },
+    ]);
+  });
+});
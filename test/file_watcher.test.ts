@@ -0,0 +1,40 @@
+import * as fs from 'fs/promises';
+import * as os from 'os';
+import * as path from 'path';
+import { watchFiles } from '../src/file_watcher';
+
+describe('watchFiles', () => {
+  let dir: string;
+
+  beforeEach(async () => {
+    dir = await fs.mkdtemp(path.join(os.tmpdir(), 'file-watcher-'));
+    await fs.writeFile(path.join(dir, 'config.toml'), 'model = "a"\n');
+  });
+
+  afterEach(async () => {
+    await fs.rm(dir, { recursive: true, force: true });
+  });
+
+  test('should report a burst of writes once', async () => {
+    let changes = 0;
+    const stop = watchFiles([path.join(dir, 'config.toml')], () => changes++, 50);
+
+    await fs.writeFile(path.join(dir, 'config.toml'), 'model = "b"\n');
+    await fs.appendFile(path.join(dir, 'config.toml'), 'stream = true\n');
+    await new Promise(resolve => setTimeout(resolve, 300));
+    stop();
+
+    expect(changes).toBe(1);
+  });
+
+  test('should ignore other files in the directory', async () => {
+    let changes = 0;
+    const stop = watchFiles([path.join(dir, 'config.toml')], () => changes++, 50);
+
+    await fs.writeFile(path.join(dir, 'daemon.sock'), '');
+    await new Promise(resolve => setTimeout(resolve, 300));
+    stop();
+
+    expect(changes).toBe(0);
+  });
+});
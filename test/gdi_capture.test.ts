@@ -0,0 +1,60 @@
+import { execFile } from 'child_process';
+import { CaptureError } from '../src/errors';
+import { captureGdiDisplay, captureGdiWindow, listGdiDisplays } from '../src/gdi_capture';
+
+jest.mock('child_process', () => ({ execFile: jest.fn() }));
+
+const mockExecFile = execFile as unknown as jest.Mock;
+
+function respond(stdout: string): void {
+  mockExecFile.mockImplementation((command, args, options, callback) => callback(null, { stdout, stderr: '' }));
+}
+
+describe('GDI capture', () => {
+  afterEach(() => {
+    mockExecFile.mockReset();
+  });
+
+  test('should run one PowerShell script without a profile, DPI-aware', async () => {
+    respond(`${Buffer.from('png').toString('base64')}\r\n`);
+
+    expect(await captureGdiDisplay()).toEqual(Buffer.from('png'));
+    const [command, args, options] = mockExecFile.mock.calls[0];
+    expect(command).toBe('powershell');
+    expect(args).toHaveLength(3);
+    expect(args.slice(0, 2)).toEqual(['-NoProfile', '-Command']);
+    expect(args[2]).toContain('SetProcessDpiAwarenessContext([IntPtr]-4)');
+    expect(args[2]).toContain('[System.Windows.Forms.Screen]::PrimaryScreen.Bounds');
+    expect(options.maxBuffer).toBeGreaterThan(1024 * 1024);
+  });
+
+  test('should capture a display by index and a window by handle', async () => {
+    respond(Buffer.from('png').toString('base64'));
+
+    expect(await captureGdiDisplay(2)).toEqual(Buffer.from('png'));
+    expect(await captureGdiWindow(132456)).toEqual(Buffer.from('png'));
+    expect(mockExecFile.mock.calls[0][1][2]).toContain('[System.Windows.Forms.Screen]::AllScreens[2].Bounds');
+    expect(mockExecFile.mock.calls[1][1][2]).toContain('[Native]::PrintWindow([IntPtr]132456, $hdc, 2)');
+  });
+
+  test('should list displays with their indexes as ids', async () => {
+    respond('[{"index":0,"name":"\\\\\\\\.\\\\DISPLAY1","width":3840,"height":2160,"scaleFactor":1.5}]');
+
+    expect(await listGdiDisplays()).toEqual([{ id: 0, index: 0, name: '\\\\.\\DISPLAY1', width: 3840, height: 2160, scaleFactor: 1.5 }]);
+  });
+
+  test('should report a failing script as a capture error', async () => {
+    mockExecFile.mockImplementation((command, args, options, callback) => callback(new Error('powershell not found')));
+
+    await expect(listGdiDisplays()).rejects.toThrow(CaptureError);
+    await expect(captureGdiDisplay()).rejects.toThrow(CaptureError);
+    await expect(captureGdiWindow(132456)).rejects.toThrow(CaptureError);
+  });
+
+  test('should reject empty output instead of returning an empty image', async () => {
+    respond('\r\n');
+
+    await expect(captureGdiDisplay(1)).rejects.toThrow('Failed to capture display 1: PowerShell returned no image');
+    await expect(captureGdiWindow(132456)).rejects.toThrow(CaptureError);
+  });
+});
@@ -0,0 +1,30 @@
+import { gistImageNames, gistMarkdown } from '../src/gist';
+
+describe('gistImageNames', () => {
+  test('should name the screenshots, numbering several', () => {
+    expect(gistImageNames(['/h/history/7-1.png'])).toEqual(['screenshot.png']);
+    expect(gistImageNames(['/h/history/7-1.jpg', '/h/history/7-2.jpg'])).toEqual(['screenshot-1.jpg', 'screenshot-2.jpg']);
+  });
+});
+
+describe('gistMarkdown', () => {
+  const entry = { id: 7, at: '2025-01-31T14:05:09.000Z', prompt: 'Why is this failing?', answer: 'The assertion is wrong.', model: 'mock / mock' };
+
+  test('should hold the question, model, screenshot and answer', () => {
+    const markdown = gistMarkdown(entry, ['https://gist.githubusercontent.com/octocat/abc/raw/screenshot.png']);
+    expect(markdown.startsWith('# Why is this failing?\n\n*')).toBe(true);
+    expect(markdown).toContain('`mock / mock`');
+    expect(markdown).toContain('![screenshot](https://gist.githubusercontent.com/octocat/abc/raw/screenshot.png)\n\nThe assertion is wrong.\n');
+  });
+
+  test('should add the follow-ups as sections', () => {
+    const markdown = gistMarkdown({ ...entry, turns: [{ at: '2025-01-31T14:07:00.000Z', question: 'Which line?', answer: 'Line 12.' }] });
+    expect(markdown.endsWith('The assertion is wrong.\n\n## Which line?\n\nLine 12.\n')).toBe(true);
+  });
+
+  test('should use the default question without screenshots', () => {
+    const markdown = gistMarkdown({ ...entry, prompt: undefined });
+    expect(markdown.startsWith('# What is on screen?')).toBe(true);
+    expect(markdown).not.toContain('![');
+  });
+});
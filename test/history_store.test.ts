@@ -0,0 +1,133 @@
+import * as fs from 'fs/promises';
+import * as os from 'os';
+import * as path from 'path';
+import { HistoryStore, firstLine, searchHistory, threadTurns } from '../src/history_store';
+
+describe('HistoryStore', () => {
+  let dir: string;
+
+  beforeEach(async () => {
+    dir = await fs.mkdtemp(path.join(os.tmpdir(), 'history-'));
+  });
+
+  afterEach(async () => {
+    await fs.rm(dir, { recursive: true });
+  });
+
+  test('should be empty before the first answer', async () => {
+    expect(await new HistoryStore(dir).list()).toEqual([]);
+  });
+
+  test('should number answers and find them again', async () => {
+    const store = new HistoryStore(dir);
+    await store.add({ at: '2025-01-31T14:05:09.000Z', prompt: 'Why is this failing?', answer: 'The assertion is wrong.', model: 'mock / mock' });
+    await store.add({ at: '2025-01-31T14:06:00.000Z', answer: 'A login form.' });
+
+    expect((await new HistoryStore(dir).list()).map(entry => entry.id)).toEqual([1, 2]);
+    expect((await store.get(1))?.prompt).toBe('Why is this failing?');
+    expect(await store.get(3)).toBeUndefined();
+  });
+
+  test('should keep only the newest entries without reusing numbers', async () => {
+    const store = new HistoryStore(dir, 2);
+    for (const answer of ['one', 'two', 'three']) {
+      await store.add({ at: '2025-01-31T14:05:09.000Z', answer });
+    }
+
+    expect((await store.list()).map(entry => [entry.id, entry.answer])).toEqual([[2, 'two'], [3, 'three']]);
+  });
+
+  test('should keep the images sent and delete them with their entry', async () => {
+    const store = new HistoryStore(dir, 1);
+    const png = Buffer.concat([Buffer.from([0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a]), Buffer.alloc(8)]);
+    const first = await store.add({ at: '2025-01-31T14:05:09.000Z', answer: 'one' }, [png, Buffer.from([0xff, 0xd8, 0xff, 0xe0])]);
+
+    expect(first?.images).toEqual(['1-1.png', '1-2.jpg']);
+    expect(store.imagePaths(first!)).toEqual([path.join(dir, 'history', '1-1.png'), path.join(dir, 'history', '1-2.jpg')]);
+    expect(await fs.readFile(store.imagePaths(first!)[0])).toEqual(png);
+
+    await store.add({ at: '2025-01-31T14:06:00.000Z', answer: 'two' });
+    expect(await fs.readdir(path.join(dir, 'history'))).toEqual([]);
+    expect((await store.last())?.answer).toBe('two');
+  });
+
+  test('should keep only the text when images aren\'t kept', async () => {
+    const store = new HistoryStore(dir, 10, false);
+    const entry = await store.add({ at: '2025-01-31T14:05:09.000Z', answer: 'one' }, [Buffer.from([0xff, 0xd8, 0xff, 0xe0])]);
+
+    expect(entry?.images).toBeUndefined();
+    expect(store.imagePaths(entry!)).toEqual([]);
+    await expect(fs.access(path.join(dir, 'history'))).rejects.toThrow();
+    expect((await store.get(1))?.answer).toBe('one');
+  });
+
+  test('should add follow-ups to an entry\'s thread', async () => {
+    const store = new HistoryStore(dir);
+    await store.add({ at: '2025-01-31T14:05:09.000Z', prompt: 'Why is this failing?', answer: 'The assertion is wrong.' });
+
+    expect(await store.addTurn(1, { at: '2025-01-31T14:07:00.000Z', question: 'Which line?', answer: 'Line 12.' })).toBe(true);
+    expect(await store.addTurn(2, { at: '2025-01-31T14:07:00.000Z', question: 'Which line?', answer: 'Line 12.' })).toBe(false);
+    expect((await store.get(1))?.turns).toEqual([{ at: '2025-01-31T14:07:00.000Z', question: 'Which line?', answer: 'Line 12.' }]);
+  });
+
+  test('should keep every change saved at the same time', async () => {
+    const store = new HistoryStore(dir);
+    await store.add({ at: '2025-01-31T14:05:09.000Z', answer: 'one' });
+
+    await Promise.all([
+      store.add({ at: '2025-01-31T14:06:00.000Z', answer: 'two' }),
+      store.addTurn(1, { at: '2025-01-31T14:06:01.000Z', question: 'Which line?', answer: 'Line 12.' }),
+      new HistoryStore(dir).add({ at: '2025-01-31T14:06:02.000Z', answer: 'three' }),
+      new HistoryStore(dir).addTurn(1, { at: '2025-01-31T14:06:03.000Z', question: 'Why?', answer: 'Off by one.' }),
+    ]);
+
+    const entries = await store.list();
+    expect(entries.map(entry => [entry.id, entry.answer])).toEqual([[1, 'one'], [2, 'two'], [3, 'three']]);
+    expect(entries[0].turns?.map(turn => turn.question)).toEqual(['Which line?', 'Why?']);
+  });
+
+  test('should keep nothing with a size of 0', async () => {
+    const store = new HistoryStore(dir, 0);
+    expect(await store.add({ at: '2025-01-31T14:05:09.000Z', answer: 'one' })).toBeUndefined();
+    expect(await store.list()).toEqual([]);
+  });
+});
+
+describe('searchHistory', () => {
+  const entries = [
+    { id: 1, at: '2025-01-31T14:05:09.000Z', prompt: 'What does this regex match?', answer: 'Dates like 2025-01-31.' },
+    { id: 2, at: '2025-01-31T14:06:00.000Z', answer: 'A **TypeError**: x is undefined.' },
+    { id: 3, at: '2025-01-31T14:07:00.000Z', answer: 'A login form.', turns: [{ at: '2025-01-31T14:08:00.000Z', question: 'Is it accessible?', answer: 'The labels are missing.' }] },
+  ];
+
+  test('should match questions and answers, ignoring case', () => {
+    expect(searchHistory(entries, 'typeerror').map(entry => entry.id)).toEqual([2]);
+    expect(searchHistory(entries, 'REGEX').map(entry => entry.id)).toEqual([1]);
+    expect(searchHistory(entries, 'segfault')).toEqual([]);
+  });
+
+  test('should match follow-ups too', () => {
+    expect(searchHistory(entries, 'labels').map(entry => entry.id)).toEqual([3]);
+  });
+});
+
+describe('threadTurns', () => {
+  test('should start with the first answer and alternate after it', () => {
+    expect(threadTurns({
+      id: 1, at: '2025-01-31T14:05:09.000Z', prompt: 'Why is this failing?', answer: 'The assertion is wrong.',
+      turns: [{ at: '2025-01-31T14:07:00.000Z', question: 'Which line?', answer: 'Line 12.' }],
+    })).toEqual([
+      { role: 'assistant', text: 'The assertion is wrong.' },
+      { role: 'user', text: 'Which line?' },
+      { role: 'assistant', text: 'Line 12.' },
+    ]);
+  });
+});
+
+describe('firstLine', () => {
+  test('should skip blank lines and code fences', () => {
+    expect(firstLine('\n\n  The fix:  \nmore')).toBe('The fix:');
+    expect(firstLine('```ts\nconst a = 1;\n```')).toBe('const a = 1;');
+    expect(firstLine('')).toBe('');
+  });
+});
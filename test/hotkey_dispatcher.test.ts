@@ -0,0 +1,42 @@
+import { HotkeyDispatcher, defaultBindings } from '../src/hotkey_dispatcher';
+
+describe('HotkeyDispatcher', () => {
+  const press = (dispatcher: HotkeyDispatcher, keys: string[], at = 0) =>
+    keys.map(key => dispatcher.handle(key, true, at)).find(binding => binding)?.source ?? null;
+
+  test('should trigger on a chord with either side of each modifier', () => {
+    const dispatcher = new HotkeyDispatcher(defaultBindings('darwin'));
+
+    expect(press(dispatcher, ['RIGHT META', 'LEFT SHIFT', 'SPACE'])).toBe('screen');
+  });
+
+  test('should prefer the most specific chord', () => {
+    const dispatcher = new HotkeyDispatcher(defaultBindings('linux'));
+
+    expect(press(dispatcher, ['LEFT CTRL', 'LEFT SHIFT', 'LEFT ALT', 'SPACE'])).toBe('clipboard');
+    expect(press(dispatcher, ['LEFT CTRL', 'LEFT SHIFT', 'R'])).toBe('region');
+  });
+
+  test('should not trigger once a key is released', () => {
+    const dispatcher = new HotkeyDispatcher(defaultBindings('linux'));
+    press(dispatcher, ['LEFT CTRL', 'LEFT SHIFT']);
+    dispatcher.handle('LEFT SHIFT', false, 0);
+
+    expect(press(dispatcher, ['SPACE'])).toBeNull();
+  });
+
+  test('should forget keys whose release was missed', () => {
+    const dispatcher = new HotkeyDispatcher(defaultBindings('linux'));
+    press(dispatcher, ['LEFT CTRL', 'LEFT SHIFT'], 0);
+
+    expect(press(dispatcher, ['SPACE'], 10000)).toBeNull();
+  });
+
+  test('should trigger a screen capture on a double-tapped modifier', () => {
+    const dispatcher = new HotkeyDispatcher(defaultBindings('darwin'), 'right cmd');
+    const tap = (at: number) => [dispatcher.handle('RIGHT META', true, at), dispatcher.handle('RIGHT META', false, at + 50)];
+
+    expect(tap(0)).toEqual([null, null]);
+    expect(tap(200)[1]).toMatchObject({ source: 'screen', label: 'double-tap right cmd' });
+  });
+});
@@ -0,0 +1,31 @@
+import { changedRegions } from '../src/image_diff';
+
+describe('changedRegions', () => {
+  const blank = (width: number, height: number) => Buffer.alloc(width * height * 3);
+  const paint = (raw: Buffer, width: number, x: number, y: number) => {
+    raw.fill(255, (y * width + x) * 3, (y * width + x) * 3 + 3);
+  };
+
+  test('should find nothing in identical images', () => {
+    expect(changedRegions(blank(64, 64), blank(64, 64), 64, 64)).toEqual([]);
+  });
+
+  test('should merge touching changes and keep distant ones apart', () => {
+    const after = blank(64, 64);
+    paint(after, 64, 2, 2);
+    paint(after, 64, 20, 3);
+    paint(after, 64, 60, 60);
+
+    expect(changedRegions(blank(64, 64), after, 64, 64)).toEqual([
+      { x: 0, y: 0, width: 32, height: 16 },
+      { x: 48, y: 48, width: 16, height: 16 },
+    ]);
+  });
+
+  test('should clip regions to the image', () => {
+    const after = blank(20, 20);
+    paint(after, 20, 19, 19);
+
+    expect(changedRegions(blank(20, 20), after, 20, 20)).toEqual([{ x: 16, y: 16, width: 4, height: 4 }]);
+  });
+});
@@ -0,0 +1,24 @@
+import { parseUiElements } from '../src/inspect';
+
+describe('parseUiElements', () => {
+  test('should convert percentage boxes to capture pixels', () => {
+    const answer = JSON.stringify({ elements: [{ type: 'button', label: 'Sign in', box: [50, 10, 25, 5] }] });
+
+    expect(parseUiElements(answer, 1600, 1000)).toEqual([
+      { type: 'button', label: 'Sign in', x: 800, y: 100, width: 400, height: 50 },
+    ]);
+  });
+
+  test('should tolerate a code fence and drop elements without a box', () => {
+    const answer = '```json\n{"elements": [{"type": "link", "label": "Help", "box": [0, 0, 10]}, ' +
+      '{"type": "label", "label": "Name", "box": [-5, 20, 10, 120]}]}\n```';
+
+    expect(parseUiElements(answer, 200, 100)).toEqual([
+      { type: 'label', label: 'Name', x: 0, y: 20, width: 20, height: 100 },
+    ]);
+  });
+
+  test('should treat a missing list as no elements', () => {
+    expect(parseUiElements('{}', 100, 100)).toEqual([]);
+  });
+});
@@ -0,0 +1,12 @@
+import { keychainCommand } from '../src/keychain';
+
+describe('keychainCommand', () => {
+  test('should use the login keychain on macOS and the Secret Service on Linux', () => {
+    expect(keychainCommand('github', 'darwin')).toEqual(['security', ['find-generic-password', '-s', 'ai-screenshot-analyzer', '-a', 'github', '-w']]);
+    expect(keychainCommand('github', 'linux')).toEqual(['secret-tool', ['lookup', 'service', 'ai-screenshot-analyzer', 'account', 'github']]);
+  });
+
+  test('should have no keychain elsewhere', () => {
+    expect(keychainCommand('github', 'win32')).toBeUndefined();
+  });
+});
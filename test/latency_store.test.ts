@@ -0,0 +1,44 @@
+import * as fs from 'fs/promises';
+import * as os from 'os';
+import * as path from 'path';
+import { LatencyStore } from '../src/latency_store';
+
+describe('LatencyStore', () => {
+  let dir: string;
+
+  beforeEach(async () => {
+    dir = await fs.mkdtemp(path.join(os.tmpdir(), 'latencies-'));
+  });
+
+  afterEach(async () => {
+    await fs.rm(dir, { recursive: true });
+  });
+
+  test('should know nothing before the first answer', async () => {
+    expect(await new LatencyStore(dir).expected('claude/claude-3-5-sonnet-20241022')).toBeUndefined();
+  });
+
+  test('should expect the median of recent latencies per model', async () => {
+    const store = new LatencyStore(dir);
+    for (const ms of [3000, 9000, 2000]) {
+      await store.record('claude/claude-3-5-sonnet-20241022', ms);
+    }
+    await store.record('openai/gpt-4o-mini', 1000);
+
+    expect(await store.expected('claude/claude-3-5-sonnet-20241022')).toBe(3000);
+    await store.record('claude/claude-3-5-sonnet-20241022', 4000);
+    expect(await new LatencyStore(dir).expected('claude/claude-3-5-sonnet-20241022')).toBe(3500);
+    expect(await store.expected('openai/gpt-4o-mini')).toBe(1000);
+  });
+
+  test('should keep only the 20 most recent latencies', async () => {
+    const store = new LatencyStore(dir);
+    for (let i = 0; i < 25; i++) {
+      await store.record('mock/mock', i < 5 ? 60000 : 1000);
+    }
+
+    expect(await store.expected('mock/mock')).toBe(1000);
+    const saved = JSON.parse(await fs.readFile(path.join(dir, 'latencies.json'), 'utf8'));
+    expect(saved['mock/mock']).toHaveLength(20);
+  });
+});
@@ -0,0 +1,49 @@
+import * as fs from 'fs/promises';
+import * as os from 'os';
+import * as path from 'path';
+import { LogFile } from '../src/log_file';
+
+describe('LogFile', () => {
+  let dir: string;
+
+  beforeEach(async () => {
+    dir = await fs.mkdtemp(path.join(os.tmpdir(), 'log-file-'));
+  });
+
+  afterEach(async () => {
+    await fs.rm(dir, { recursive: true });
+  });
+
+  test('should append to an existing log', async () => {
+    const file = path.join(dir, 'daemon.log');
+    await fs.writeFile(file, 'earlier\n');
+
+    const log = new LogFile(file);
+    log.write('started\n');
+
+    // Written before close(), so a process.exit() right after loses nothing
+    expect(await fs.readFile(file, 'utf8')).toBe('earlier\nstarted\n');
+    log.close();
+  });
+
+  test('should start a new file on reopen after the old one was rotated', async () => {
+    const file = path.join(dir, 'logs', 'daemon.log');
+    const log = new LogFile(file);
+    log.write('before rotation\n');
+    await fs.rename(file, `${file}.1`);
+    log.write('still the old file\n');
+
+    log.reopen();
+    log.write('after rotation\n');
+    log.close();
+
+    expect(await fs.readFile(`${file}.1`, 'utf8')).toBe('before rotation\nstill the old file\n');
+    expect(await fs.readFile(file, 'utf8')).toBe('after rotation\n');
+  });
+
+  test('should leave SIGHUP to the daemon', () => {
+    const log = new LogFile(path.join(dir, 'capture.log'));
+    expect(process.listenerCount('SIGHUP')).toBe(0);
+    log.close();
+  });
+});
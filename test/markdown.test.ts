@@ -0,0 +1,113 @@
+import { MarkdownRenderer, MarkdownStyles, renderInline, renderTable, visibleWidth, wrapVisible } from '../src/markdown';
+
+const tag = (name: string) => (text: string) => `<${name}>${text}</${name}>`;
+const identity = (text: string) => text;
+
+const TAGGED: MarkdownStyles = {
+  text: identity,
+  heading: (text, level) => `<h${level}>${text}</h${level}>`,
+  bold: tag('b'),
+  italic: tag('i'),
+  inlineCode: tag('code'),
+  link: (text, url) => `<a ${url}>${text}</a>`,
+  bullet: identity,
+  quote: tag('q'),
+  rule: identity,
+  tableBorder: identity,
+  fence: tag('fence'),
+  code: tag('pre')
+};
+
+// No markup, so table widths line up
+const PLAIN: MarkdownStyles = { ...TAGGED, bold: identity, heading: identity, inlineCode: identity };
+
+describe('renderInline', () => {
+  test('should style bold, italics, links and code spans', () => {
+    expect(renderInline('**bold**, *it* and _it_ with `**code**` and [docs](https://x.io)', TAGGED))
+      .toBe('<b>bold</b>, <i>it</i> and <i>it</i> with <code>**code**</code> and <a https://x.io>docs</a>');
+  });
+
+  test('should leave snake_case and arithmetic alone', () => {
+    expect(renderInline('snake_case_name is 2 * 3 * 4', TAGGED)).toBe('snake_case_name is 2 * 3 * 4');
+  });
+});
+
+describe('renderTable', () => {
+  test('should draw a box with aligned columns', () => {
+    expect(renderTable(['| Name | Qty |', '|:-----|----:|', '| apple | 3 |', '| kiwi | 12 |'], PLAIN)).toEqual([
+      '┌───────┬─────┐',
+      '│ Name  │ Qty │',
+      '├───────┼─────┤',
+      '│ apple │   3 │',
+      '│ kiwi  │  12 │',
+      '└───────┴─────┘'
+    ]);
+  });
+
+  test('should size columns by visible width', () => {
+    expect(visibleWidth('\x1B[1mbold\x1B[22m')).toBe(4);
+  });
+});
+
+describe('MarkdownRenderer', () => {
+  test('should render block elements', () => {
+    const renderer = new MarkdownRenderer(TAGGED);
+    const lines = ['# Title ##', '- item', '  * nested', '- [ ] todo', '2) second', '> note', '***'].flatMap(line => renderer.line(line));
+    expect(lines).toEqual(['<h1>Title</h1>', '• item', '  • nested', '☐ todo', '2) second', '<q>│ note</q>', '─'.repeat(40)]);
+  });
+
+  test('should keep code blocks as they are, fences included', () => {
+    const renderer = new MarkdownRenderer(TAGGED);
+    const lines = ['```python', '# comment', '| a | b |', '```'].flatMap(line => renderer.line(line));
+    expect(lines).toEqual(['<fence>```python</fence>', '<pre># comment</pre>', '<pre>| a | b |</pre>', '<fence>```</fence>']);
+  });
+
+  test('should hold a table back until it ends', () => {
+    const renderer = new MarkdownRenderer(PLAIN);
+    expect(renderer.line('| a |')).toEqual([]);
+    expect(renderer.line('| b |')).toEqual([]);
+    expect(renderer.line('done')).toEqual(['┌───┐', '│ a │', '│ b │', '└───┘', 'done']);
+    expect(renderer.line('| c |')).toEqual([]);
+    expect(renderer.end()).toEqual(['┌───┐', '│ c │', '└───┘']);
+  });
+});
+
+describe('wrapVisible', () => {
+  test('should wrap at word boundaries, ignoring escape codes', () => {
+    expect(wrapVisible('The quick brown fox jumps over the lazy dog', 16)).toEqual(['The quick brown', 'fox jumps over', 'the lazy dog']);
+    expect(wrapVisible('\x1B[1mThe quick\x1B[22m brown fox', 10, '  ')).toEqual(['\x1B[1mThe quick\x1B[22m', '  brown', '  fox']);
+  });
+
+  test('should keep words longer than the line whole', () => {
+    expect(wrapVisible('see https://example.com/a/very/long/path ok', 10)).toEqual(['see', 'https://example.com/a/very/long/path', 'ok']);
+  });
+});
+
+describe('MarkdownRenderer with a width', () => {
+  test('should wrap prose with hanging indents but never code', () => {
+    const renderer = new MarkdownRenderer(PLAIN, 20);
+    const lines = [
+      '- first item that is long enough to wrap',
+      '  2. nested numbered item wraps too',
+      '> a quoted remark that wraps',
+      '---',
+      '```',
+      'const aVeryLongLineOfCodeThatMustNotWrap = 1;',
+      '```'
+    ].flatMap(line => renderer.line(line));
+
+    expect(lines).toEqual([
+      '• first item that is',
+      '  long enough to',
+      '  wrap',
+      '  2. nested numbered',
+      '     item wraps too',
+      '<q>│ a quoted remark</q>',
+      '<q>│ that wraps</q>',
+      '─'.repeat(20),
+      '<fence>```</fence>',
+      '<pre>const aVeryLongLineOfCodeThatMustNotWrap = 1;</pre>',
+      '<fence>```</fence>'
+    ]);
+  });
+});
@@ -0,0 +1,38 @@
+import { alerterArgs, clickedAction, notificationSummary } from '../src/notification';
+
+describe('notificationSummary', () => {
+  test('should flatten the answer to one plain line', () => {
+    expect(notificationSummary('## Fix\n\nUse this:\n```ts\nconst a = 1;\nconst b = 2;\n```\nDone.'))
+      .toBe('Fix Use this: (ts code, 2 lines, shown on screen.) Done.');
+  });
+
+  test('should cut long answers short', () => {
+    expect(notificationSummary('The button is disabled because the form is invalid.', 20)).toBe('The button is disab…');
+  });
+});
+
+describe('alerterArgs', () => {
+  test('should list the buttons as actions', () => {
+    expect(alerterArgs('Done.')).toEqual([
+      '-title', 'AI Screenshot Analyzer', '-message', 'Done.', '-group', 'ai-screenshot-analyzer',
+      '-actions', 'Copy code,Open in editor,Ask follow-up', '-closeLabel', 'Dismiss', '-timeout', '600',
+    ]);
+  });
+
+  test('should show a plain banner without buttons', () => {
+    expect(alerterArgs('Done.', [])).toEqual(['-title', 'AI Screenshot Analyzer', '-message', 'Done.', '-group', 'ai-screenshot-analyzer']);
+  });
+});
+
+describe('clickedAction', () => {
+  test('should map the clicked label to its action', () => {
+    expect(clickedAction('Copy code\n')).toBe('copy-code');
+    expect(clickedAction('Ask follow-up')).toBe('follow-up');
+  });
+
+  test('should ignore dismissals and clicks on the banner', () => {
+    expect(clickedAction('@CLOSED')).toBeUndefined();
+    expect(clickedAction('@TIMEOUT')).toBeUndefined();
+    expect(clickedAction('@CONTENTCLICKED')).toBeUndefined();
+  });
+});
@@ -0,0 +1,58 @@
+import { execFile } from 'child_process';
+import sharp from 'sharp';
+import { CaptureError } from '../src/errors';
+import { ocrContext, ocrText, runTesseract } from '../src/ocr';
+
+jest.mock('child_process', () => ({ execFile: jest.fn() }));
+
+const mockExecFile = execFile as unknown as jest.Mock;
+
+describe('runTesseract', () => {
+  let image: Buffer;
+
+  beforeAll(async () => {
+    image = await sharp({ create: { width: 4, height: 4, channels: 3, background: '#ffffff' } }).png().toBuffer();
+  });
+
+  afterEach(() => {
+    mockExecFile.mockReset();
+  });
+
+  test('should read words with their boxes as tsv', async () => {
+    mockExecFile.mockImplementation((command, args, options, callback) => callback(null, { stdout: 'level\ttext\n', stderr: '' }));
+
+    expect(await runTesseract(image, 'tsv', 'OCR needed')).toBe('level\ttext\n');
+    expect(mockExecFile.mock.calls[0][0]).toBe('tesseract');
+    expect(mockExecFile.mock.calls[0][1].slice(1)).toEqual(['stdout', 'tsv']);
+  });
+
+  test('should fail with the caller\'s message', async () => {
+    mockExecFile.mockImplementation((command, args, options, callback) => callback(Object.assign(new Error('spawn tesseract ENOENT'), { code: 'ENOENT' })));
+
+    const failure = runTesseract(image, 'tsv', 'PII redaction needs tesseract installed (redact_pii is on)');
+    await expect(failure).rejects.toThrow(CaptureError);
+    await expect(failure).rejects.toThrow('PII redaction needs tesseract installed (redact_pii is on): Error: spawn tesseract ENOENT');
+  });
+
+  test('should collapse the blank lines between blocks', async () => {
+    mockExecFile.mockImplementation((command, args, options, callback) => callback(null, { stdout: '\nERROR at line 3\n\n\n\nStack trace\n\f', stderr: '' }));
+
+    expect(await ocrText(image)).toBe('ERROR at line 3\n\nStack trace');
+    expect(mockExecFile.mock.calls[0][1].slice(1)).toEqual(['stdout']);
+  });
+
+  test('should say --ocr-only needs tesseract', async () => {
+    mockExecFile.mockImplementation((command, args, options, callback) => callback(new Error('spawn tesseract ENOENT')));
+
+    await expect(ocrText(image)).rejects.toThrow(/^--ocr-only needs tesseract installed/);
+  });
+});
+
+describe('ocrContext', () => {
+  test('should tell the model it gets text instead of an image', () => {
+    const context = ocrContext('npm ERR! code E404');
+
+    expect(context).toContain('No image is attached');
+    expect(context).toContain('"""\nnpm ERR! code E404\n"""');
+  });
+});
@@ -0,0 +1,14 @@
+import { openCommand } from '../src/open_path';
+
+describe('openCommand', () => {
+  test('should pass the target as a single argument', () => {
+    expect(openCommand('/tmp/answer.md', 'darwin')).toEqual(['open', ['/tmp/answer.md']]);
+    expect(openCommand('https://example.com/?a=1', 'linux')).toEqual(['xdg-open', ['https://example.com/?a=1']]);
+  });
+
+  test('should not go through cmd on Windows', () => {
+    const url = 'shortcuts://done?result=a&calc.exe|whoami';
+
+    expect(openCommand(url, 'win32')).toEqual(['rundll32', ['url.dll,FileProtocolHandler', url]]);
+  });
+});
@@ -0,0 +1,73 @@
+import * as fs from 'fs/promises';
+import * as os from 'os';
+import * as path from 'path';
+import { colorEnabled, isOutputFormat, tokenCounts, writeJson, writeOutFile } from '../src/output';
+import { registerSecret } from '../src/redact';
+
+describe('isOutputFormat', () => {
+  test('should accept text and json only', () => {
+    expect(isOutputFormat('json')).toBe(true);
+    expect(isOutputFormat('text')).toBe(true);
+    expect(isOutputFormat('yaml')).toBe(false);
+  });
+});
+
+describe('tokenCounts', () => {
+  test('should report input and output tokens, or null without usage', () => {
+    expect(tokenCounts({ inputTokens: 1630, outputTokens: 87, cacheReadTokens: 1200 })).toEqual({ input: 1630, output: 87 });
+    expect(tokenCounts(null)).toBeNull();
+  });
+});
+
+describe('writeJson', () => {
+  test('should write one redacted line to stdout', () => {
+    const write = jest.spyOn(process.stdout, 'write').mockImplementation(() => true);
+    registerSecret('output-test-secret-key');
+
+    writeJson({ answer: 'The key is output-test-secret-key\nsee above' });
+
+    expect(write).toHaveBeenCalledWith('{"answer":"The key is [REDACTED]\\nsee above"}\n');
+    write.mockRestore();
+  });
+});
+
+describe('writeOutFile', () => {
+  test('should create missing directories and end the file with a newline', async () => {
+    const dir = await fs.mkdtemp(path.join(os.tmpdir(), 'out-file-'));
+    const file = path.join(dir, 'nested', 'answer.md');
+
+    await writeOutFile(file, '# Answer\nIt works');
+
+    expect(await fs.readFile(file, 'utf8')).toBe('# Answer\nIt works\n');
+    await fs.rm(dir, { recursive: true });
+  });
+
+  test('should replace the previous answer and redact secrets', async () => {
+    const dir = await fs.mkdtemp(path.join(os.tmpdir(), 'out-file-'));
+    const file = path.join(dir, 'answer.json');
+    registerSecret('out-file-secret-key');
+
+    await writeOutFile(file, 'first\n');
+    await writeOutFile(file, '{"answer":"out-file-secret-key"}\n');
+
+    expect(await fs.readFile(file, 'utf8')).toBe('{"answer":"[REDACTED]"}\n');
+    await fs.rm(dir, { recursive: true });
+  });
+});
+
+describe('colorEnabled', () => {
+  test('should color a terminal only', () => {
+    expect(colorEnabled({}, true)).toBe(true);
+    expect(colorEnabled({}, false)).toBe(false);
+    expect(colorEnabled({}, undefined)).toBe(false);
+  });
+
+  test('should honor NO_COLOR, FORCE_COLOR and TERM=dumb', () => {
+    expect(colorEnabled({ NO_COLOR: '1' }, true)).toBe(false);
+    expect(colorEnabled({ NO_COLOR: '1', FORCE_COLOR: '1' }, true)).toBe(false);
+    expect(colorEnabled({ NO_COLOR: '' }, true)).toBe(true);
+    expect(colorEnabled({ FORCE_COLOR: '1' }, false)).toBe(true);
+    expect(colorEnabled({ FORCE_COLOR: '0' }, true)).toBe(false);
+    expect(colorEnabled({ TERM: 'dumb' }, true)).toBe(false);
+  });
+});
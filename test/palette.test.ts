@@ -0,0 +1,40 @@
+import { dominantColors, paletteTokens } from '../src/palette';
+
+describe('dominantColors', () => {
+  const pixels = (...runs: Array<[number, number[]]>) =>
+    Buffer.from(runs.flatMap(([count, rgb]) => Array(count).fill(rgb).flat()));
+
+  test('should rank colors by how much of the image they cover', () => {
+    const raw = pixels([10, [255, 255, 255]], [30, [30, 30, 30]], [5, [255, 0, 0]]);
+
+    expect(dominantColors(raw, 3, 8)).toEqual([
+      { hex: '#1e1e1e', rgb: [30, 30, 30], share: 30 / 45 },
+      { hex: '#ffffff', rgb: [255, 255, 255], share: 10 / 45 },
+      { hex: '#ff0000', rgb: [255, 0, 0], share: 5 / 45 },
+    ]);
+  });
+
+  test('should merge near-identical shades', () => {
+    const raw = pixels([3, [0, 100, 200]], [1, [16, 116, 216]]);
+
+    expect(dominantColors(raw, 3, 8)).toEqual([{ hex: '#0c70d4', rgb: [12, 112, 212], share: 1 }]);
+  });
+
+  test('should return at most count colors and skip alpha', () => {
+    const raw = Buffer.from([255, 0, 0, 255, 0, 255, 0, 255, 0, 0, 255, 255]);
+
+    expect(dominantColors(raw, 4, 2).map(color => color.hex)).toEqual(['#ff0000', '#00ff00']);
+  });
+});
+
+describe('paletteTokens', () => {
+  const colors = [{ hex: '#1e1e1e', rgb: [30, 30, 30] as [number, number, number], share: 2 / 3 }];
+
+  test('should write CSS custom properties for .css files', () => {
+    expect(paletteTokens(colors, 'tokens.css')).toBe(':root {\n  --color-1: #1e1e1e;\n}\n');
+  });
+
+  test('should write JSON otherwise', () => {
+    expect(JSON.parse(paletteTokens(colors, 'palette.json'))).toEqual([{ hex: '#1e1e1e', rgb: [30, 30, 30], share: 0.667 }]);
+  });
+});
@@ -0,0 +1,38 @@
+import { otherWindowFocused, pasteKeystrokeCommands } from '../src/paste';
+
+describe('pasteKeystrokeCommands', () => {
+  test('should press Cmd+V on macOS and Ctrl+V on Windows', () => {
+    expect(pasteKeystrokeCommands('darwin', {})).toEqual([['osascript', ['-e', 'tell application "System Events" to keystroke "v" using command down']]]);
+    expect(pasteKeystrokeCommands('win32', {})[0][0]).toBe('powershell');
+  });
+
+  test('should prefer wtype on Wayland and use xdotool on X11', () => {
+    expect(pasteKeystrokeCommands('linux', { WAYLAND_DISPLAY: 'wayland-0' }).map(([command]) => command)).toEqual(['wtype', 'xdotool']);
+    expect(pasteKeystrokeCommands('linux', {})).toEqual([['xdotool', ['key', '--clearmodifiers', 'ctrl+v']]]);
+  });
+});
+
+describe('otherWindowFocused', () => {
+  test('should be ready at once when another window has focus', async () => {
+    const onWait = jest.fn();
+    expect(await otherWindowFocused(async () => false, 1000, 1, onWait)).toBe(true);
+    expect(onWait).not.toHaveBeenCalled();
+  });
+
+  test('should wait for the terminal to lose focus', async () => {
+    const focused = jest.fn().mockResolvedValueOnce(true).mockResolvedValueOnce(true).mockResolvedValue(false);
+    const onWait = jest.fn();
+    expect(await otherWindowFocused(focused, 1000, 1, onWait)).toBe(true);
+    expect(onWait).toHaveBeenCalledTimes(1);
+    expect(focused).toHaveBeenCalledTimes(3);
+  });
+
+  test('should give up when the terminal keeps focus', async () => {
+    expect(await otherWindowFocused(async () => true, 5, 1)).toBe(false);
+  });
+
+  test('should not be ready when focus is unknown', async () => {
+    expect(await otherWindowFocused(async () => undefined, 1000, 1)).toBeUndefined();
+    expect(await otherWindowFocused(jest.fn().mockResolvedValueOnce(true).mockResolvedValue(undefined), 1000, 1)).toBeUndefined();
+  });
+});
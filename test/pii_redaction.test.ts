@@ -0,0 +1,50 @@
+import { OcrWord, findPii, parseTesseractTsv, passesLuhn } from '../src/pii_redaction';
+
+describe('parseTesseractTsv', () => {
+  test('should keep word rows with text', () => {
+    const tsv = [
+      'level\tpage_num\tblock_num\tpar_num\tline_num\tword_num\tleft\ttop\twidth\theight\tconf\ttext',
+      '4\t1\t1\t1\t1\t0\t10\t20\t300\t16\t-1\t',
+      '5\t1\t1\t1\t1\t1\t10\t20\t60\t16\t96.1\tContact:',
+      '5\t1\t1\t1\t1\t2\t75\t20\t40\t16\t95.0\t ',
+    ].join('\n');
+
+    expect(parseTesseractTsv(tsv)).toEqual([
+      { text: 'Contact:', line: '1.1.1.1', left: 10, top: 20, width: 60, height: 16 },
+    ]);
+  });
+});
+
+describe('findPii', () => {
+  const line = (texts: string[], top = 0, lineId = '1'): OcrWord[] =>
+    texts.map((text, i) => ({ text, line: lineId, left: i * 100, top, width: 90, height: 20 }));
+
+  test('should find emails and API keys', () => {
+    const words = [
+      ...line(['Mail', 'jane.doe@example.com', 'now'], 0, 'a'),
+      ...line(['key:', 'sk-ant-REDACTED'], 40, 'b'),
+    ];
+
+    expect(findPii(words)).toEqual([
+      { x: 100, y: 0, width: 90, height: 20 },
+      { x: 100, y: 40, width: 90, height: 20 },
+    ]);
+  });
+
+  test('should cover card numbers split across words', () => {
+    const words = line(['Card', '4111', '1111', '1111', '1111']);
+
+    expect(findPii(words)).toEqual([{ x: 100, y: 0, width: 390, height: 20 }]);
+  });
+
+  test('should ignore long numbers that are not card numbers', () => {
+    expect(findPii(line(['Order', '1234', '5678', '9012', '3456']))).toEqual([]);
+  });
+});
+
+describe('passesLuhn', () => {
+  test('should validate card checksums', () => {
+    expect(passesLuhn('4111 1111 1111 1111')).toBe(true);
+    expect(passesLuhn('4111-1111-1111-1112')).toBe(false);
+  });
+});
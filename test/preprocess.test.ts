@@ -0,0 +1,31 @@
+import sharp from 'sharp';
+import { isPreprocessStep, preprocessImage } from '../src/preprocess';
+
+describe('preprocessImage', () => {
+  const gradient = async () => {
+    // A dim, low-contrast gray strip: values 40-80
+    const raw = Buffer.alloc(64 * 3);
+    for (let x = 0; x < 64; x++) {
+      raw.fill(40 + Math.round(x * 40 / 63), x * 3, x * 3 + 3);
+    }
+    return sharp(raw, { raw: { width: 64, height: 1, channels: 3 } }).png().toBuffer();
+  };
+
+  test('should stretch contrast', async () => {
+    const stretched = await preprocessImage(await gradient(), ['contrast']);
+    const stats = await sharp(stretched).stats();
+
+    expect(stats.channels[0].max - stats.channels[0].min).toBeGreaterThan(150);
+  });
+
+  test('should convert to grayscale', async () => {
+    const gray = await preprocessImage(await gradient(), ['grayscale']);
+
+    expect((await sharp(gray).metadata()).channels).toBe(1);
+  });
+
+  test('should recognize step names', () => {
+    expect(isPreprocessStep('sharpen')).toBe(true);
+    expect(isPreprocessStep('invert')).toBe(false);
+  });
+});
@@ -0,0 +1,50 @@
+import sharp from 'sharp';
+import { applyMasks, isMaskArea, maskRects } from '../src/privacy_mask';
+
+describe('maskRects', () => {
+  test('should place named areas in logical pixels', () => {
+    expect(maskRects(['menu bar'], [], 2880, 1800, 2, 'darwin')).toEqual([
+      { x: 0, y: 0, width: 2880, height: 76 },
+    ]);
+    expect(maskRects(['Taskbar'], [], 1920, 1080, 1, 'win32')).toEqual([
+      { x: 0, y: 1032, width: 1920, height: 48 },
+    ]);
+  });
+
+  test('should put notifications where the platform shows them', () => {
+    expect(maskRects(['notifications'], [], 1920, 1080, 1, 'darwin')[0]).toMatchObject({ x: 1500, y: 38 });
+    expect(maskRects(['notifications'], [], 1920, 1080, 1, 'win32')[0]).toMatchObject({ x: 1500, y: 772 });
+  });
+
+  test('should count negative coordinates from the right and bottom', () => {
+    expect(maskRects([], [{ x: -100, y: -50, width: 100, height: 50 }], 800, 600)).toEqual([
+      { x: 700, y: 550, width: 100, height: 50 },
+    ]);
+  });
+
+  test('should clip to the image and drop rectangles outside it', () => {
+    expect(maskRects([], [{ x: 700, y: -20, width: 200, height: 40 }], 800, 600)).toEqual([
+      { x: 700, y: 580, width: 100, height: 20 },
+    ]);
+    expect(maskRects([], [{ x: 900, y: 0, width: 10, height: 10 }], 800, 600)).toEqual([]);
+  });
+
+  test('should recognize area names loosely', () => {
+    expect(isMaskArea(' Dock ')).toBe(true);
+    expect(isMaskArea('status bar')).toBe(false);
+  });
+});
+
+describe('applyMasks', () => {
+  test('should black out the rectangles', async () => {
+    const white = await sharp({ create: { width: 10, height: 10, channels: 3, background: { r: 255, g: 255, b: 255 } } })
+      .png()
+      .toBuffer();
+
+    const masked = await applyMasks(white, [{ x: 0, y: 0, width: 5, height: 10 }]);
+    const { data } = await sharp(masked).removeAlpha().raw().toBuffer({ resolveWithObject: true });
+
+    expect(data[0]).toBe(0);
+    expect(data[9 * 3]).toBe(255);
+  });
+});
@@ -0,0 +1,96 @@
+import { AnswerProgress, StageTimer, estimateTokens, formatBytes, progressText } from '../src/progress';
+
+describe('progressText', () => {
+  test('should show the time before the first token', () => {
+    expect(progressText(0, 1234, 0, '', 80)).toBe('⠋ 1.2s · waiting for the first token');
+  });
+
+  test('should show the token estimate and the partial line', () => {
+    expect(progressText(1, 3200, 600, 'The button is disabled because', 80)).toBe('⠙ 3.2s · ~150 tokens · The button is disabled because');
+  });
+
+  test('should keep the newest words when the line is too long', () => {
+    const text = progressText(0, 3200, 600, 'The button is disabled because the form is still invalid', 40);
+    expect(text).toBe('⠋ 3.2s · ~150 tokens · …still invalid');
+    expect(text.length).toBeLessThan(40);
+  });
+});
+
+describe('progressText with a stage and ETA', () => {
+  test('should show the stage until the first token, then the tokens', () => {
+    expect(progressText(0, 800, 0, '', 80, { stage: 'uploading 412 KB', etaMs: 3200 })).toBe('⠋ 0.8s · uploading 412 KB · ETA ~4s');
+    expect(progressText(0, 4100, 40, 'Done', 80, { stage: 'receiving', etaMs: 0 })).toBe('⠋ 4.1s · ~10 tokens · ETA <1s · Done');
+  });
+});
+
+describe('StageTimer', () => {
+  test('should time each stage, the last one until the end', () => {
+    const stages = new StageTimer();
+    stages.enter('capturing', 0);
+    stages.enter('encoding', 400);
+    stages.enter('uploading', 600);
+    stages.bytes = 421888;
+    stages.enter('waiting', 1200);
+    stages.enter('receiving', 3500);
+    stages.enter('rendering', 4600);
+
+    expect(stages.label()).toBe('rendering');
+    expect(stages.durations(4610)).toEqual({ capturing: 400, encoding: 200, uploading: 600, waiting: 2300, receiving: 1100, rendering: 10 });
+    expect(stages.summary(4610)).toBe('capture 0.4s · encode 0.2s · upload 412 KB 0.6s · model 2.3s · answer 1.1s · render 0.0s');
+  });
+
+  test('should add up repeated stages and name the upload size', () => {
+    const stages = new StageTimer();
+    stages.enter('capturing', 0);
+    stages.enter('encoding', 100);
+    stages.enter('capturing', 300);
+    stages.enter('capturing', 350);
+    stages.enter('encoding', 400);
+    stages.enter('uploading', 500);
+    stages.bytes = 2048;
+
+    expect(stages.durations(900)).toEqual({ capturing: 200, encoding: 300, uploading: 400 });
+    expect(stages.label()).toBe('uploading 2 KB');
+  });
+
+  test('should still report the size when the upload was not timed', () => {
+    const stages = new StageTimer();
+    stages.bytes = 3 * 1024 * 1024;
+    stages.enter('waiting', 0);
+
+    expect(stages.summary(1500)).toBe('model 1.5s · 3.0 MB sent');
+    stages.reset();
+    expect(stages.current()).toBeUndefined();
+    expect(stages.summary()).toBe('');
+  });
+});
+
+describe('formatBytes', () => {
+  test('should use bytes, kilobytes or megabytes', () => {
+    expect(formatBytes(512)).toBe('512 B');
+    expect(formatBytes(421888)).toBe('412 KB');
+    expect(formatBytes(5 * 1024 * 1024 + 1)).toBe('5.0 MB');
+  });
+});
+
+describe('estimateTokens', () => {
+  test('should round up to whole tokens', () => {
+    expect(estimateTokens(0)).toBe(0);
+    expect(estimateTokens(9)).toBe(3);
+  });
+});
+
+describe('AnswerProgress', () => {
+  test('should write nothing when disabled', () => {
+    const write = jest.spyOn(process.stdout, 'write').mockImplementation(() => true);
+    const progress = new AnswerProgress(false);
+
+    progress.start();
+    progress.add('Some text\nmore');
+    progress.draw();
+    progress.stop();
+
+    expect(write).not.toHaveBeenCalled();
+    write.mockRestore();
+  });
+});
@@ -0,0 +1,60 @@
+import { RateLimitError } from '../src/errors';
+import { QueuedTrigger, RateLimitQueue } from '../src/rate_limit_queue';
+
+describe('RateLimitQueue', () => {
+  const trigger = (question: string): QueuedTrigger => ({
+    triggeredAt: new Date(),
+    screenshot: Buffer.from('image'),
+    question,
+  });
+
+  beforeEach(() => {
+    jest.useFakeTimers();
+    jest.spyOn(console, 'log').mockImplementation(() => {});
+  });
+
+  afterEach(() => {
+    jest.useRealTimers();
+    jest.restoreAllMocks();
+  });
+
+  test('should drain queued triggers in order once the limit resets', async () => {
+    const processed: string[] = [];
+    const queue = new RateLimitQueue(async item => {
+      processed.push(item.question as string);
+    });
+
+    queue.pause(new RateLimitError('limited', 5000));
+    queue.enqueue(trigger('first'));
+    queue.enqueue(trigger('second'));
+
+    expect(queue.isLimited()).toBe(true);
+    expect(queue.size()).toBe(2);
+
+    await jest.advanceTimersByTimeAsync(5000);
+
+    expect(processed).toEqual(['first', 'second']);
+    expect(queue.size()).toBe(0);
+    expect(queue.isLimited()).toBe(false);
+  });
+
+  test('should wait again when still rate limited', async () => {
+    let calls = 0;
+    const queue = new RateLimitQueue(async () => {
+      calls++;
+      if (calls === 1) {
+        throw new RateLimitError('still limited', 1000);
+      }
+    });
+
+    queue.pause(new RateLimitError('limited', 1000));
+    queue.enqueue(trigger('only'));
+
+    await jest.advanceTimersByTimeAsync(1000);
+    expect(queue.size()).toBe(1);
+
+    await jest.advanceTimersByTimeAsync(1000);
+    expect(calls).toBe(2);
+    expect(queue.size()).toBe(0);
+  });
+});
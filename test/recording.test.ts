@@ -0,0 +1,23 @@
+import { selectKeyframes } from '../src/recording';
+
+describe('selectKeyframes', () => {
+  test('should keep the first and last frame', () => {
+    expect(selectKeyframes([0, 0, 0, 0], 3)).toEqual([0, 3]);
+  });
+
+  test('should pick the frames that changed most, in order', () => {
+    const changes = [0, 0.01, 0.4, 0, 0.2, 0.05, 0.3];
+
+    expect(selectKeyframes(changes, 4)).toEqual([0, 2, 4, 6]);
+  });
+
+  test('should send every changed frame when under the limit', () => {
+    expect(selectKeyframes([0, 0.1, 0.2], 6)).toEqual([0, 1, 2]);
+  });
+
+  test('should handle tiny recordings', () => {
+    expect(selectKeyframes([], 6)).toEqual([]);
+    expect(selectKeyframes([0], 6)).toEqual([0]);
+    expect(selectKeyframes([0, 0.5], 1)).toEqual([0]);
+  });
+});
@@ -0,0 +1,27 @@
+import { redactSecrets, registerSecret } from '../src/redact';
+
+describe('redactSecrets', () => {
+  test('should mask Anthropic-style keys', () => {
+    const text = 'Claude API error: invalid x-api-key sk-ant-REDACTED';
+
+    expect(redactSecrets(text)).not.toContain('abcdefghijklmnop');
+    expect(redactSecrets(text)).toContain('[REDACTED]');
+  });
+
+  test('should mask bearer tokens and keep the scheme', () => {
+    expect(redactSecrets('Authorization: Bearer abc123def456ghi789')).toBe('Authorization: Bearer [REDACTED]');
+    expect(redactSecrets('sent Bearer abc123def456ghi789')).toBe('sent Bearer [REDACTED]');
+  });
+
+  test('should mask registered secrets of any shape', () => {
+    registerSecret('custom-secret-value');
+
+    expect(redactSecrets('key=custom-secret-value was rejected')).not.toContain('custom-secret-value');
+  });
+
+  test('should leave ordinary text alone', () => {
+    const text = 'Screenshot captured successfully: 1920x1080';
+
+    expect(redactSecrets(text)).toBe(text);
+  });
+});
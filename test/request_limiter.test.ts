@@ -0,0 +1,61 @@
+import { RequestDroppedError } from '../src/errors';
+import { RequestLimiter } from '../src/request_limiter';
+
+describe('RequestLimiter', () => {
+  const deferred = () => {
+    let resolve!: (value: string) => void;
+    const promise = new Promise<string>(r => (resolve = r));
+    return { promise, resolve };
+  };
+
+  beforeEach(() => {
+    jest.spyOn(console, 'log').mockImplementation(() => {});
+  });
+
+  afterEach(() => {
+    jest.restoreAllMocks();
+  });
+
+  test('should queue requests over the limit and run them in order', async () => {
+    const limiter = new RequestLimiter(1, 2);
+    const first = deferred();
+    const started: string[] = [];
+
+    const a = limiter.run(() => {
+      started.push('a');
+      return first.promise;
+    });
+    const b = limiter.run(async () => {
+      started.push('b');
+      return 'b';
+    });
+
+    expect(started).toEqual(['a']);
+    expect(limiter.active()).toBe(1);
+    expect(limiter.queued()).toBe(1);
+
+    first.resolve('a');
+    await expect(a).resolves.toBe('a');
+    await expect(b).resolves.toBe('b');
+    expect(started).toEqual(['a', 'b']);
+    expect(limiter.active()).toBe(0);
+  });
+
+  test('should drop requests once the queue is full', async () => {
+    const limiter = new RequestLimiter(1, 0);
+    const first = deferred();
+
+    const a = limiter.run(() => first.promise);
+    await expect(limiter.run(async () => 'b')).rejects.toThrow(RequestDroppedError);
+
+    first.resolve('a');
+    await expect(a).resolves.toBe('a');
+  });
+
+  test('should free the slot when a request fails', async () => {
+    const limiter = new RequestLimiter(1, 0);
+
+    await expect(limiter.run(async () => { throw new Error('boom'); })).rejects.toThrow('boom');
+    await expect(limiter.run(async () => 'ok')).resolves.toBe('ok');
+  });
+});
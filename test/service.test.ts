@@ -0,0 +1,49 @@
+import { ServiceSpec, desktopEntry, launchdPlist, systemdUnit } from '../src/service';
+
+const spec: ServiceSpec = {
+  command: ['/usr/bin/node', '/opt/My Apps/analyzer/dist/main.js', 'run', '--mode', 'hotkey'],
+  env: { PATH: '/usr/bin:/bin', AI_API_KEY: 'sk-a&b$c' },
+  logFile: '/home/me/.local/state/ai-screenshot-analyzer/daemon.log',
+  errorLogFile: '/home/me/.local/state/ai-screenshot-analyzer/daemon.err.log',
+};
+
+describe('launchdPlist', () => {
+  test('should run the command at load with the environment and logs', () => {
+    const plist = launchdPlist(spec);
+
+    expect(plist).toContain('<string>/opt/My Apps/analyzer/dist/main.js</string>');
+    expect(plist).toContain('<key>AI_API_KEY</key>\n    <string>sk-a&amp;b$c</string>');
+    expect(plist).toContain('<key>RunAtLoad</key>\n  <true/>');
+    expect(plist).toContain('<key>StandardOutPath</key>\n  <string>/home/me/.local/state/ai-screenshot-analyzer/daemon.log</string>');
+    expect(plist).toContain('<key>KeepAlive</key>');
+  });
+
+  test('should run once for a login item', () => {
+    const plist = launchdPlist(spec, 'com.ai-screenshot-analyzer.login', false);
+
+    expect(plist).toContain('<string>com.ai-screenshot-analyzer.login</string>');
+    expect(plist).not.toContain('KeepAlive');
+  });
+});
+
+describe('systemdUnit', () => {
+  test('should quote arguments and escape $ in the environment', () => {
+    const unit = systemdUnit(spec);
+
+    expect(unit).toContain('ExecStart=/usr/bin/node "/opt/My Apps/analyzer/dist/main.js" run --mode hotkey\n');
+    expect(unit).toContain('Environment="AI_API_KEY=sk-a&b$$c"\n');
+    expect(unit).toContain('StandardOutput=append:/home/me/.local/state/ai-screenshot-analyzer/daemon.log\n');
+    expect(unit).toContain('WantedBy=graphical-session.target');
+  });
+});
+
+describe('desktopEntry', () => {
+  test('should pass the environment through env and quote arguments', () => {
+    const entry = desktopEntry(spec);
+
+    expect(entry).toContain(
+      'Exec=env PATH=/usr/bin:/bin "AI_API_KEY=sk-a&b\\\\$c" /usr/bin/node "/opt/My Apps/analyzer/dist/main.js" run --mode hotkey\n'
+    );
+    expect(entry).toContain('Terminal=false');
+  });
+});
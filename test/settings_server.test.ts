@@ -0,0 +1,67 @@
+import { AppConfig } from '../src/config';
+import { applySettings } from '../src/settings_server';
+
+describe('applySettings', () => {
+  function config(): AppConfig {
+    const current = new AppConfig({
+      defaultProvider: 'openai',
+      model: 'gpt-4o',
+      apiKey: 'sk-old',
+      responseLanguage: 'German',
+      maxTokens: 800,
+      copyAnswers: true,
+    });
+    current.save = jest.fn().mockResolvedValue(undefined);
+    return current;
+  }
+
+  test('should save changed values', async () => {
+    const current = config();
+    const form = new URLSearchParams({ defaultProvider: 'gemini', model: 'gemini-1.5-pro', apiKey: 'key-new', maxTokens: '1200', copyAnswers: 'on' });
+
+    expect(await applySettings(current, form)).toBe('✅ Settings saved');
+    expect(current).toMatchObject({ defaultProvider: 'gemini', model: 'gemini-1.5-pro', apiKey: 'key-new', maxTokens: 1200, copyAnswers: true });
+    expect(current.save).toHaveBeenCalled();
+  });
+
+  test('should clear blank fields back to their defaults', async () => {
+    const current = config();
+    const form = new URLSearchParams({ defaultProvider: '', model: ' ', apiKey: '', responseLanguage: '', maxTokens: '' });
+
+    await applySettings(current, form);
+    expect(current.defaultProvider).toBe('claude');
+    expect(current.model).toBeUndefined();
+    expect(current.responseLanguage).toBeUndefined();
+    expect(current.maxTokens).toBeUndefined();
+    expect(current.copyAnswers).toBeUndefined();
+    // The key is never shown, so blank keeps it
+    expect(current.apiKey).toBe('sk-old');
+  });
+
+  test('should reject numbers that aren\'t positive without saving', async () => {
+    const current = config();
+
+    expect(await applySettings(current, new URLSearchParams({ maxTokens: '-5' }))).toContain('must be a positive number');
+    expect(current.maxTokens).toBe(800);
+    expect(current.save).not.toHaveBeenCalled();
+  });
+
+  test('should reject providers and themes that aren\'t offered', async () => {
+    const current = config();
+
+    expect(await applySettings(current, new URLSearchParams({ defaultProvider: 'gpt' }))).toContain('must be one of claude, openai');
+    expect(await applySettings(current, new URLSearchParams({ theme: 'neon' }))).toContain('must be one of dark, light, solarized');
+    expect(current.defaultProvider).toBe('openai');
+    expect(current.save).not.toHaveBeenCalled();
+  });
+
+  test('should only accept a left or right modifier as the double-tap key', async () => {
+    const current = config();
+
+    expect(await applySettings(current, new URLSearchParams({ doubleTapKey: 'cmd' }))).toContain('Use a left or right modifier');
+    expect(current.save).not.toHaveBeenCalled();
+
+    expect(await applySettings(current, new URLSearchParams({ doubleTapKey: 'right cmd' }))).toBe('✅ Settings saved');
+    expect(current.doubleTapKey).toBe('right cmd');
+  });
+});
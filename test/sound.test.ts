@@ -0,0 +1,17 @@
+import { soundCommands } from '../src/sound';
+
+describe('soundCommands', () => {
+  test('should play the system sound for each cue', () => {
+    expect(soundCommands('capture', undefined, 'darwin')).toEqual([['afplay', ['/System/Library/Sounds/Tink.aiff']]]);
+    expect(soundCommands('done', undefined, 'linux').map(([command]) => command)).toEqual(['paplay', 'ffplay']);
+  });
+
+  test('should prefer a configured file', () => {
+    expect(soundCommands('done', '/home/me/ding.wav', 'linux')[0]).toEqual(['paplay', ['/home/me/ding.wav']]);
+    expect(soundCommands('error', "C:\\Sounds\\it's.wav", 'win32')[0][1][2]).toBe("(New-Object Media.SoundPlayer 'C:\\Sounds\\it''s.wav').PlaySync()");
+  });
+
+  test('should have nothing to play on unknown platforms', () => {
+    expect(soundCommands('capture', undefined, 'aix')).toEqual([]);
+  });
+});
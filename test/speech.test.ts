@@ -0,0 +1,27 @@
+import { speechCommands, speechText } from '../src/speech';
+
+describe('speechText', () => {
+  test('should drop markdown markup and read links by their label', () => {
+    expect(speechText('## The Fix ##\n\nThe **button** is disabled because `isValid` is *false*. See [the docs](https://x.io).'))
+      .toBe('The Fix\n\nThe button is disabled because isValid is false. See the docs.');
+  });
+
+  test('should read list items, quotes and table rows plainly', () => {
+    expect(speechText('- [ ] check the form\n2. Run it\n> Note this\n\n---\n\n| Name | Qty |\n|:--|--:|\n| apple | 3 |'))
+      .toBe('check the form\nRun it\nNote this\n\nName, Qty\napple, 3');
+  });
+
+  test('should summarize code blocks instead of reading them', () => {
+    expect(speechText('Use this:\n```ts\nconst a = 1;\nconst b = 2;\n```\nDone.'))
+      .toBe('Use this:\n(ts code, 2 lines, shown on screen.)\nDone.');
+    expect(speechText('Truncated:\n~~~\nx')).toBe('Truncated:\n(code, 1 line, shown on screen.)');
+  });
+});
+
+describe('speechCommands', () => {
+  test('should use say, SAPI or espeak', () => {
+    expect(speechCommands('darwin')).toEqual([['say', ['-f', '-']]]);
+    expect(speechCommands('win32')[0][1][2]).toContain('System.Speech.Synthesis.SpeechSynthesizer');
+    expect(speechCommands('linux').map(([command]) => command)).toEqual(['espeak-ng', 'espeak', 'spd-say']);
+  });
+});
@@ -0,0 +1,47 @@
+import { ConfigError } from '../src/errors';
+import { THEMES, THEME_ROLES, isThemeColor, resolveTheme } from '../src/theme';
+
+describe('isThemeColor', () => {
+  test('should accept color names, hex codes and default', () => {
+    expect(isThemeColor('cyan')).toBe(true);
+    expect(isThemeColor('redBright')).toBe(true);
+    expect(isThemeColor('#268bd2')).toBe(true);
+    expect(isThemeColor('#fff')).toBe(true);
+    expect(isThemeColor('default')).toBe(true);
+    expect(isThemeColor('teal')).toBe(false);
+    expect(isThemeColor('#12345')).toBe(false);
+  });
+});
+
+describe('resolveTheme', () => {
+  test('should default to the dark preset', () => {
+    expect(resolveTheme()).toEqual(THEMES.dark);
+  });
+
+  test('should give every preset a valid color for every role', () => {
+    for (const theme of Object.values(THEMES)) {
+      for (const role of THEME_ROLES) {
+        expect(isThemeColor(theme[role])).toBe(true);
+      }
+    }
+  });
+
+  test('should keep white and yellow out of the light preset', () => {
+    expect(Object.values(THEMES.light)).not.toContain('white');
+    expect(Object.values(THEMES.light)).not.toContain('yellow');
+  });
+
+  test('should override single colors of the preset', () => {
+    const theme = resolveTheme('light', { error: '#d70000', code_background: 'default' });
+
+    expect(theme.error).toBe('#d70000');
+    expect(theme.code_background).toBe('default');
+    expect(theme.header).toBe(THEMES.light.header);
+  });
+
+  test('should reject unknown presets, roles and colors', () => {
+    expect(() => resolveTheme('neon')).toThrow(ConfigError);
+    expect(() => resolveTheme('dark', { banner: 'red' })).toThrow(/Unknown theme color "banner"/);
+    expect(() => resolveTheme('dark', { error: 'crimson' })).toThrow(/Invalid color for error/);
+  });
+});
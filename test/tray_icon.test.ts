@@ -0,0 +1,19 @@
+import { pngToIco } from '../src/tray_icon';
+
+describe('pngToIco', () => {
+  test('should write a single-image ICO header pointing at the PNG', () => {
+    const png = Buffer.from([0x89, 0x50, 0x4e, 0x47]);
+    const ico = pngToIco(png, 32);
+
+    expect(ico.readUInt16LE(2)).toBe(1);
+    expect(ico.readUInt16LE(4)).toBe(1);
+    expect(ico[6]).toBe(32);
+    expect(ico.readUInt32LE(14)).toBe(png.length);
+    expect(ico.readUInt32LE(18)).toBe(22);
+    expect(ico.subarray(22)).toEqual(png);
+  });
+
+  test('should store 256px as 0', () => {
+    expect(pngToIco(Buffer.alloc(1), 256)[6]).toBe(0);
+  });
+});
@@ -0,0 +1,70 @@
+import { TriggerQueue } from '../src/trigger_queue';
+
+describe('TriggerQueue', () => {
+  beforeEach(() => {
+    jest.spyOn(console, 'log').mockImplementation(() => {});
+  });
+
+  afterEach(() => {
+    jest.restoreAllMocks();
+  });
+
+  const deferred = () => {
+    let resolve: () => void = () => {};
+    const promise = new Promise<void>(r => {
+      resolve = r;
+    });
+    return { promise, resolve };
+  };
+
+  test('should run one trigger at a time', async () => {
+    const queue = new TriggerQueue();
+    const first = deferred();
+    const order: string[] = [];
+
+    const firstRun = queue.run('hotkey', async () => {
+      order.push('first start');
+      await first.promise;
+      order.push('first end');
+    });
+    const secondRun = queue.run('tray', async () => {
+      order.push('second');
+    });
+
+    expect(queue.isBusy()).toBe(true);
+    first.resolve();
+
+    await expect(firstRun).resolves.toBe(true);
+    await expect(secondRun).resolves.toBe(true);
+    expect(order).toEqual(['first start', 'first end', 'second']);
+    expect(queue.isBusy()).toBe(false);
+  });
+
+  test('should keep only the newest waiting trigger', async () => {
+    const queue = new TriggerQueue();
+    const first = deferred();
+    const ran: string[] = [];
+
+    queue.run('first', () => first.promise);
+    const replaced = queue.run('second', async () => {
+      ran.push('second');
+    });
+    const newest = queue.run('third', async () => {
+      ran.push('third');
+    });
+
+    await expect(replaced).resolves.toBe(false);
+    first.resolve();
+    await expect(newest).resolves.toBe(true);
+    expect(ran).toEqual(['third']);
+  });
+
+  test('should pass failures to the caller and carry on', async () => {
+    const queue = new TriggerQueue();
+
+    await expect(queue.run('broken', async () => {
+      throw new Error('capture failed');
+    })).rejects.toThrow('capture failed');
+    await expect(queue.run('next', async () => {})).resolves.toBe(true);
+  });
+});
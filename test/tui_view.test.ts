@@ -0,0 +1,67 @@
+import { TuiEntry, TuiView, answerLines, maxScroll, renderTui, wrapText } from '../src/tui_view';
+
+const entry = (question: string, answer: string, status: TuiEntry['status'] = 'done'): TuiEntry => ({
+  question,
+  source: 'screen',
+  at: new Date(2025, 0, 31, 14, 5, 9),
+  answer,
+  status
+});
+
+const view = (entries: TuiEntry[], overrides: Partial<TuiView> = {}): TuiView => ({
+  entries,
+  selected: entries.length - 1,
+  scroll: 0,
+  input: '',
+  source: 'screen',
+  status: 'Ready',
+  ...overrides
+});
+
+describe('wrapText', () => {
+  test('should wrap on words and keep blank lines and indentation', () => {
+    expect(wrapText('the quick brown fox\n\n  jumps over it', 10)).toEqual(['the quick', 'brown fox', '', '  jumps', '  over it']);
+  });
+
+  test('should split words longer than the width', () => {
+    expect(wrapText('abcdefghij', 4)).toEqual(['abcd', 'efgh', 'ij']);
+  });
+});
+
+describe('answerLines', () => {
+  test('should style code blocks and show the question first', () => {
+    const lines = answerLines(entry('Fix?', 'Use:\n```js\nx++\n```'), 40);
+    expect(lines.map(line => line.style)).toEqual(['question', 'text', 'text', 'fence', 'code', 'fence']);
+    expect(lines[0].text).toBe('Q: Fix?');
+  });
+
+  test('should show progress before the first token', () => {
+    expect(answerLines(entry('Fix?', '', 'analyzing'), 40)[2]).toEqual({ text: 'Waiting for the answer…', style: 'muted' });
+  });
+});
+
+describe('renderTui', () => {
+  test('should fill the screen with rows of exactly the terminal width', () => {
+    const screen = renderTui(view([entry('First', 'One'), entry('Second', 'Two')]), 60, 10);
+    expect(screen).toHaveLength(10);
+    for (const row of screen) {
+      expect(row.map(segment => segment.text).join('')).toHaveLength(60);
+    }
+    expect(screen[screen.length - 1][0].text.trimEnd()).toBe('screen ›');
+  });
+
+  test('should list the newest capture first and highlight the selected one', () => {
+    const screen = renderTui(view([entry('First', 'One'), entry('Second', 'Two')], { selected: 0 }), 60, 10);
+    expect(screen[1][0]).toMatchObject({ style: 'muted' });
+    expect(screen[1][0].text).toContain('Second');
+    expect(screen[2][0]).toMatchObject({ style: 'selected' });
+    expect(screen[1][2].text.trimEnd()).toBe('Q: First');
+  });
+
+  test('should scroll the answer pane', () => {
+    const long = view([entry('Long', Array.from({ length: 30 }, (_, i) => `line ${i}`).join('\n'))]);
+    expect(maxScroll(long, 60, 10)).toBe(32 - 7);
+    const screen = renderTui({ ...long, scroll: 5 }, 60, 10);
+    expect(screen[1][2].text.trimEnd()).toBe('line 3');
+  });
+});
@@ -0,0 +1,75 @@
+import { CancelledError, ConfigError } from '../src/errors';
+import { DEFAULT_CALLBACK_SCHEMES, callbackUrl, confirmSnapperUrl, handlerAppleScript, parseSnapperUrl, shellQuote } from '../src/url_scheme';
+
+describe('parseSnapperUrl', () => {
+  test('should read the prompt, source and callbacks', () => {
+    expect(parseSnapperUrl('aisnapper://capture?prompt=Why%20is%20this%20red%3F&source=active-window&x-success=shortcuts%3A%2F%2Fx-callback-url%2Fdone')).toEqual({
+      prompt: 'Why is this red?',
+      source: 'active-window',
+      xSuccess: 'shortcuts://x-callback-url/done',
+      xError: undefined,
+      xCancel: undefined,
+    });
+  });
+
+  test('should accept the x-callback-url form and default to the screen', () => {
+    expect(parseSnapperUrl('aisnapper://x-callback-url/capture')).toEqual({
+      prompt: undefined,
+      source: 'screen',
+      xSuccess: undefined,
+      xError: undefined,
+      xCancel: undefined,
+    });
+  });
+
+  test('should reject other schemes, actions and sources', () => {
+    expect(() => parseSnapperUrl('https://capture?prompt=x')).toThrow(ConfigError);
+    expect(() => parseSnapperUrl('aisnapper://delete')).toThrow(ConfigError);
+    expect(() => parseSnapperUrl('aisnapper://capture?source=window')).toThrow(ConfigError);
+    expect(() => parseSnapperUrl('not a url')).toThrow(ConfigError);
+  });
+
+  test('should refuse web and file callbacks', () => {
+    expect(() => parseSnapperUrl('aisnapper://capture?x-success=https%3A%2F%2Fattacker.example%2Fcollect')).toThrow(ConfigError);
+    expect(() => parseSnapperUrl('aisnapper://capture?x-error=HTTP%3A%2F%2Fattacker.example%2F')).toThrow(ConfigError);
+    expect(() => parseSnapperUrl('aisnapper://capture?x-cancel=file%3A%2F%2F%2Ftmp%2Fx')).toThrow(ConfigError);
+    expect(() => parseSnapperUrl('aisnapper://capture?x-success=not%20a%20url')).toThrow(ConfigError);
+  });
+});
+
+describe('confirmSnapperUrl', () => {
+  test('should not ask for allowed callbacks or none', async () => {
+    const confirm = jest.fn();
+    await confirmSnapperUrl(parseSnapperUrl('aisnapper://capture?x-success=shortcuts%3A%2F%2Fx-callback-url%2Fdone'), DEFAULT_CALLBACK_SCHEMES, confirm);
+    await confirmSnapperUrl(parseSnapperUrl('aisnapper://capture?prompt=hi'), [], confirm);
+    expect(confirm).not.toHaveBeenCalled();
+  });
+
+  test('should ask before capturing for other apps', async () => {
+    const request = parseSnapperUrl('aisnapper://capture?x-success=otherapp%3A%2F%2Fdone&x-error=shortcuts%3A%2F%2Ferr');
+    const confirm = jest.fn().mockResolvedValue(true);
+
+    await confirmSnapperUrl(request, DEFAULT_CALLBACK_SCHEMES, confirm);
+    expect(confirm).toHaveBeenCalledWith('A link wants to capture your screen and send the answer to otherapp://. Allow it?');
+    await expect(confirmSnapperUrl(request, ['shortcuts'], jest.fn().mockResolvedValue(false))).rejects.toThrow(CancelledError);
+    await expect(confirmSnapperUrl(request, ['OtherApp', 'shortcuts'], jest.fn().mockResolvedValue(false))).resolves.toBeUndefined();
+  });
+});
+
+describe('callbackUrl', () => {
+  test('should add the result to the callback query', () => {
+    expect(callbackUrl('raycast://extensions/done?id=1', { result: 'a & b' })).toBe('raycast://extensions/done?id=1&result=a+%26+b');
+  });
+});
+
+describe('handlerAppleScript', () => {
+  test('should pass the URL quoted to open-url', () => {
+    expect(shellQuote("it's")).toBe(`'it'\\''s'`);
+    expect(handlerAppleScript(['/usr/bin/node', '/opt/app/main.js', 'open-url'], '/tmp/url.log')).toBe([
+      'on open location theURL',
+      `    do shell script "'/usr/bin/node' '/opt/app/main.js' 'open-url' " & quoted form of theURL & " >> '/tmp/url.log' 2>&1 &"`,
+      'end open location',
+      '',
+    ].join('\n'));
+  });
+});
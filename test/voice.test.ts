@@ -0,0 +1,30 @@
+import * as os from 'os';
+import * as path from 'path';
+import { cleanTranscript, parseCommandLine, recorderCommands } from '../src/voice';
+
+describe('parseCommandLine', () => {
+  test('should split on spaces outside quotes and expand ~/', () => {
+    expect(parseCommandLine('whisper-cli -m "~/models/ggml base.bin" -nt -f')).toEqual([
+      'whisper-cli', '-m', path.join(os.homedir(), 'models/ggml base.bin'), '-nt', '-f',
+    ]);
+    expect(parseCommandLine(`transcribe --lang 'en'`)).toEqual(['transcribe', '--lang', 'en']);
+  });
+});
+
+describe('cleanTranscript', () => {
+  test('should drop whisper timestamps and markers', () => {
+    expect(cleanTranscript('[00:00:00.000 --> 00:00:02.000]  Why is this test\n[00:00:02.000 --> 00:00:03.500]  failing?\n')).toBe('Why is this test failing?');
+    expect(cleanTranscript(' [BLANK_AUDIO]\n')).toBe('');
+  });
+});
+
+describe('recorderCommands', () => {
+  test('should try sox first, then ffmpeg with the platform device', () => {
+    const commands = recorderCommands('/tmp/q.wav', 5, 'darwin');
+
+    expect(commands.map(([command]) => command)).toEqual(['rec', 'ffmpeg']);
+    expect(commands[0][1]).toEqual(['-q', '-c', '1', '-r', '16000', '/tmp/q.wav', 'trim', '0', '5']);
+    expect(commands[1][1]).toContain('avfoundation');
+    expect(recorderCommands('/tmp/q.wav', 5, 'win32')).toHaveLength(1);
+  });
+});
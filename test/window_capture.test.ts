@@ -0,0 +1,62 @@
+import { findBlockedWindows, matchWindow, parseWmctrl, WindowInfo } from '../src/window_capture';
+
+describe('parseWmctrl', () => {
+  test('should read the id, window class and title', () => {
+    const output = [
+      '0x03e00003  0 code.Code             laptop main.rs - crate - Visual Studio Code',
+      '0x04a0000b -1 xfce4-panel.Xfce4-panel  laptop xfce4-panel',
+      '0x05000001  1 Navigator.firefox     laptop '
+    ].join('\n');
+
+    expect(parseWmctrl(output)).toEqual([
+      { id: '0x03e00003', app: 'Code', title: 'main.rs - crate - Visual Studio Code' },
+      { id: '0x04a0000b', app: 'Xfce4-panel', title: 'xfce4-panel' },
+      { id: '0x05000001', app: 'firefox', title: '' }
+    ]);
+  });
+
+  test('should read the geometry from -lGx output', () => {
+    const output = '0x03e00003  0 -1920 27   1920 1053 code.Code  laptop main.rs - Visual Studio Code';
+
+    expect(parseWmctrl(output)).toEqual([
+      {
+        id: '0x03e00003',
+        app: 'Code',
+        title: 'main.rs - Visual Studio Code',
+        bounds: { x: -1920, y: 27, width: 1920, height: 1053 }
+      }
+    ]);
+  });
+});
+
+describe('findBlockedWindows', () => {
+  test('should match app names case-insensitively', () => {
+    const windows: WindowInfo[] = [
+      { id: '1', app: '1Password', title: 'Vault' },
+      { id: '2', app: 'Keychain Access', title: 'login' },
+      { id: '3', app: 'Safari', title: '1Password - Sign in' }
+    ];
+
+    expect(findBlockedWindows(windows, ['1password', ' Keychain ']).map(w => w.id)).toEqual(['1', '2']);
+    expect(findBlockedWindows(windows, [''])).toEqual([]);
+  });
+});
+
+describe('matchWindow', () => {
+  const windows: WindowInfo[] = [
+    { id: '1', app: 'Terminal', title: 'code review notes' },
+    { id: '2', app: 'Code', title: 'main.ts - Visual Studio Code' },
+    { id: '3', app: 'Safari', title: 'Docs' }
+  ];
+
+  test('should prefer an exact app or title match over a substring', () => {
+    expect(matchWindow(windows, 'code')?.id).toBe('2');
+    expect(matchWindow(windows, 'docs')?.id).toBe('3');
+  });
+
+  test('should fall back to the first case-insensitive substring match', () => {
+    expect(matchWindow(windows, 'visual studio code')?.id).toBe('2');
+    expect(matchWindow(windows, 'REVIEW')?.id).toBe('1');
+    expect(matchWindow(windows, 'Slack')).toBeUndefined();
+  });
+});